@@ -1,11 +1,18 @@
 //! Axum [Extractors](axum::extract) for extracting data from requests for the controller.
 
+use crate::{
+    Error,
+    model::{Page, ReadReplica, csv, page::Pagination},
+};
+
 use axum::{
     RequestExt,
-    extract::{FromRequest, OptionalFromRequest},
-    http::StatusCode,
+    body::Body,
+    extract::{FromRequest, FromRequestParts, OptionalFromRequest},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use futures::{Stream, StreamExt};
 use serde::{Serialize, de::DeserializeOwned};
 
 /// JSON [Extractor](axum::extract) / [Response](axum::response).
@@ -115,6 +122,187 @@ where
     }
 }
 
+impl<T> IntoResponse for Page<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        /// The records in an envelope with the pagination.
+        #[derive(Serialize)]
+        struct Envelope<T> {
+            data: Vec<T>,
+            #[serde(flatten)]
+            pagination: Pagination,
+        }
+
+        let Self {
+            data,
+            next_cursor,
+            pagination,
+        } = self;
+        let mut response = match pagination {
+            Some(pagination) if pagination.is_envelope => {
+                Json(Envelope { data, pagination }).into_response()
+            }
+            Some(pagination) => {
+                let mut response = Json(data).into_response();
+                let headers = response.headers_mut();
+                headers.insert("x-total-count", HeaderValue::from(pagination.total));
+                if let Ok(links) = HeaderValue::from_str(&pagination.links()) {
+                    headers.insert(axum::http::header::LINK, links);
+                }
+                response
+            }
+            None => Json(data).into_response(),
+        };
+        let next_cursor =
+            next_cursor.and_then(|next_cursor| HeaderValue::from_str(next_cursor.as_ref()).ok());
+        if let Some(next_cursor) = next_cursor {
+            response.headers_mut().insert("x-next-cursor", next_cursor);
+        }
+        response
+    }
+}
+
+/// A stream of records as a response, which is never collected in memory.
+///
+/// The records are either newline delimited JSON (`application/x-ndjson`)
+/// or a JSON array (`application/json`) in chunks.
+/// If a record fails, the response is cut off.
+#[must_use]
+pub struct JsonStream<S> {
+    /// The records.
+    pub stream: S,
+    /// If the records are newline delimited JSON instead of a JSON array.
+    pub is_ndjson: bool,
+}
+impl<S> JsonStream<S> {
+    /// Newline delimited JSON if the `Accept` header accepts it, otherwise a JSON array.
+    pub fn from_accept(stream: S, headers: &HeaderMap) -> Self {
+        Self {
+            stream,
+            is_ndjson: accepts(headers, "application/x-ndjson"),
+        }
+    }
+}
+impl<S, T> IntoResponse for JsonStream<S>
+where
+    S: Stream<Item = Result<T, Error>> + Send + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let Self { stream, is_ndjson } = self;
+        let records = stream.enumerate().map(move |(i, record)| {
+            let record = record.map_err(|err| std::io::Error::other(format!("{err:?}")))?;
+            // the records of a JSON array are separated by commas
+            let mut bytes = if is_ndjson || i == 0 {
+                vec![]
+            } else {
+                vec![b',']
+            };
+            serde_json::to_writer(&mut bytes, &record).map_err(std::io::Error::other)?;
+            if is_ndjson {
+                bytes.push(b'\n');
+            }
+            Ok::<_, std::io::Error>(bytes::Bytes::from(bytes))
+        });
+        let (content_type, body) = if is_ndjson {
+            ("application/x-ndjson", Body::from_stream(records))
+        } else {
+            let start = futures::stream::iter([Ok(bytes::Bytes::from_static(b"["))]);
+            let end = futures::stream::iter([Ok(bytes::Bytes::from_static(b"]"))]);
+            (
+                "application/json",
+                Body::from_stream(start.chain(records).chain(end)),
+            )
+        };
+        ([(header::CONTENT_TYPE, content_type)], body).into_response()
+    }
+}
+
+/// A stream of records as a CSV response (`text/csv`), which is never collected in memory.
+///
+/// The first row is the headers, which are the names of the fields.
+/// If a record fails, the response is cut off.
+#[must_use]
+pub struct CsvStream<S> {
+    /// The records.
+    pub stream: S,
+    /// The names of the fields, see [Collection::FIELDS](crate::Collection::FIELDS).
+    pub fields: &'static [&'static str],
+}
+impl<S, T> IntoResponse for CsvStream<S>
+where
+    S: Stream<Item = Result<T, Error>> + Send + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let Self { stream, fields } = self;
+        let headers = futures::stream::iter([csv::headers(fields)]);
+        let records = stream.map(move |record| csv::row(&record?, fields));
+        let rows = headers.chain(records).map(|row| {
+            row.map(bytes::Bytes::from)
+                .map_err(|err| std::io::Error::other(format!("{err:?}")))
+        });
+        (
+            [(header::CONTENT_TYPE, "text/csv")],
+            Body::from_stream(rows),
+        )
+            .into_response()
+    }
+}
+
+/// If the `Accept` header accepts the media type, like `text/csv`.
+#[must_use]
+pub fn accepts(headers: &HeaderMap, media_type: &str) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| {
+            accept
+                .split(',')
+                .any(|media| media.split(';').next().map(str::trim) == Some(media_type))
+        })
+}
+
+/// [Extractor](axum::extract) for reading from the primary instead of the read replica.
+///
+/// It is `true` if the request has an `X-Read-Primary` header that isn't `false` or `0`,
+/// like right after a write, because a read replica can lag behind the primary.
+#[must_use]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReadPrimary(pub bool);
+impl ReadPrimary {
+    /// From the `X-Read-Primary` header.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let read_primary = headers
+            .get("x-read-primary")
+            .is_some_and(|value| value != "false" && value != "0");
+        Self(read_primary)
+    }
+    /// The primary database if the request reads from it, see [ReadReplica::primary].
+    ///
+    /// Read from `primary.as_ref().unwrap_or(db)`.
+    pub fn primary<Db>(self, db: &Db) -> Option<Db>
+    where
+        Db: ReadReplica,
+    {
+        self.0.then(|| db.primary())
+    }
+}
+impl<State> FromRequestParts<State> for ReadPrimary
+where
+    State: Send + Sync,
+{
+    type Rejection = core::convert::Infallible;
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _: &State,
+    ) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
 /// Is the mime type for json.
 fn is_json_mime(mime: &mime::Mime) -> bool {
     mime.type_() == "application"
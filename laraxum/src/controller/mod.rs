@@ -7,17 +7,30 @@ pub mod extract;
 mod serve;
 
 use auth::AuthToken;
-use extract::Json;
+use extract::{CsvStream, Json, JsonStream, ReadPrimary, accepts};
 
 use crate::{
     error::{Error, ModelError},
-    model::{Collection, Model, Table},
+    model::{
+        Children, Collection, ExpandQuery, Fields, FieldsQuery, Import, ManyModel, Model, Page,
+        Stats, Table, Tree,
+        csv::{self, Imported, RowError},
+        request::{Request, method::Create},
+        tree::Node,
+    },
 };
 
 use core::ops::Deref;
 use std::sync::Arc;
 
-use axum::extract::{Path, Query, State};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, RawQuery, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    routing::Router,
+};
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 
 /// Get the URL environment variable. Defaults to `"localhost:80"`.
@@ -28,6 +41,37 @@ pub fn url() -> std::borrow::Cow<'static, str> {
     crate::env::env_var_default!("URL", "localhost:80")
 }
 
+/// Stream all records of a table in a task, so the stream doesn't borrow the state.
+///
+/// The records are sent to the stream as they are read,
+/// and the task stops when the stream is dropped, like when the client disconnects.
+fn spawn_stream_all<T, S>(
+    state: Arc<S>,
+) -> impl Stream<Item = Result<T::Response, Error>> + Send + 'static
+where
+    T: Collection + 'static,
+    T::Response: 'static,
+    S: Deref<Target = T::Db> + Send + Sync + 'static,
+{
+    /// How many records can be read before they are sent to the response.
+    const BUFFER: usize = 64;
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(BUFFER);
+    tokio::spawn(async move {
+        let records = T::stream_all(&*state);
+        let mut records = core::pin::pin!(records);
+        while let Some(record) = records.next().await {
+            if sender.send(record).await.is_err() {
+                break;
+            }
+        }
+    });
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        let record = receiver.recv().await?;
+        Some((record, receiver))
+    })
+}
+
 /// A controller manages the connection between model and view.  
 ///
 /// Every function corresponds to a web endpoint for the resource.  
@@ -68,26 +112,339 @@ where
     /// body: no \
     ///
     /// Default action is to get all records.  
-    /// [GetManyRequestQuery][Controller::GetManyRequestQuery] can be used for custom requests.
+    /// [GetManyRequestQuery][Controller::GetManyRequestQuery] can be used for custom requests.  
+    /// [ExpandQuery] expands lazy foreign keys.  
+    /// [FieldsQuery] selects the fields of the response.  
+    /// [ReadPrimary] reads from the primary instead of the read replica.  
+    /// The cursor of the next [Page] is in the `X-Next-Cursor` header.
+    /// The pagination of a [Page] is in an envelope or in the `X-Total-Count` and `Link` headers,
+    /// which link to other pages based on the [RawQuery].
     async fn get_many(
         state: State<Arc<Self::State>>,
         auth: AuthToken<Self::Auth>,
         query: Query<Self::GetManyRequestQuery>,
-    ) -> Result<Json<Vec<Self::Response>>, Error> {
-        let rs = Self::get_all(&*state.0).await?;
-        Ok(Json(rs))
+        expand: Query<ExpandQuery>,
+        fields: Query<FieldsQuery>,
+        raw_query: RawQuery,
+        read_primary: ReadPrimary,
+    ) -> Result<Page<Fields<Self::Response>>, Error> {
+        fields.validate::<Self>()?;
+        let mut expand = expand.0;
+        fields.restrict(&mut expand);
+        let db: &Self::Db = &state.0;
+        let primary = read_primary.primary(db);
+        let db = primary.as_ref().unwrap_or(db);
+        let mut rs = match fields.paths() {
+            Some(paths) => Self::get_all_fields(db, &paths).await?,
+            None => Self::get_all(db).await?,
+        };
+        expand.expand::<Self>(db, &mut rs).await?;
+        Ok(fields.select_page(Page::from(rs)))
+    }
+    /// Stream all records, for exporting a large table.
+    ///
+    /// method: `GET` \
+    /// id: no \
+    /// body: no \
+    ///
+    /// The records are read in a task and sent to the response as they are read,
+    /// so they are never all in memory.
+    /// They are newline delimited JSON if the `Accept` header accepts `application/x-ndjson`,
+    /// otherwise a JSON array in chunks, see [JsonStream].
+    async fn stream_many(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        headers: HeaderMap,
+    ) -> JsonStream<impl Stream<Item = Result<Self::Response, Error>> + Send + 'static>
+    where
+        Self: 'static,
+        Self::State: Send + Sync + 'static,
+        Self::Response: 'static,
+    {
+        let records = spawn_stream_all::<Self, _>(state.0);
+        JsonStream::from_accept(records, &headers)
+    }
+    /// Export all records as CSV.
+    ///
+    /// method: `GET` \
+    /// id: no \
+    /// body: no \
+    ///
+    /// The headers are the names of the fields of the response, see [Collection::FIELDS].
+    /// The records are streamed like [stream_many](Controller::stream_many).
+    async fn export(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+    ) -> CsvStream<impl Stream<Item = Result<Self::Response, Error>> + Send + 'static>
+    where
+        Self: 'static,
+        Self::State: Send + Sync + 'static,
+        Self::Response: 'static,
+    {
+        CsvStream {
+            stream: spawn_stream_all::<Self, _>(state.0),
+            fields: Self::FIELDS,
+        }
+    }
+    /// Get many records, or export all records as CSV if the `Accept` header accepts `text/csv`.
+    ///
+    /// method: `GET` \
+    /// id: no \
+    /// body: no \
+    ///
+    /// See [get_many](Controller::get_many) and [export](Controller::export).
+    async fn get_many_or_export(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        query: Query<Self::GetManyRequestQuery>,
+        expand: Query<ExpandQuery>,
+        fields: Query<FieldsQuery>,
+        raw_query: RawQuery,
+        headers: HeaderMap,
+    ) -> Response
+    where
+        Self: 'static,
+        Self::State: Send + Sync + 'static,
+        Self::Response: 'static,
+    {
+        if accepts(&headers, "text/csv") {
+            Self::export(state, auth).await.into_response()
+        } else {
+            let read_primary = ReadPrimary::from_headers(&headers);
+            Self::get_many(state, auth, query, expand, fields, raw_query, read_primary)
+                .await
+                .into_response()
+        }
+    }
+    /// Import records from CSV.
+    ///
+    /// method: `POST` \
+    /// id: no \
+    /// body: yes -> CSV \
+    ///
+    /// The headers are the names of the fields of the create request.
+    /// If any row isn't valid, the errors of the rows are returned and no records are created,
+    /// otherwise the records are created in batches in one transaction.
+    async fn import(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        body: Bytes,
+    ) -> Result<Json<Imported>, ModelError<Vec<RowError<Self::CreateRequestError>>>>
+    where
+        Self: Import,
+        Self::CreateRequest: Request<Create, Error = Self::CreateRequestError>,
+    {
+        let requests =
+            csv::read::<Self::CreateRequest>(&body).map_err(ModelError::UnprocessableEntity)?;
+        let created = u64::try_from(requests.len()).unwrap_or_default();
+        Self::create_batch(&*state.0, requests).await?;
+        Ok(Json(Imported { created }))
     }
     /// Get a record.
     ///
     /// method: `GET` \
     /// id: yes -> [Self::Id][Model::Id] \
     /// body: no \
+    ///
+    /// [ExpandQuery] expands lazy foreign keys.  
+    /// [FieldsQuery] selects the fields of the response.  
+    /// [ReadPrimary] reads from the primary instead of the read replica.
     async fn get(
         state: State<Arc<Self::State>>,
         auth: AuthToken<Self::Auth>,
         id: Path<Self::Id>,
-    ) -> Result<Json<Self::Response>, Error> {
-        let rs = Self::get_one(&*state.0, id.0).await?;
+        expand: Query<ExpandQuery>,
+        fields: Query<FieldsQuery>,
+        read_primary: ReadPrimary,
+    ) -> Result<Json<Fields<Self::Response>>, Error> {
+        fields.validate::<Self>()?;
+        let mut expand = expand.0;
+        fields.restrict(&mut expand);
+        let db: &Self::Db = &state.0;
+        let primary = read_primary.primary(db);
+        let db = primary.as_ref().unwrap_or(db);
+        let mut rs = match fields.paths() {
+            Some(paths) => Self::get_one_fields(db, id.0, &paths).await?,
+            None => Self::get_one(db, id.0).await?,
+        };
+        expand
+            .expand::<Self>(db, core::slice::from_mut(&mut rs))
+            .await?;
+        Ok(Json(fields.select(rs)))
+    }
+    /// Get the children of a record.
+    ///
+    /// method: `GET` \
+    /// id: yes -> [Self::Id][Model::Id] \
+    /// body: no \
+    ///
+    /// The children are the records of the `Child` table
+    /// whose foreign key column, marked by `AggregateBy`, refers to this record.
+    async fn get_children<Child, AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        id: Path<Self::Id>,
+    ) -> Result<Json<Vec<Child::Response>>, Error>
+    where
+        Child: Children<AggregateBy, Db = Self::Db, ParentId = Self::Id>,
+        Child::Response: Serialize,
+    {
+        let rs = Child::get_children(&*state.0, id.0).await?;
+        Ok(Json(rs))
+    }
+    /// Get the many-to-many relations of a record.
+    ///
+    /// method: `GET` \
+    /// id: yes -> [Self::Id][Model::Id] \
+    /// body: no \
+    ///
+    /// The relations are the values of the `Many` table, marked by `AggregateBy`, for this record.
+    async fn get_relation<Many, AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        id: Path<Self::Id>,
+    ) -> Result<Json<Vec<Many::ManyResponse>>, Error>
+    where
+        Many: ManyModel<AggregateBy, Db = Self::Db, OneRequest = Self::Id>,
+        Many::ManyResponse: Serialize,
+    {
+        let rs = Many::get_many(&*state.0, id.0).await?;
+        Ok(Json(rs))
+    }
+    /// Add a many-to-many relation to a record.
+    ///
+    /// method: `POST` \
+    /// id: yes -> ([Self::Id][Model::Id], [Many::ManyId][ManyModel::ManyId]) \
+    /// body: optional -> [Many::PivotRequest][ManyModel::PivotRequest] \
+    ///
+    /// The body is only needed if the relation has pivot columns.
+    async fn add_relation<Many, AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        ids: Path<(Self::Id, Many::ManyId)>,
+        pivot: Option<Json<Many::PivotRequest>>,
+    ) -> Result<(), Error>
+    where
+        Many: ManyModel<AggregateBy, Db = Self::Db, OneRequest = Self::Id>,
+        Many::ManyId: for<'a> Deserialize<'a>,
+        Many::PivotRequest: for<'a> Deserialize<'a>,
+    {
+        let (id, many) = ids.0;
+        let pivot = if let Some(pivot) = pivot {
+            pivot.0
+        } else {
+            // only a relation without pivot columns can be added without a body
+            let unit = serde::de::value::UnitDeserializer::<serde::de::value::Error>::new();
+            <Many::PivotRequest as Deserialize>::deserialize(unit).map_err(|_| Error::BadRequest)?
+        };
+        let many = Many::many_request(many, pivot);
+        Many::add_many(&*state.0, id, core::slice::from_ref(&many)).await?;
+        Ok(())
+    }
+    /// Remove a many-to-many relation from a record.
+    ///
+    /// method: `DELETE` \
+    /// id: yes -> ([Self::Id][Model::Id], [Many::ManyId][ManyModel::ManyId]) \
+    /// body: no \
+    async fn remove_relation<Many, AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        ids: Path<(Self::Id, Many::ManyId)>,
+    ) -> Result<(), Error>
+    where
+        Many: ManyModel<AggregateBy, Db = Self::Db, OneRequest = Self::Id>,
+        Many::ManyId: for<'a> Deserialize<'a>,
+    {
+        let (id, many) = ids.0;
+        Many::remove_many(&*state.0, id, core::slice::from_ref(&many)).await?;
+        Ok(())
+    }
+    /// Add the routes of the many-to-many relations of a record to the router,
+    /// like `GET /users/{id}/groups`, `POST /users/{id}/groups/{many_id}`
+    /// and `DELETE /users/{id}/groups/{many_id}`.
+    ///
+    /// The `path` is the path of a record, like `/users/{id}`.
+    fn relation_routes(router: Router<Arc<Self::State>>, _path: &str) -> Router<Arc<Self::State>> {
+        router
+    }
+    /// Add the routes of the collection to the router, like `GET /orders/stats`.
+    ///
+    /// The `path` is the path of the collection, like `/orders`.
+    fn collection_routes(
+        router: Router<Arc<Self::State>>,
+        _path: &str,
+    ) -> Router<Arc<Self::State>> {
+        router
+    }
+    /// Get the statistics of the records, like counts and sums, which can be grouped.
+    ///
+    /// method: `GET` \
+    /// id: no \
+    /// body: no \
+    ///
+    /// [StatsRequest][Stats::StatsRequest] chooses the grouping and the statistics,
+    /// like `?group_by=status&sum=total&avg=total`.
+    async fn get_stats(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        query: Query<<Self as Stats>::StatsRequest>,
+    ) -> Result<Json<Vec<<Self as Stats>::StatsResponse>>, Error>
+    where
+        Self: Stats,
+        <Self as Stats>::StatsRequest: for<'a> Deserialize<'a>,
+        <Self as Stats>::StatsResponse: Serialize,
+    {
+        let rs = <Self as Stats>::stats(&*state.0, query.0).await?;
+        Ok(Json(rs))
+    }
+    /// Get the ancestors of a record, starting with its parent.
+    ///
+    /// method: `GET` \
+    /// id: yes -> [Self::Id][Model::Id] \
+    /// body: no \
+    ///
+    /// The parent is the record that the foreign key column, marked by `AggregateBy`, refers to.
+    async fn get_ancestors<AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        id: Path<Self::Id>,
+    ) -> Result<Json<Vec<Self::Response>>, Error>
+    where
+        Self: Tree<AggregateBy>,
+    {
+        let rs = <Self as Tree<AggregateBy>>::get_ancestors(&*state.0, id.0).await?;
+        Ok(Json(rs))
+    }
+    /// Get all descendants of a record.
+    ///
+    /// method: `GET` \
+    /// id: yes -> [Self::Id][Model::Id] \
+    /// body: no \
+    async fn get_descendants<AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        id: Path<Self::Id>,
+    ) -> Result<Json<Vec<Self::Response>>, Error>
+    where
+        Self: Tree<AggregateBy>,
+    {
+        let rs = <Self as Tree<AggregateBy>>::get_descendants(&*state.0, id.0).await?;
+        Ok(Json(rs))
+    }
+    /// Get a record with its descendants nested in it.
+    ///
+    /// method: `GET` \
+    /// id: yes -> [Self::Id][Model::Id] \
+    /// body: no \
+    async fn get_subtree<AggregateBy>(
+        state: State<Arc<Self::State>>,
+        auth: AuthToken<Self::Auth>,
+        id: Path<Self::Id>,
+    ) -> Result<Json<Node<Self::Response>>, Error>
+    where
+        Self: Tree<AggregateBy>,
+    {
+        let rs = <Self as Tree<AggregateBy>>::get_subtree(&*state.0, id.0).await?;
         Ok(Json(rs))
     }
     /// Create a record.
@@ -65,3 +65,41 @@ macro_rules! env_var_default {
     };
 }
 pub(crate) use env_var_default;
+/// Get optional environment variable and parse it.
+///
+/// # Errors
+/// - Not unicode.
+/// - Not valid.
+macro_rules! env_var_parse_opt {
+    ($env_var:expr) => {
+        match ::std::env::var_os($env_var) {
+            ::core::option::Option::Some(value) => value
+                .to_str()
+                .and_then(|value| ::core::str::FromStr::from_str(value).ok())
+                .map(::core::option::Option::Some)
+                .ok_or($crate::env::EnvError {
+                    name: $env_var,
+                    value,
+                }),
+            ::core::option::Option::None => {
+                ::core::result::Result::Ok(::core::option::Option::None)
+            }
+        }
+    };
+}
+pub(crate) use env_var_parse_opt;
+
+/// Error when an environment variable is invalid.
+#[derive(Debug, thiserror::Error)]
+#[error("environment variable {name:?} was not valid: {value:?}")]
+pub struct EnvError {
+    /// The name of the environment variable.
+    pub name: &'static str,
+    /// The value of the environment variable.
+    pub value: std::ffi::OsString,
+}
+impl From<EnvError> for sqlx::Error {
+    fn from(error: EnvError) -> Self {
+        Self::Configuration(Box::new(error))
+    }
+}
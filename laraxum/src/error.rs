@@ -10,8 +10,8 @@ use serde::Serialize;
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
-    // /// [400 Bad Request](https://datatracker.ietf.org/doc/html/rfc9110#section-15.5.1)
-    // BadRequest,
+    /// [400 Bad Request](https://datatracker.ietf.org/doc/html/rfc9110#section-15.5.1)
+    BadRequest,
     /// [404 Not Found](https://datatracker.ietf.org/doc/html/rfc9110#section-15.5.5)
     NotFound,
     /// [409 Conflict](https://datatracker.ietf.org/doc/html/rfc9110#section-15.5.10)
@@ -20,17 +20,22 @@ pub enum Error {
     // TooManyRequests,
     /// [500 Internal Server Error](https://datatracker.ietf.org/doc/html/rfc9110#section-15.6.1)
     Internal,
+    /// [504 Gateway Timeout](https://datatracker.ietf.org/doc/html/rfc9110#section-15.6.5)
+    ///
+    /// A query took longer than the timeout.
+    Timeout,
 }
 impl Error {
     const fn status_code(self) -> StatusCode {
         match self {
-            // Self::BadRequest => StatusCode::BAD_REQUEST,
+            Self::BadRequest => StatusCode::BAD_REQUEST,
             // Self::Unauthenticated => StatusCode::UNAUTHORIZED,
             // Self::Unauthorized => StatusCode::FORBIDDEN,
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::Conflict => StatusCode::CONFLICT,
             // Self::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
             Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Timeout => StatusCode::GATEWAY_TIMEOUT,
         }
     }
 }
@@ -39,11 +44,11 @@ impl From<sqlx::Error> for Error {
         match error {
             // sqlx::Error::RowNotFound => Self::NotFound,
             sqlx::Error::Database(error) => {
-                eprintln!("sql database error: {error:?}");
+                tracing::error!(?error, "sql database error");
                 Self::Conflict
             }
             error => {
-                eprintln!("sql error: {error:?}");
+                tracing::error!(?error, "sql error");
                 Self::Internal
             }
         }
@@ -8,10 +8,13 @@ pub mod model;
 pub use controller::{
     Controller,
     auth::{AuthToken, Authenticate, AuthenticateToken, Authorize},
-    extract::Json,
+    extract::{Json, ReadPrimary},
 };
 pub use error::{AppError, AuthError, Error, ModelError};
-pub use model::{AggregateMany, AggregateOne, Collection, Connect, Db, ManyModel, Model, Table};
+pub use model::{
+    AggregateMany, AggregateOne, Children, Collection, Connect, Db, Import, ManyModel, Model,
+    ReadReplica, Stats, Table, Transact, Tree, seed::Seed,
+};
 
 #[cfg(feature = "macros")]
 #[doc(inline)]
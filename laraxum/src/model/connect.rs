@@ -0,0 +1,224 @@
+//! Options for connecting to a database.
+
+pub use crate::env::EnvError;
+
+use core::time::Duration;
+
+/// Options for connecting to a database and for the connection pool.
+///
+/// Options that aren't set use the defaults of [sqlx::pool::PoolOptions].
+#[derive(Clone, Debug, Default)]
+pub struct ConnectOptions {
+    /// The database URL.
+    pub url: Option<String>,
+    /// The database URL of a read replica.
+    pub read_url: Option<String>,
+    /// The maximum number of connections in the pool.
+    pub max_connections: Option<u32>,
+    /// The minimum number of idle connections in the pool.
+    pub min_connections: Option<u32>,
+    /// How long to wait for a connection from the pool.
+    pub acquire_timeout: Option<Duration>,
+    /// How long a connection can be idle before it is closed.
+    pub idle_timeout: Option<Duration>,
+    /// How long a connection can be open before it is closed.
+    pub max_lifetime: Option<Duration>,
+    /// How many prepared statements are cached per connection.
+    pub statement_cache_capacity: Option<usize>,
+    /// How to retry connecting if it fails.
+    pub retry: Retry,
+    /// How to run queries.
+    pub query: super::QueryOptions,
+}
+impl ConnectOptions {
+    /// Get the options from environment variables.
+    ///
+    /// - `DATABASE_URL`
+    /// - `DATABASE_READ_URL`
+    /// - `DATABASE_MAX_CONNECTIONS`
+    /// - `DATABASE_MIN_CONNECTIONS`
+    /// - `DATABASE_ACQUIRE_TIMEOUT_SECS`
+    /// - `DATABASE_IDLE_TIMEOUT_SECS`
+    /// - `DATABASE_MAX_LIFETIME_SECS`
+    /// - `DATABASE_STATEMENT_CACHE_CAPACITY`
+    /// - See [Retry::from_env].
+    /// - See [QueryOptions::from_env](super::QueryOptions::from_env).
+    ///
+    /// # Errors
+    /// - Invalid environment variable.
+    pub fn from_env() -> Result<Self, EnvError> {
+        Ok(Self {
+            url: crate::env::env_var_parse_opt!("DATABASE_URL")?,
+            read_url: crate::env::env_var_parse_opt!("DATABASE_READ_URL")?,
+            max_connections: crate::env::env_var_parse_opt!("DATABASE_MAX_CONNECTIONS")?,
+            min_connections: crate::env::env_var_parse_opt!("DATABASE_MIN_CONNECTIONS")?,
+            acquire_timeout: crate::env::env_var_parse_opt!("DATABASE_ACQUIRE_TIMEOUT_SECS")?
+                .map(Duration::from_secs),
+            idle_timeout: crate::env::env_var_parse_opt!("DATABASE_IDLE_TIMEOUT_SECS")?
+                .map(Duration::from_secs),
+            max_lifetime: crate::env::env_var_parse_opt!("DATABASE_MAX_LIFETIME_SECS")?
+                .map(Duration::from_secs),
+            statement_cache_capacity: crate::env::env_var_parse_opt!(
+                "DATABASE_STATEMENT_CACHE_CAPACITY"
+            )?,
+            retry: Retry::from_env()?,
+            query: super::QueryOptions::from_env()?,
+        })
+    }
+    /// Create the pool options.
+    #[must_use]
+    pub fn pool_options<DB>(&self) -> sqlx::pool::PoolOptions<DB>
+    where
+        DB: sqlx::Database,
+    {
+        let mut pool_options = sqlx::pool::PoolOptions::<DB>::new();
+        if let Some(max_connections) = self.max_connections {
+            pool_options = pool_options.max_connections(max_connections);
+        }
+        if let Some(min_connections) = self.min_connections {
+            pool_options = pool_options.min_connections(min_connections);
+        }
+        if let Some(acquire_timeout) = self.acquire_timeout {
+            pool_options = pool_options.acquire_timeout(acquire_timeout);
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            pool_options = pool_options.idle_timeout(idle_timeout);
+        }
+        if let Some(max_lifetime) = self.max_lifetime {
+            pool_options = pool_options.max_lifetime(max_lifetime);
+        }
+        pool_options
+    }
+}
+
+/// Retry with exponential backoff.
+///
+/// The delay starts at `initial_delay` and doubles after every attempt, up to `max_delay`.
+#[derive(Clone, Debug)]
+pub struct Retry {
+    /// How many times to retry after the first attempt fails.
+    pub retries: u32,
+    /// The delay before the first retry.
+    pub initial_delay: Duration,
+    /// The maximum delay between retries.
+    pub max_delay: Duration,
+}
+impl Default for Retry {
+    /// Don't retry.
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+impl Retry {
+    /// Get the options from environment variables.
+    ///
+    /// - `DATABASE_CONNECT_RETRIES`
+    /// - `DATABASE_CONNECT_RETRY_DELAY_MS`
+    /// - `DATABASE_CONNECT_RETRY_MAX_DELAY_MS`
+    ///
+    /// # Errors
+    /// - Invalid environment variable.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let default = Self::default();
+        Ok(Self {
+            retries: crate::env::env_var_parse_opt!("DATABASE_CONNECT_RETRIES")?
+                .unwrap_or(default.retries),
+            initial_delay: crate::env::env_var_parse_opt!("DATABASE_CONNECT_RETRY_DELAY_MS")?
+                .map_or(default.initial_delay, Duration::from_millis),
+            max_delay: crate::env::env_var_parse_opt!("DATABASE_CONNECT_RETRY_MAX_DELAY_MS")?
+                .map_or(default.max_delay, Duration::from_millis),
+        })
+    }
+    /// Run until it succeeds or there are no retries left.
+    ///
+    /// # Errors
+    /// - The last attempt fails.
+    pub async fn run<T, E, F, Fut>(&self, mut f: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: core::fmt::Display,
+    {
+        let mut delay = self.initial_delay;
+        let mut retries = self.retries;
+        loop {
+            match f().await {
+                Ok(ok) => return Ok(ok),
+                Err(err) if retries > 0 => {
+                    tracing::warn!(%err, ?delay, "connecting failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2).min(self.max_delay);
+                    retries = retries.saturating_sub(1);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETRY: Retry = Retry {
+        retries: 4,
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(300),
+    };
+
+    /// Fail the first `failures` attempts and return the time of each attempt.
+    async fn attempts(
+        retry: &Retry,
+        failures: usize,
+    ) -> (Result<usize, &'static str>, Vec<Duration>) {
+        let start = tokio::time::Instant::now();
+        let mut attempts = vec![];
+        let result = retry
+            .run(|| {
+                attempts.push(start.elapsed());
+                let attempt = attempts.len();
+                async move {
+                    if attempt > failures {
+                        Ok(attempt)
+                    } else {
+                        Err("failed")
+                    }
+                }
+            })
+            .await;
+        (result, attempts)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_backs_off_up_to_max_delay() {
+        let (result, times) = attempts(&RETRY, 4).await;
+        assert_eq!(result, Ok(5));
+        let ms = Duration::from_millis;
+        assert_eq!(times, [ms(0), ms(100), ms(300), ms(600), ms(900)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_stops_after_success() {
+        let (result, times) = attempts(&RETRY, 1).await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(times.len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_returns_last_error() {
+        let (result, times) = attempts(&RETRY, 10).await;
+        assert_eq!(result, Err("failed"));
+        assert_eq!(times.len(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_without_retries() {
+        let (result, times) = attempts(&Retry::default(), 1).await;
+        assert_eq!(result, Err("failed"));
+        assert_eq!(times, [Duration::ZERO]);
+    }
+}
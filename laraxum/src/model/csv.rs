@@ -0,0 +1,121 @@
+//! Export records as CSV and import records from CSV.
+//!
+//! The headers of the CSV are the names of the fields,
+//! which are the names in `response(name)` when exporting and in `request(name)` when importing.
+
+use super::{
+    Collection,
+    request::{Request, method::Create},
+};
+use crate::Error;
+
+use serde::{Serialize, de::DeserializeOwned};
+
+/// A collection where many records can be created at once, like when importing them.
+pub trait Import: Collection {
+    /// Create the records in batches in one transaction.
+    ///
+    /// The requests must already be validated.
+    async fn create_batch(db: &Self::Db, requests: Vec<Self::CreateRequest>) -> Result<(), Error>;
+}
+
+/// The error of a row of an import.
+#[derive(Debug, Serialize)]
+pub struct RowError<E> {
+    /// The row, starting at `1` for the first row after the headers.
+    pub row: u64,
+    /// Why the row can't be read, like a missing column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Why the request of the row isn't valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<E>,
+}
+
+/// The response of an import.
+#[derive(Debug, Serialize)]
+pub struct Imported {
+    /// How many records were created.
+    pub created: u64,
+}
+
+/// Read and validate the requests in the rows of a CSV.
+///
+/// # Errors
+/// - A row can't be read or isn't valid.
+///   All rows are read, so there is an error for each row that fails.
+pub fn read<T>(csv: &[u8]) -> Result<Vec<T>, Vec<RowError<T::Error>>>
+where
+    T: DeserializeOwned + Request<Create>,
+{
+    let mut reader = ::csv::Reader::from_reader(csv);
+    let headers = reader.headers().cloned().map_err(|err| {
+        vec![RowError {
+            row: 0,
+            message: Some(err.to_string()),
+            error: None,
+        }]
+    })?;
+    let mut requests = vec![];
+    let mut errors = vec![];
+    for (row, record) in (1..).zip(reader.records()) {
+        let request = record.and_then(|record| record.deserialize::<T>(Some(&headers)));
+        match request {
+            Ok(request) => match request.validate() {
+                Ok(()) => requests.push(request),
+                Err(error) => errors.push(RowError {
+                    row,
+                    message: None,
+                    error: Some(error),
+                }),
+            },
+            Err(err) => errors.push(RowError {
+                row,
+                message: Some(err.to_string()),
+                error: None,
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(requests)
+    } else {
+        Err(errors)
+    }
+}
+
+/// The headers of a CSV, which are the fields of the response, see [Collection::FIELDS].
+///
+/// # Errors
+/// - The headers can't be written.
+pub fn headers(fields: &[&str]) -> Result<Vec<u8>, Error> {
+    write(fields)
+}
+
+/// A row of a CSV, with the values of the fields of a record.
+///
+/// Nested values, like relations, are written as JSON.
+///
+/// # Errors
+/// - The record can't be serialized or written.
+pub fn row<T>(record: &T, fields: &[&str]) -> Result<Vec<u8>, Error>
+where
+    T: Serialize,
+{
+    let record = serde_json::to_value(record).map_err(|_| Error::Internal)?;
+    let values = fields.iter().map(|field| match record.get(field) {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+    });
+    write(values)
+}
+
+fn write<I>(values: I) -> Result<Vec<u8>, Error>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut writer = ::csv::Writer::from_writer(vec![]);
+    writer.write_record(values).map_err(|_| Error::Internal)?;
+    writer.into_inner().map_err(|_| Error::Internal)
+}
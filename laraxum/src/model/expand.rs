@@ -0,0 +1,181 @@
+//! Expand lazy foreign keys.
+//!
+//! A lazy foreign key only returns the identifier of the foreign record,
+//! unless it is expanded with the `expand` query parameter, like `?expand=address,contact.address`.
+
+use super::{Collection, Model};
+use crate::Error;
+
+use core::hash::Hash;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// A lazy foreign key, which is either the identifier of the foreign record or the foreign record itself.
+#[derive(Debug, serde::Serialize)]
+#[serde(untagged)]
+pub enum Expand<Id, T> {
+    /// The identifier of the foreign record.
+    Id(Id),
+    /// The foreign record.
+    Expanded(Arc<T>),
+}
+impl<Id, T> Expand<Id, T>
+where
+    Id: Copy,
+{
+    /// The identifier of the foreign record, if it isn't expanded.
+    pub const fn id(&self) -> Option<Id> {
+        match self {
+            Self::Id(id) => Some(*id),
+            Self::Expanded(_) => None,
+        }
+    }
+}
+impl<Id, T> super::types::Decode for Expand<Id, T>
+where
+    Id: super::types::Decode,
+{
+    type Decode = Id::Decode;
+    #[inline]
+    fn decode(decode: Self::Decode) -> Self {
+        Self::Id(Id::decode(decode))
+    }
+}
+
+/// Request query for the relations to expand.
+#[derive(serde::Deserialize, Default)]
+pub struct ExpandQuery {
+    /// The paths of the relations, separated by commas, like `address,contact.address`.
+    pub expand: Option<String>,
+}
+impl ExpandQuery {
+    /// The paths of the relations.
+    #[must_use]
+    pub fn paths(&self) -> Vec<&str> {
+        self.expand
+            .as_deref()
+            .map(|expand| expand.split(',').filter(|path| !path.is_empty()).collect())
+            .unwrap_or_default()
+    }
+    /// Expand the relations in the records.
+    ///
+    /// # Errors
+    /// - A path isn't a relation of the table.
+    /// - The query fails.
+    pub async fn expand<T>(&self, db: &T::Db, response: &mut [T::Response]) -> Result<(), Error>
+    where
+        T: Collection,
+    {
+        let paths = self.paths();
+        if paths.is_empty() {
+            return Ok(());
+        }
+        if !paths.iter().all(|path| T::is_expandable(path)) {
+            return Err(Error::BadRequest);
+        }
+        T::expand(db, response.iter_mut().collect(), &paths).await
+    }
+}
+
+/// The paths that go through a relation, without the name of the relation.
+///
+/// Returns `None` if no path goes through the relation.
+#[must_use]
+pub fn sub_paths<'a>(paths: &[&'a str], name: &str) -> Option<Vec<&'a str>> {
+    let mut is_expanded = false;
+    let mut sub_paths = vec![];
+    for path in paths {
+        match path.split_once('.') {
+            Some((path_name, sub_path)) if path_name == name => {
+                is_expanded = true;
+                sub_paths.push(sub_path);
+            }
+            None if *path == name => {
+                is_expanded = true;
+            }
+            _ => {}
+        }
+    }
+    is_expanded.then_some(sub_paths)
+}
+
+/// Load the foreign records of lazy foreign keys with one query,
+/// then expand the relations in the paths of the foreign records.
+///
+/// # Errors
+/// - The query fails.
+pub async fn expand<T>(
+    db: &T::Db,
+    response: Vec<&mut Expand<T::Id, T>>,
+    paths: &[&str],
+) -> Result<(), Error>
+where
+    T: Model<Response = T> + Send + Sync,
+    T::Id: Eq + Hash + Send,
+{
+    let ids: HashSet<T::Id> = response
+        .iter()
+        .filter_map(|response| response.id())
+        .collect();
+    let ids: Vec<T::Id> = ids.into_iter().collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let mut records = T::get_one_batch(db, &ids).await?;
+    T::expand(db, records.iter_mut().flatten().collect(), paths).await?;
+    let records: HashMap<T::Id, Arc<T>> = ids
+        .into_iter()
+        .zip(records)
+        .filter_map(|(id, record)| Some((id, Arc::new(record?))))
+        .collect();
+    for response in response {
+        if let Some(record) = response.id().and_then(|id| records.get(&id)) {
+            *response = Expand::Expanded(Arc::clone(record));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sub_paths_of_relation() {
+        let paths = [
+            "contact.address",
+            "address",
+            "contact.owner.address",
+            "contacts",
+        ];
+        assert_eq!(
+            sub_paths(&paths, "contact"),
+            Some(vec!["address", "owner.address"])
+        );
+    }
+
+    #[test]
+    fn sub_paths_of_expanded_relation() {
+        assert_eq!(sub_paths(&["address"], "address"), Some(vec![]));
+    }
+
+    #[test]
+    fn sub_paths_of_other_relation() {
+        assert_eq!(
+            sub_paths(&["contact.address", "addresses"], "address"),
+            None
+        );
+        assert_eq!(sub_paths(&[], "address"), None);
+    }
+
+    #[test]
+    fn paths_skip_empty() {
+        let query = ExpandQuery {
+            expand: Some(String::from("address,,contact.address,")),
+        };
+        assert_eq!(query.paths(), ["address", "contact.address"]);
+        assert!(ExpandQuery::default().paths().is_empty());
+    }
+}
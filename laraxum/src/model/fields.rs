@@ -0,0 +1,246 @@
+//! Sparse fieldsets, which select the fields of the response.
+//!
+//! The fields are selected with the `fields` query parameter, like `?fields=id,name,contact.email`.
+//! A field of a relation only selects that field of the relation,
+//! but the relation itself selects all of its fields.
+//!
+//! Only the columns of the selected fields are queried, see [select_sql],
+//! and the relations that aren't selected aren't joined or loaded.
+
+use super::{Collection, Page, expand::ExpandQuery};
+use crate::Error;
+
+use std::sync::Arc;
+
+use serde::{Serialize, Serializer, ser::Error as _};
+
+/// Request query for the fields of the response.
+#[derive(serde::Deserialize, Default)]
+pub struct FieldsQuery {
+    /// The paths of the fields, separated by commas, like `id,name,contact.email`.
+    pub fields: Option<String>,
+}
+impl FieldsQuery {
+    /// The paths of the fields, or `None` if all fields are selected.
+    #[must_use]
+    pub fn paths(&self) -> Option<Vec<&str>> {
+        self.fields
+            .as_deref()
+            .map(|fields| fields.split(',').filter(|path| !path.is_empty()).collect())
+    }
+    /// Validate the paths against the fields of the response of the table.
+    ///
+    /// # Errors
+    /// - A path isn't a field of the table.
+    pub fn validate<T>(&self) -> Result<(), Error>
+    where
+        T: Collection,
+    {
+        match self.paths() {
+            Some(paths) if !paths.iter().all(|path| T::is_field(path)) => Err(Error::BadRequest),
+            _ => Ok(()),
+        }
+    }
+    /// Only expand the relations in the selected fields, because the others aren't in the response.
+    pub fn restrict(&self, expand: &mut ExpandQuery) {
+        let Some(paths) = self.paths() else {
+            return;
+        };
+        let expand_paths = expand
+            .paths()
+            .into_iter()
+            .filter(|expand_path| {
+                paths
+                    .iter()
+                    .any(|path| is_sub_path(path, expand_path) || is_sub_path(expand_path, path))
+            })
+            .collect::<Vec<&str>>()
+            .join(",");
+        expand.expand = Some(expand_paths);
+    }
+    /// Select the fields of a record.
+    pub fn select<T>(&self, data: T) -> Fields<T> {
+        Fields {
+            data,
+            paths: self.shared_paths(),
+        }
+    }
+    /// Select the fields of the records of a page.
+    #[must_use]
+    pub fn select_page<T>(&self, page: Page<T>) -> Page<Fields<T>> {
+        let paths = self.shared_paths();
+        page.map(|data| Fields {
+            data,
+            paths: paths.clone(),
+        })
+    }
+    fn shared_paths(&self) -> Option<Arc<[String]>> {
+        self.paths()
+            .map(|paths| paths.into_iter().map(String::from).collect())
+    }
+}
+
+/// A record with only the selected fields in its response.
+#[derive(Debug)]
+pub struct Fields<T> {
+    /// The record.
+    pub data: T,
+    /// The paths of the selected fields, or `None` if all fields are selected.
+    pub paths: Option<Arc<[String]>>,
+}
+impl<T> Serialize for Fields<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match &self.paths {
+            Some(paths) => {
+                let mut value = serde_json::to_value(&self.data).map_err(S::Error::custom)?;
+                let paths = paths.iter().map(String::as_str).collect::<Vec<&str>>();
+                select(&mut value, &paths);
+                value.serialize(serializer)
+            }
+            None => self.data.serialize(serializer),
+        }
+    }
+}
+
+/// A column of the `SELECT` list of a table, see [select_sql].
+#[derive(Debug, Clone, Copy)]
+pub struct SelectColumn {
+    /// The path of the field of the column,
+    /// or `None` if the column is always selected, like the ID.
+    pub path: Option<&'static str>,
+    /// The column and its alias, like `contact.email AS contact_email`.
+    pub sql: &'static str,
+}
+
+/// A `LEFT JOIN` of a foreign record, see [select_sql].
+#[derive(Debug, Clone, Copy)]
+pub struct SelectJoin {
+    /// The path of the field of the foreign record.
+    pub path: &'static str,
+    /// The join, like ` LEFT JOIN contact AS contact ON contact_id=contact.id`.
+    pub sql: &'static str,
+}
+
+/// If the field is selected, or any of its fields if it is a relation.
+#[must_use]
+pub fn is_selected(paths: &[&str], path: &str) -> bool {
+    paths
+        .iter()
+        .any(|selected| is_sub_path(path, selected) || is_sub_path(selected, path))
+}
+
+/// Build the query of the selected fields, with only their columns and joins.
+///
+/// `from` is the table, like ` FROM contact AS contact`,
+/// and `rest` is the rest of the query, like ` WHERE contact.id=?`.
+#[must_use]
+pub fn select_sql(
+    paths: &[&str],
+    columns: &[SelectColumn],
+    from: &str,
+    joins: &[SelectJoin],
+    rest: &str,
+) -> String {
+    let columns = columns
+        .iter()
+        .filter(|column| column.path.is_none_or(|path| is_selected(paths, path)))
+        .map(|column| column.sql)
+        .collect::<Vec<&str>>()
+        .join(",");
+    let mut sql = ["SELECT ", &columns, from].concat();
+    for join in joins.iter().filter(|join| is_selected(paths, join.path)) {
+        sql.push_str(join.sql);
+    }
+    sql.push_str(rest);
+    sql
+}
+
+/// If `path` is the same as `parent` or a field in it, like `contact.email` in `contact`.
+fn is_sub_path(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|path| path.is_empty() || path.starts_with('.'))
+}
+
+/// Remove the fields that aren't in the paths, in each record if it is a list.
+fn select(value: &mut serde_json::Value, paths: &[&str]) {
+    match value {
+        serde_json::Value::Array(values) => {
+            for value in values {
+                select(value, paths);
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            fields.retain(|name, value| {
+                // the whole field is selected, even if some of its fields are also selected
+                if paths.contains(&name.as_str()) {
+                    return true;
+                }
+                let sub_paths = paths
+                    .iter()
+                    .filter_map(|path| path.strip_prefix(name.as_str())?.strip_prefix('.'))
+                    .collect::<Vec<&str>>();
+                if sub_paths.is_empty() {
+                    return false;
+                }
+                select(value, &sub_paths);
+                true
+            });
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_selected_field_of_relation() {
+        let paths = ["name", "contact.email"];
+        assert!(is_selected(&paths, "name"));
+        assert!(is_selected(&paths, "contact"));
+        assert!(is_selected(&paths, "contact.email"));
+        assert!(!is_selected(&paths, "contact.mobile"));
+        assert!(!is_selected(&paths, "names"));
+    }
+
+    #[test]
+    fn select_sql_of_selected_fields() {
+        let columns = [
+            SelectColumn {
+                path: None,
+                sql: "users.id AS users__id",
+            },
+            SelectColumn {
+                path: Some("name"),
+                sql: "users.name AS users__name",
+            },
+            SelectColumn {
+                path: Some("contact.email"),
+                sql: "contacts.email AS contacts__email",
+            },
+        ];
+        let joins = [SelectJoin {
+            path: "contact",
+            sql: " LEFT JOIN contacts AS contacts ON users.contact_id=contacts.id",
+        }];
+        let from = " FROM users AS users";
+        assert_eq!(
+            select_sql(&["name"], &columns, from, &joins, " WHERE users.id=?"),
+            "SELECT users.id AS users__id,users.name AS users__name \
+                FROM users AS users WHERE users.id=?",
+        );
+        assert_eq!(
+            select_sql(&["contact"], &columns, from, &joins, ""),
+            "SELECT users.id AS users__id,contacts.email AS contacts__email \
+                FROM users AS users \
+                LEFT JOIN contacts AS contacts ON users.contact_id=contacts.id",
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Values of filters in query parameters.
+//!
+//! Most filters take a value of the type of the column, like `?filter_age_gt=18`.
+//! The `in` filter takes values separated by commas as one string,
+//! like `?filter_city_in=Berlin,Paris`, see [in_values].
+
+use super::Sort;
+use crate::Error;
+
+use core::{fmt::Display, str::FromStr};
+use serde::{
+    Deserialize, Deserializer,
+    de::{DeserializeOwned, Error as _, IntoDeserializer, value},
+};
+
+/// Two values separated by a comma, like `?filter_age_between=18,65`.
+#[derive(Clone, Copy, Debug)]
+pub struct Between<T> {
+    /// The lower bound, inclusive.
+    pub start: T,
+    /// The upper bound, inclusive.
+    pub end: T,
+}
+impl<'de, T> Deserialize<'de> for Between<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let (start, end) = value
+            .split_once(',')
+            .ok_or_else(|| D::Error::custom("expected two values separated by a comma"))?;
+        let start = start.parse().map_err(D::Error::custom)?;
+        let end = end.parse().map_err(D::Error::custom)?;
+        Ok(Self { start, end })
+    }
+}
+
+/// A filter without a value, like `?filter_deleted_at_is_null`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Flag;
+impl<'de> Deserialize<'de> for Flag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        serde::de::IgnoredAny::deserialize(deserializer)?;
+        Ok(Self)
+    }
+}
+
+/// The values of an `in` filter, which are separated by commas and trimmed,
+/// like `["Berlin", "Paris"]` for `?filter_city_in=Berlin,%20Paris`.
+///
+/// # Errors
+/// - There are no values, which is a bad request.
+pub fn in_values(values: &str) -> Result<Vec<&str>, Error> {
+    let values = values
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<&str>>();
+    if values.is_empty() {
+        return Err(Error::BadRequest);
+    }
+    Ok(values)
+}
+
+/// The condition of an `in` filter with a placeholder for each value,
+/// like `city IN (?,?)` for two values.
+#[must_use]
+pub fn in_condition(column: &str, len: usize) -> String {
+    let placeholders = vec!["?"; len].join(",");
+    [column, " IN (", &placeholders, ")"].concat()
+}
+
+/// Columns to sort by in order, separated by commas, with `-` for descending,
+/// like `?sort=-created_at,name`.
+#[derive(Clone)]
+pub struct SortBy<T>(pub Vec<(T, Sort)>);
+impl<'de, T> Deserialize<'de> for SortBy<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let sorts = value
+            .split(',')
+            .filter(|column| !column.is_empty())
+            .map(|column| {
+                let (column, sort) = match column.strip_prefix('-') {
+                    Some(column) => (column, Sort::Descending),
+                    None => (column, Sort::Ascending),
+                };
+                let column = IntoDeserializer::<value::Error>::into_deserializer(column);
+                let column = T::deserialize(column).map_err(D::Error::custom)?;
+                Ok((column, sort))
+            });
+        let sorts: Result<Vec<(T, Sort)>, D::Error> = sorts.collect();
+        sorts.map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_values_are_trimmed() {
+        assert_eq!(
+            in_values("Berlin, Paris ,,London").ok(),
+            Some(vec!["Berlin", "Paris", "London"])
+        );
+    }
+
+    #[test]
+    fn in_values_must_not_be_empty() {
+        assert!(matches!(in_values(""), Err(Error::BadRequest)));
+        assert!(matches!(in_values(" , "), Err(Error::BadRequest)));
+    }
+
+    #[test]
+    fn in_condition_has_placeholder_for_each_value() {
+        assert_eq!(in_condition("users.city", 3), "users.city IN (?,?,?)");
+    }
+}
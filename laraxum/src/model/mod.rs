@@ -1,13 +1,63 @@
 //! A model manages the data storage and interacts with the database.
 
+pub mod connect;
+pub mod csv;
+pub mod expand;
+pub mod fields;
+pub mod filter;
+pub mod nested;
+pub mod page;
+pub mod pivot;
+pub mod poly;
+pub mod query;
 pub mod request;
+pub mod seed;
+pub mod stats;
+pub mod tree;
 pub mod types;
 
+pub use connect::ConnectOptions;
+pub use csv::Import;
+pub use expand::{Expand, ExpandQuery};
+pub use fields::{Fields, FieldsQuery};
+pub use nested::Nested;
+pub use page::{Cursor, Page};
+pub use pivot::{Pivot, PivotRequest};
+pub use poly::Poly;
+pub use query::QueryOptions;
+pub use tree::Tree;
+
 use crate::{Error, ModelError};
 
+use futures::Stream;
+
 /// A database and a table that belongs to it.
 pub trait Db<Model> {}
 
+/// A database that can read from a read replica.
+///
+/// Reads use the read replica if there is one and writes always use the primary.
+pub trait ReadReplica: Sized {
+    /// The same database, but reading from the primary instead of the read replica.
+    ///
+    /// A read replica can lag behind the primary,
+    /// so use this to read a record right after writing it.
+    #[must_use]
+    fn primary(&self) -> Self;
+}
+
+/// A database whose writes can be grouped in a transaction.
+pub trait Transact {
+    /// The database driver of the transaction.
+    type Database: sqlx::Database;
+}
+
+/// A transaction of the database of a table.
+///
+/// Nested writes run in the transaction of the record that they belong to,
+/// so they are committed or rolled back together.
+pub type Transaction<T> = sqlx::Transaction<'static, <<T as Table>::Db as Transact>::Database>;
+
 /// Get the `DATABASE_URL` environment variable.
 ///
 /// # Panics
@@ -19,13 +69,21 @@ pub fn database_url() -> Option<String> {
 
 /// Connect to a database.
 pub trait Connect: Sized {
-    type Error;
-    async fn connect() -> Result<Self, Self::Error>;
+    type Error: From<connect::EnvError>;
+    /// Connect with options from environment variables.
+    ///
+    /// See [ConnectOptions::from_env].
+    #[must_use]
+    async fn connect() -> Result<Self, Self::Error> {
+        Self::connect_with(ConnectOptions::from_env()?).await
+    }
+    /// Connect with options.
+    async fn connect_with(options: ConnectOptions) -> Result<Self, Self::Error>;
 }
 
 /// A table in a database.
 pub trait Table: Sized {
-    type Db: Db<Self> + Send + Sync;
+    type Db: Db<Self> + ReadReplica + Transact + Send + Sync;
     type Response: Send + Sync;
 }
 
@@ -37,14 +95,55 @@ pub trait Collection: Table {
     type CreateRequest;
     /// Error when creating record.
     type CreateRequestError;
+    /// The names of the fields of the response, like the headers of a CSV export.
+    const FIELDS: &'static [&'static str] = &[];
 
     /// Return all records.
     async fn get_all(db: &Self::Db) -> Result<Vec<Self::Response>, Error>;
+    /// Stream all records as they are read from the database,
+    /// instead of collecting them before returning them.
+    ///
+    /// The stream doesn't time out, because it reads as fast as it is consumed.
+    fn stream_all(db: &Self::Db) -> impl Stream<Item = Result<Self::Response, Error>> + Send;
+    /// Return all records with only the selected fields.
+    ///
+    /// The fields that aren't selected are placeholders, see [fields](crate::model::fields).
+    async fn get_all_fields(db: &Self::Db, fields: &[&str]) -> Result<Vec<Self::Response>, Error> {
+        let _ = fields;
+        Self::get_all(db).await
+    }
     /// Create a record.
     async fn create_one(
         db: &Self::Db,
         rq: Self::CreateRequest,
     ) -> Result<(), ModelError<Self::CreateRequestError>>;
+    /// If the path of relations can be expanded, like `contact.address`.
+    ///
+    /// See [expand](crate::model::expand).
+    #[must_use]
+    fn is_expandable(path: &str) -> bool {
+        let _ = path;
+        false
+    }
+    /// If the path is a field of the response, like `contact.email`.
+    ///
+    /// See [fields](crate::model::fields).
+    #[must_use]
+    fn is_field(path: &str) -> bool {
+        let _ = path;
+        false
+    }
+    /// Expand the lazy foreign keys in the paths of relations.
+    ///
+    /// The paths must be expandable, see [Collection::is_expandable].
+    async fn expand(
+        db: &Self::Db,
+        response: Vec<&mut Self::Response>,
+        paths: &[&str],
+    ) -> Result<(), Error> {
+        let _ = (db, response, paths);
+        Ok(())
+    }
 }
 
 /// A table with uniquely identifiable records using an identifier column.
@@ -64,6 +163,46 @@ pub trait Model: Collection {
 
     /// Return a record.
     async fn get_one(db: &Self::Db, id: Self::Id) -> Result<Self::Response, Error>;
+    /// Return a record with only the selected fields.
+    ///
+    /// The fields that aren't selected are placeholders, see [fields](crate::model::fields).
+    async fn get_one_fields(
+        db: &Self::Db,
+        id: Self::Id,
+        fields: &[&str],
+    ) -> Result<Self::Response, Error> {
+        let _ = fields;
+        Self::get_one(db, id).await
+    }
+    /// Return the record for each of multiple identifiers, in the same order.
+    ///
+    /// This is used to load many records with one query instead of a query for each record.
+    /// The identifiers must be unique.
+    async fn get_one_batch(
+        db: &Self::Db,
+        ids: &[Self::Id],
+    ) -> Result<Vec<Option<Self::Response>>, Error> {
+        let mut records = Vec::with_capacity(ids.len());
+        for id in ids {
+            match Self::get_one(db, *id).await {
+                Ok(record) => records.push(Some(record)),
+                Err(Error::NotFound) => records.push(None),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(records)
+    }
+    /// Create a record and return its identifier.
+    async fn create_get_id(
+        db: &Self::Db,
+        rq: Self::CreateRequest,
+    ) -> Result<Self::Id, ModelError<Self::CreateRequestError>>;
+    /// Create a record in a transaction and return its identifier, without committing it.
+    async fn create_get_id_in(
+        db: &Self::Db,
+        transaction: &mut Transaction<Self>,
+        rq: Self::CreateRequest,
+    ) -> Result<Self::Id, ModelError<Self::CreateRequestError>>;
     /// Create a record and return it.
     async fn create_get_one(
         db: &Self::Db,
@@ -82,7 +221,7 @@ pub trait Model: Collection {
         id: Self::Id,
     ) -> Result<Self::Response, ModelError<Self::UpdateRequestError>> {
         Self::update_one(db, rq, id).await?;
-        let rs = Self::get_one(db, id).await?;
+        let rs = Self::get_one(&db.primary(), id).await?;
         Ok(rs)
     }
     /// Patch update a record.
@@ -98,7 +237,7 @@ pub trait Model: Collection {
         id: Self::Id,
     ) -> Result<Self::Response, ModelError<Self::PatchRequestError>> {
         Self::patch_one(db, rq, id).await?;
-        let rs = Self::get_one(db, id).await?;
+        let rs = Self::get_one(&db.primary(), id).await?;
         Ok(rs)
     }
     /// Delete a record.
@@ -111,35 +250,121 @@ pub trait Model: Collection {
 /// The `AggregateBy` type generic is a marker type for the identifier column.  
 ///
 /// This can be used to create many-to-many relationships.
+/// If the table has pivot columns,
+/// the values are a [Pivot] in a response and a [PivotRequest] in a request.
 pub trait ManyModel<AggregateBy>: Table {
     /// The identifier column used to identify many value columns.
     type OneRequest;
+    /// The value column without the pivot columns.
+    type ManyId;
     /// The value column in a request.
     type ManyRequest;
+    /// The pivot columns in a request, `()` if there are none.
+    type PivotRequest;
     /// The value column in a response.
     type ManyResponse;
 
+    /// Make the value column in a request from the value column and the pivot columns.
+    fn many_request(many: Self::ManyId, pivot: Self::PivotRequest) -> Self::ManyRequest;
     /// Return many value columns.
     async fn get_many(
         db: &Self::Db,
         one: Self::OneRequest,
     ) -> Result<Vec<Self::ManyResponse>, Error>;
-    /// Create many value columns.
+    /// Return many value columns for each of multiple identifiers, in the same order.
+    ///
+    /// This is used to load the relations of many records with one query instead of a query for each record.
+    /// The identifiers must be unique.
+    async fn get_many_batch(
+        db: &Self::Db,
+        ones: &[Self::OneRequest],
+    ) -> Result<Vec<Vec<Self::ManyResponse>>, Error>
+    where
+        Self::OneRequest: Clone,
+    {
+        let mut many = Vec::with_capacity(ones.len());
+        for one in ones {
+            many.push(Self::get_many(db, one.clone()).await?);
+        }
+        Ok(many)
+    }
+    /// Create many value columns with one query for each batch of values.
+    ///
+    /// A value that is in `many` more than once is only created once.
     async fn create_many(
         db: &Self::Db,
         one: Self::OneRequest,
         many: &[Self::ManyRequest],
     ) -> Result<(), Error>;
+    /// Create many value columns like `create_many` in a transaction, without committing it.
+    async fn create_many_in(
+        db: &Self::Db,
+        transaction: &mut Transaction<Self>,
+        one: Self::OneRequest,
+        many: &[Self::ManyRequest],
+    ) -> Result<(), Error>;
     /// Update many value columns.
+    ///
+    /// Only the values that aren't in `many` are deleted and only the new values are created,
+    /// the pivot columns of the other values are updated. This happens in one transaction.
     async fn update_many(
         db: &Self::Db,
         one: Self::OneRequest,
         many: &[Self::ManyRequest],
     ) -> Result<(), Error>;
+    /// Add value columns, without deleting the other values.
+    ///
+    /// The values that already exist aren't created again, their pivot columns are updated.
+    /// This happens in one transaction.
+    async fn add_many(
+        db: &Self::Db,
+        one: Self::OneRequest,
+        many: &[Self::ManyRequest],
+    ) -> Result<(), Error>;
+    /// Remove value columns with one query for each batch of values.
+    async fn remove_many(
+        db: &Self::Db,
+        one: Self::OneRequest,
+        many: &[Self::ManyId],
+    ) -> Result<(), Error>;
     /// Delete many value columns.
     async fn delete_many(db: &Self::Db, one: Self::OneRequest) -> Result<(), Error>;
 }
 
+/// A collection with a foreign key column to a parent table.
+///
+/// It can be implemented for each foreign key column.
+/// The `AggregateBy` type generic is a marker type for the foreign key column.
+///
+/// This can be used to create one-to-many relationships.
+pub trait Children<AggregateBy>: Collection {
+    /// The identifier of the parent record.
+    type ParentId;
+
+    /// Return the records that belong to a parent record.
+    async fn get_children(
+        db: &Self::Db,
+        parent: Self::ParentId,
+    ) -> Result<Vec<Self::Response>, Error>;
+    /// Return the records that belong to each of multiple parent records, in the same order.
+    ///
+    /// This is used to load the relations of many records with one query instead of a query for each record.
+    /// The identifiers must be unique.
+    async fn get_children_batch(
+        db: &Self::Db,
+        parents: &[Self::ParentId],
+    ) -> Result<Vec<Vec<Self::Response>>, Error>
+    where
+        Self::ParentId: Clone,
+    {
+        let mut children = Vec::with_capacity(parents.len());
+        for parent in parents {
+            children.push(Self::get_children(db, parent.clone()).await?);
+        }
+        Ok(children)
+    }
+}
+
 /// A collection where many records can be aggregated.
 pub trait AggregateMany<AggregateBy>: Collection {
     type OneRequest<'a>;
@@ -148,6 +373,47 @@ pub trait AggregateMany<AggregateBy>: Collection {
         db: &Self::Db,
         one: Self::OneRequest<'a>,
     ) -> Result<Vec<Self::ManyResponse>, Error>;
+    /// Aggregate a page of records, with the cursor of the next page if there is one.
+    async fn aggregate_page(
+        db: &Self::Db,
+        one: Self::OneRequest<'_>,
+    ) -> Result<Page<Self::ManyResponse>, Error> {
+        Self::aggregate_many(db, one).await.map(Page::from)
+    }
+    /// Aggregate many records with only the selected fields.
+    ///
+    /// The fields that aren't selected are placeholders, see [fields](crate::model::fields).
+    async fn aggregate_many_fields(
+        db: &Self::Db,
+        one: Self::OneRequest<'_>,
+        fields: &[&str],
+    ) -> Result<Vec<Self::ManyResponse>, Error> {
+        let _ = fields;
+        Self::aggregate_many(db, one).await
+    }
+    /// Aggregate a page of records with only the selected fields.
+    ///
+    /// The fields that aren't selected are placeholders, see [fields](crate::model::fields).
+    async fn aggregate_page_fields(
+        db: &Self::Db,
+        one: Self::OneRequest<'_>,
+        fields: &[&str],
+    ) -> Result<Page<Self::ManyResponse>, Error> {
+        let _ = fields;
+        Self::aggregate_page(db, one).await
+    }
+}
+/// A collection with statistics of its records, like counts and sums, which can be grouped.
+pub trait Stats: Collection {
+    /// Request for which statistics of which columns.
+    type StatsRequest;
+    /// The statistics of a group of records.
+    type StatsResponse;
+    /// Get the statistics of each group of records, or of all records if not grouped.
+    async fn stats(
+        db: &Self::Db,
+        request: Self::StatsRequest,
+    ) -> Result<Vec<Self::StatsResponse>, Error>;
 }
 /// A collection where a single record can be aggregated.
 pub trait AggregateOne<AggregateBy>: Collection {
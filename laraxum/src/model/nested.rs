@@ -0,0 +1,41 @@
+//! Nested writes of foreign records.
+//!
+//! A nested foreign key column takes either the primary key of an existing foreign record
+//! or a request to create the foreign record, like `{ "address": 1 }`
+//! or `{ "address": { "street": "Main Street" } }`.
+
+use super::{Model, Transaction};
+use crate::ModelError;
+
+/// Request for a foreign record, which is either its primary key or a request to create it.
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Nested<Id, CreateRequest> {
+    /// The primary key of an existing foreign record.
+    Id(Id),
+    /// Request to create the foreign record.
+    Create(CreateRequest),
+}
+impl<Id, CreateRequest> Nested<Id, CreateRequest> {
+    /// Return the primary key of the foreign record, creating it first if needed.
+    ///
+    /// The foreign record is created in the transaction of the record that refers to it,
+    /// so neither is created if the other fails.
+    ///
+    /// # Errors
+    /// - The request to create the foreign record is invalid.
+    /// - The query fails.
+    pub async fn create<T>(
+        self,
+        db: &T::Db,
+        transaction: &mut Transaction<T>,
+    ) -> Result<Id, ModelError<T::CreateRequestError>>
+    where
+        T: Model<Id = Id, CreateRequest = CreateRequest>,
+    {
+        match self {
+            Self::Id(id) => Ok(id),
+            Self::Create(request) => T::create_get_id_in(db, transaction, request).await,
+        }
+    }
+}
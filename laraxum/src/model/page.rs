@@ -0,0 +1,211 @@
+//! Pages of records, with a cursor for keyset pagination or the total for page based pagination.
+//!
+//! A cursor is the sort key and primary key of the last record of a page,
+//! so the next page continues after that record, even if records are inserted or deleted.
+
+use crate::Error;
+
+use core::fmt::Write as _;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// An opaque cursor, like `?cursor=5b3132332c345d`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cursor(String);
+impl Cursor {
+    /// Encode the key of a record into a cursor.
+    ///
+    /// # Errors
+    /// - The key can't be serialized.
+    pub fn encode<K>(key: &K) -> Result<Self, Error>
+    where
+        K: Serialize,
+    {
+        let bytes = serde_json::to_vec(key).map_err(|_| Error::Internal)?;
+        let mut cursor = String::with_capacity(bytes.len().saturating_mul(2));
+        for byte in bytes {
+            let _ = write!(cursor, "{byte:02x}");
+        }
+        Ok(Self(cursor))
+    }
+    /// Decode the key of a record from a cursor.
+    ///
+    /// # Errors
+    /// - The cursor is invalid, which is a bad request.
+    pub fn decode<K>(&self) -> Result<K, Error>
+    where
+        K: DeserializeOwned,
+    {
+        let bytes = self
+            .0
+            .as_bytes()
+            .chunks(2)
+            .map(|chunk| {
+                let chunk = core::str::from_utf8(chunk).ok()?;
+                u8::from_str_radix(chunk, 16).ok()
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(Error::BadRequest)?;
+        serde_json::from_slice(&bytes).map_err(|_| Error::BadRequest)
+    }
+}
+impl AsRef<str> for Cursor {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// The position of a page in all records, for page based pagination.
+#[derive(Clone, Debug, Serialize)]
+pub struct Pagination {
+    /// The page, starting at `0`.
+    pub page: u64,
+    /// How many records are in a page.
+    pub per_page: u64,
+    /// How many records there are in all pages.
+    pub total: u64,
+    /// If the records are in an envelope with the pagination,
+    /// instead of the pagination being in the headers.
+    #[serde(skip)]
+    pub is_envelope: bool,
+    /// The query of the request, which the links to other pages are based on.
+    #[serde(skip)]
+    pub query: Option<String>,
+}
+impl Pagination {
+    /// The last page, which is `0` if there are no records.
+    #[must_use]
+    pub const fn last_page(&self) -> u64 {
+        match self.total.saturating_sub(1).checked_div(self.per_page) {
+            Some(last_page) => last_page,
+            None => 0,
+        }
+    }
+    /// The links to the first, previous, next and last pages, like a [RFC 8288] `Link` header.
+    ///
+    /// The links only have a query, so they are relative to the path of the request.
+    ///
+    /// [RFC 8288]: https://www.rfc-editor.org/rfc/rfc8288
+    #[must_use]
+    pub fn links(&self) -> String {
+        let query = self
+            .query
+            .as_deref()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|parameter| {
+                !parameter.is_empty() && parameter.split('=').next() != Some("page")
+            })
+            .collect::<Vec<&str>>();
+        let link = |page: u64, rel: &str| {
+            let mut link = String::from("<?");
+            for parameter in &query {
+                link.push_str(parameter);
+                link.push('&');
+            }
+            let _ = write!(link, "page={page}>; rel=\"{rel}\"");
+            link
+        };
+
+        let last_page = self.last_page();
+        let mut links = vec![link(0, "first")];
+        if let Some(prev_page) = self.page.checked_sub(1) {
+            links.push(link(prev_page.min(last_page), "prev"));
+        }
+        if self.page < last_page {
+            links.push(link(self.page.saturating_add(1), "next"));
+        }
+        links.push(link(last_page, "last"));
+        links.join(", ")
+    }
+}
+
+/// The records of an aggregation.
+#[derive(Debug)]
+pub struct Page<T> {
+    /// The records.
+    pub data: Vec<T>,
+    /// The cursor of the next page, if there are more records.
+    ///
+    /// Only aggregations with a cursor have one.
+    pub next_cursor: Option<Cursor>,
+    /// The position of the page in all records.
+    ///
+    /// Only aggregations with pages have one.
+    pub pagination: Option<Pagination>,
+}
+impl<T> Page<T> {
+    /// Set the query of the request, which the links to other pages are based on.
+    #[must_use]
+    pub fn with_query(mut self, query: Option<String>) -> Self {
+        if let Some(pagination) = &mut self.pagination {
+            pagination.query = query;
+        }
+        self
+    }
+    /// Map each record of the page.
+    #[must_use]
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            data: self.data.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            pagination: self.pagination,
+        }
+    }
+}
+impl<T> From<Vec<T>> for Page<T> {
+    fn from(data: Vec<T>) -> Self {
+        Self {
+            data,
+            next_cursor: None,
+            pagination: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(s: &str) -> Cursor {
+        Cursor(String::from(s))
+    }
+
+    #[test]
+    fn encode_is_hex_of_json() {
+        let encoded = Cursor::encode(&(123, 4)).unwrap();
+        assert_eq!(encoded.as_ref(), "5b3132332c345d");
+    }
+
+    #[test]
+    fn decode_encoded() {
+        let key = (String::from("Zoë, \"Z\""), 42_u64);
+        let decoded: (String, u64) = Cursor::encode(&key).unwrap().decode().unwrap();
+        assert_eq!(decoded, key);
+    }
+
+    #[test]
+    fn decode_invalid_hex() {
+        assert!(matches!(
+            cursor("5g").decode::<u64>(),
+            Err(Error::BadRequest)
+        ));
+        assert!(matches!(
+            cursor("5b3").decode::<Vec<u64>>(),
+            Err(Error::BadRequest)
+        ));
+        assert!(matches!(
+            cursor("ü").decode::<u64>(),
+            Err(Error::BadRequest)
+        ));
+    }
+
+    #[test]
+    fn decode_other_key() {
+        let encoded = Cursor::encode(&("a", 1)).unwrap();
+        assert!(matches!(
+            encoded.decode::<(u64, u64)>(),
+            Err(Error::BadRequest)
+        ));
+    }
+}
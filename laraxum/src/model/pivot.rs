@@ -0,0 +1,30 @@
+//! Extra columns in the table of a many-to-many relation.
+//!
+//! A [ManyModel](super::ManyModel) table can have pivot columns besides its two foreign keys,
+//! like the role of a user in a group.
+
+/// A foreign record with the pivot columns of its relation.
+///
+/// The fields of both are serialized next to each other.
+/// The foreign record can be converted into a record without the pivot columns,
+/// so a field of the foreign records doesn't have to include them.
+#[derive(Debug, serde::Serialize)]
+pub struct Pivot<T, P> {
+    /// The foreign record.
+    #[serde(flatten)]
+    pub record: T,
+    /// The pivot columns.
+    #[serde(flatten)]
+    pub pivot: P,
+}
+
+/// Request for the primary key of a foreign record with the pivot columns of its relation,
+/// like `{ "id": 1, "role": "admin" }`.
+#[derive(Debug, serde::Deserialize)]
+pub struct PivotRequest<Id, P> {
+    /// The primary key of the foreign record.
+    pub id: Id,
+    /// The pivot columns.
+    #[serde(flatten)]
+    pub pivot: P,
+}
@@ -0,0 +1,29 @@
+//! Polymorphic foreign keys.
+//!
+//! A polymorphic foreign key refers to a record in one of multiple tables.
+//! It is stored as the table and the primary key of the foreign record,
+//! and is serialized as a tagged object, like `{ "type": "user", "id": 1 }`.
+
+/// A polymorphic foreign key, which is an enum with a variant for each foreign table.
+pub trait Poly: Sized {
+    /// The type of the primary keys of the foreign tables.
+    type Id;
+
+    /// Return the name of the table of the foreign record.
+    fn table(&self) -> &'static str;
+    /// Return the primary key of the foreign record.
+    fn id(&self) -> Self::Id;
+    /// Create the foreign key from the name of the table and the primary key,
+    /// or `None` if the table is not one of the foreign tables.
+    fn from_parts(table: &str, id: Self::Id) -> Option<Self>;
+}
+
+/// Decode a polymorphic foreign key from the name of the table and the primary key.
+///
+/// # Errors
+/// - The table is not one of the foreign tables.
+pub fn decode<T: Poly>(table: &str, id: T::Id) -> Result<T, sqlx::Error> {
+    T::from_parts(table, id).ok_or_else(|| {
+        sqlx::Error::Decode(format!("unknown table of polymorphic foreign key: {table}").into())
+    })
+}
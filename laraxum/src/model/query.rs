@@ -0,0 +1,110 @@
+//! Options for running queries.
+
+use crate::{Error, env::EnvError};
+
+use core::time::Duration;
+use std::{collections::HashMap, sync::Arc};
+
+/// Options for running queries.
+#[derive(Clone, Debug, Default)]
+pub struct QueryOptions {
+    /// How long a query can take before it fails with [Error::Timeout].
+    pub timeout: Option<Duration>,
+    /// The timeouts of tables that override `timeout`, by the name of the table.
+    pub table_timeouts: Arc<HashMap<String, Duration>>,
+    /// Queries that take at least this long are logged with their SQL and table.
+    pub slow_threshold: Option<Duration>,
+}
+impl QueryOptions {
+    /// Get the options from environment variables.
+    ///
+    /// - `DATABASE_QUERY_TIMEOUT_MS`
+    /// - `DATABASE_SLOW_QUERY_MS`
+    ///
+    /// # Errors
+    /// - Invalid environment variable.
+    pub fn from_env() -> Result<Self, EnvError> {
+        Ok(Self {
+            timeout: crate::env::env_var_parse_opt!("DATABASE_QUERY_TIMEOUT_MS")?
+                .map(Duration::from_millis),
+            slow_threshold: crate::env::env_var_parse_opt!("DATABASE_SLOW_QUERY_MS")?
+                .map(Duration::from_millis),
+            table_timeouts: Arc::default(),
+        })
+    }
+    /// Set the timeout of a table, like `users`, instead of `timeout`.
+    #[must_use]
+    pub fn with_table_timeout(mut self, table: impl Into<String>, timeout: Duration) -> Self {
+        Arc::make_mut(&mut self.table_timeouts).insert(table.into(), timeout);
+        self
+    }
+    /// The timeout of a table, which is the name of the table with or without the database.
+    #[must_use]
+    pub fn timeout(&self, table: &str) -> Option<Duration> {
+        let table_name = table
+            .rsplit_once('.')
+            .map_or(table, |(_, table_name)| table_name);
+        self.table_timeouts
+            .get(table)
+            .or_else(|| self.table_timeouts.get(table_name))
+            .copied()
+            .or(self.timeout)
+    }
+    /// Run a query with the timeout and log it if it's slow.
+    ///
+    /// # Errors
+    /// - The query takes longer than the timeout.
+    /// - The query fails.
+    pub async fn run<T, E, Fut>(&self, table: &str, sql: &str, query: Fut) -> Result<T, Error>
+    where
+        Fut: Future<Output = Result<T, E>>,
+        E: Into<Error>,
+    {
+        let start = tokio::time::Instant::now();
+        let response = match self.timeout(table) {
+            Some(timeout) => {
+                if let Ok(response) = tokio::time::timeout(timeout, query).await {
+                    response
+                } else {
+                    tracing::warn!(table, sql, ?timeout, "query timed out");
+                    return Err(Error::Timeout);
+                }
+            }
+            None => query.await,
+        };
+        let elapsed = start.elapsed();
+        if self
+            .slow_threshold
+            .is_some_and(|slow_threshold| elapsed >= slow_threshold)
+        {
+            tracing::warn!(table, sql, ?elapsed, "slow query");
+        }
+        response.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_of_table() {
+        let query = QueryOptions {
+            timeout: Some(Duration::from_secs(1)),
+            ..QueryOptions::default()
+        }
+        .with_table_timeout("users", Duration::from_secs(2))
+        .with_table_timeout("database.posts", Duration::from_secs(3));
+        assert_eq!(
+            query.timeout("database.users"),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(query.timeout("users"), Some(Duration::from_secs(2)));
+        assert_eq!(
+            query.timeout("database.posts"),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(query.timeout("database.tags"), Some(Duration::from_secs(1)));
+        assert_eq!(QueryOptions::default().timeout("users"), None);
+    }
+}
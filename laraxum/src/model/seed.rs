@@ -0,0 +1,423 @@
+//! Seed a database with fixtures.
+//!
+//! A fixture is a named record that is created through the
+//! [CreateRequest](crate::Collection::CreateRequest) of its table,
+//! so it is validated just like a request from a client.
+//!
+//! A string that starts with `@` is a reference to another fixture and
+//! is replaced with the id of the record that was created for that fixture,
+//! which is how foreign keys and [ManyModel](crate::ManyModel) links are set.
+//! Use `@@` at the start of a string for a literal `@`.
+//! Fixture names must be unique across all tables, else seeding fails, and
+//! a fixture must be seeded before it can be referenced.
+//!
+//! Seeding is idempotent.
+//! The name of every seeded fixture is stored in the database together with the id of its record,
+//! and a fixture that has already been seeded is not created again,
+//! so the seeds can be run again, for example every time a development environment starts.
+//!
+//! # Formats
+//!
+//! - JSON (`.json`): an object of fixture names and records.
+//! - NDJSON (`.ndjson`, `.jsonl`): one object of fixture names and records per line.
+//! - TOML (`.toml`): a table of fixture names and records, requires the `toml` feature.
+//!
+//! Fixtures are seeded in the order they are written in.
+//!
+//! # Example
+//!
+//! ```json
+//! {
+//!     "home": { "street": "Main Street 1", "postcode": "12345", "city": "Berlin" }
+//! }
+//! ```
+//! ```json
+//! {
+//!     "alice": { "address_id": "@home", "firstname": "Alice", "lastname": "Smith" }
+//! }
+//! ```
+//! ```no_run
+//! # use laraxum::model::seed::{Fixtures, Seed, Seeder};
+//! # use serde::{Serialize, de::DeserializeOwned};
+//! # async fn seed<Address, Contact>(db: &Address::Db) -> Result<(), Box<dyn std::error::Error>>
+//! # where
+//! #     Address: Seed<
+//! #         Db: Sync,
+//! #         CreateRequest: DeserializeOwned,
+//! #         CreateRequestError: core::fmt::Debug + 'static,
+//! #         Id: Serialize,
+//! #     >,
+//! #     Contact: Seed<
+//! #         Db = Address::Db,
+//! #         CreateRequest: DeserializeOwned,
+//! #         CreateRequestError: core::fmt::Debug + 'static,
+//! #         Id: Serialize,
+//! #     >,
+//! # {
+//! let mut seeder = Seeder::new(db);
+//! seeder.seed::<Address>(Fixtures::load("fixtures/addresses.json")?).await?;
+//! seeder.seed::<Contact>(Fixtures::load("fixtures/contacts.json")?).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{Error, ModelError, model::Model};
+
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// A table that can be seeded with fixtures.
+pub trait Seed: Model {
+    /// Return the id of the record created for a fixture, if it has already been seeded.
+    async fn get_seeded(db: &Self::Db, name: &str) -> Result<Option<Self::Id>, Error>;
+    /// Create a record for a fixture and remember it, so it isn't created again.
+    async fn create_seeded(
+        db: &Self::Db,
+        name: &str,
+        rq: Self::CreateRequest,
+    ) -> Result<Self::Id, ModelError<Self::CreateRequestError>>;
+}
+
+/// A named record.
+pub struct Fixture {
+    /// The name that other fixtures use to refer to this fixture.
+    pub name: String,
+    /// The record, in the shape of the [CreateRequest](crate::Collection::CreateRequest).
+    pub record: serde_json::Value,
+}
+
+/// Fixtures in the order they are written in.
+#[derive(Default)]
+pub struct Fixtures(pub Vec<Fixture>);
+impl<'de> Deserialize<'de> for Fixtures {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Fixtures;
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a map of fixture names and records")
+            }
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut fixtures = Vec::with_capacity(map.size_hint().unwrap_or_default());
+                while let Some((name, record)) = map.next_entry()? {
+                    fixtures.push(Fixture { name, record });
+                }
+                Ok(Fixtures(fixtures))
+            }
+        }
+        deserializer.deserialize_map(Visitor)
+    }
+}
+impl Fixtures {
+    /// Parse fixtures from JSON.
+    ///
+    /// # Errors
+    /// - Parsing fails.
+    pub fn from_json(s: &str) -> Result<Self, FixturesError> {
+        serde_json::from_str(s).map_err(FixturesError::Json)
+    }
+    /// Parse fixtures from NDJSON.
+    ///
+    /// # Errors
+    /// - Parsing fails.
+    pub fn from_ndjson(s: &str) -> Result<Self, FixturesError> {
+        let mut fixtures = Self::default();
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            fixtures.0.extend(Self::from_json(line)?.0);
+        }
+        Ok(fixtures)
+    }
+    /// Parse fixtures from TOML.
+    ///
+    /// # Errors
+    /// - Parsing fails.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, FixturesError> {
+        toml::from_str(s).map_err(FixturesError::Toml)
+    }
+    /// Read fixtures from a file.
+    ///
+    /// The format is chosen by the file extension.
+    ///
+    /// # Errors
+    /// - Reading fails.
+    /// - Unknown file extension.
+    /// - Parsing fails.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FixturesError> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)?;
+        match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("json") => Self::from_json(&s),
+            Some("ndjson" | "jsonl") => Self::from_ndjson(&s),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&s),
+            _ => Err(FixturesError::Format(path.to_path_buf())),
+        }
+    }
+}
+
+/// Error when reading fixtures.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FixturesError {
+    #[error("{0}")]
+    Io(
+        #[from]
+        #[source]
+        std::io::Error,
+    ),
+    #[error("{0}")]
+    Json(#[source] serde_json::Error),
+    #[cfg(feature = "toml")]
+    #[error("{0}")]
+    Toml(#[source] toml::de::Error),
+    #[error("unknown fixtures format: {0:?}")]
+    Format(std::path::PathBuf),
+}
+
+/// Error when seeding fixtures.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SeedError<CreateRequestError> {
+    /// A fixture refers to a fixture that hasn't been seeded.
+    #[error("fixture {fixture} refers to unknown fixture {reference}")]
+    UnknownReference { fixture: String, reference: String },
+    /// A fixture has the same name as a fixture of another table.
+    #[error("fixture {fixture} is already a fixture of another table")]
+    DuplicateName { fixture: String },
+    /// A fixture couldn't be deserialized into the request.
+    #[error("fixture {fixture} isn't a valid request: {error}")]
+    Request {
+        fixture: String,
+        #[source]
+        error: serde_json::Error,
+    },
+    /// A fixture couldn't be created.
+    #[error("fixture {fixture} couldn't be created: {error:?}")]
+    Model {
+        fixture: String,
+        error: ModelError<CreateRequestError>,
+    },
+}
+
+/// Seed tables with fixtures and resolve references between them.
+pub struct Seeder<'a, D> {
+    db: &'a D,
+    /// the table and the id of every seeded fixture by its name
+    ids: HashMap<String, (&'static str, serde_json::Value)>,
+}
+impl<'a, D> Seeder<'a, D> {
+    #[must_use]
+    pub fn new(db: &'a D) -> Self {
+        Self {
+            db,
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Seed a table with fixtures.
+    ///
+    /// Fixtures that have already been seeded are skipped,
+    /// but can still be referenced by other fixtures.
+    /// A fixture can't have the name of a fixture of another table.
+    ///
+    /// # Errors
+    /// - See [SeedError].
+    pub async fn seed<T>(
+        &mut self,
+        fixtures: Fixtures,
+    ) -> Result<(), SeedError<T::CreateRequestError>>
+    where
+        D: Sync,
+        T: Seed<Db = D>,
+        T::CreateRequest: DeserializeOwned,
+        T::Id: Serialize,
+    {
+        let table = core::any::type_name::<T>();
+        for Fixture { name, record } in fixtures.0 {
+            self.check_name(table, &name)?;
+            let model_error = |error| SeedError::Model {
+                fixture: name.clone(),
+                error,
+            };
+            let id = match T::get_seeded(self.db, &name).await {
+                Ok(Some(id)) => id,
+                Ok(None) => {
+                    let record = self.resolve(&name, record)?;
+                    let rq =
+                        serde_json::from_value(record).map_err(|error| SeedError::Request {
+                            fixture: name.clone(),
+                            error,
+                        })?;
+                    T::create_seeded(self.db, &name, rq)
+                        .await
+                        .map_err(model_error)?
+                }
+                Err(error) => return Err(model_error(ModelError::Other(error))),
+            };
+            let id = serde_json::to_value(id)
+                .map_err(|_| model_error(ModelError::Other(Error::Internal)))?;
+            self.ids.insert(name, (table, id));
+        }
+        Ok(())
+    }
+
+    /// Check that the name isn't the name of a fixture of another table,
+    /// because references don't have a table.
+    fn check_name<E>(&self, table: &str, fixture: &str) -> Result<(), SeedError<E>> {
+        match self.ids.get(fixture) {
+            Some((seeded_table, _)) if *seeded_table != table => Err(SeedError::DuplicateName {
+                fixture: fixture.to_owned(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replace references with the ids of the fixtures they refer to.
+    fn resolve<E>(
+        &self,
+        fixture: &str,
+        value: serde_json::Value,
+    ) -> Result<serde_json::Value, SeedError<E>> {
+        match value {
+            serde_json::Value::String(s) => {
+                if let Some(s) = s.strip_prefix("@@") {
+                    Ok(serde_json::Value::String(format!("@{s}")))
+                } else if let Some(reference) = s.strip_prefix('@') {
+                    self.ids
+                        .get(reference)
+                        .map(|(_, id)| id.clone())
+                        .ok_or_else(|| SeedError::UnknownReference {
+                            fixture: fixture.to_owned(),
+                            reference: reference.to_owned(),
+                        })
+                } else {
+                    Ok(serde_json::Value::String(s))
+                }
+            }
+            serde_json::Value::Array(values) => values
+                .into_iter()
+                .map(|value| self.resolve(fixture, value))
+                .collect::<Result<_, _>>()
+                .map(serde_json::Value::Array),
+            serde_json::Value::Object(values) => values
+                .into_iter()
+                .map(|(key, value)| Ok((key, self.resolve(fixture, value)?)))
+                .collect::<Result<_, _>>()
+                .map(serde_json::Value::Object),
+            value => Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn names(fixtures: &Fixtures) -> Vec<&str> {
+        fixtures.0.iter().map(|fixture| &*fixture.name).collect()
+    }
+
+    #[test]
+    fn from_json_keeps_order() {
+        let fixtures = Fixtures::from_json(r#"{ "b": { "x": 1 }, "a": { "x": 2 } }"#).unwrap();
+        assert_eq!(names(&fixtures), ["b", "a"]);
+        assert_eq!(fixtures.0[0].record, json!({ "x": 1 }));
+    }
+
+    #[test]
+    fn from_ndjson_skips_empty_lines() {
+        let fixtures =
+            Fixtures::from_ndjson("{ \"a\": {} }\n\n{ \"b\": {}, \"c\": {} }\n").unwrap();
+        assert_eq!(names(&fixtures), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn from_json_fails_for_list() {
+        assert!(matches!(
+            Fixtures::from_json("[]"),
+            Err(FixturesError::Json(_))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml_keeps_order() {
+        let fixtures = Fixtures::from_toml("[b]\nx = 1\n[a]\nx = 2\n").unwrap();
+        assert_eq!(names(&fixtures), ["b", "a"]);
+    }
+
+    #[test]
+    fn load_fails_for_unknown_format() {
+        let path = std::env::temp_dir().join("laraxum_seed_test.yaml");
+        std::fs::write(&path, "").unwrap();
+        let fixtures = Fixtures::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(fixtures, Err(FixturesError::Format(_))));
+    }
+
+    #[test]
+    fn resolve_references() {
+        let mut seeder = Seeder::new(&());
+        seeder
+            .ids
+            .insert("home".to_owned(), ("addresses", json!(1)));
+        seeder
+            .ids
+            .insert("work".to_owned(), ("addresses", json!(2)));
+        let record = json!({
+            "address_id": "@home",
+            "addresses": ["@home", "@work"],
+            "nested": { "address_id": "@work" },
+            "handle": "@@alice",
+            "name": "Alice",
+            "age": 30,
+        });
+        let record = seeder.resolve::<()>("alice", record).unwrap();
+        assert_eq!(
+            record,
+            json!({
+                "address_id": 1,
+                "addresses": [1, 2],
+                "nested": { "address_id": 2 },
+                "handle": "@alice",
+                "name": "Alice",
+                "age": 30,
+            })
+        );
+    }
+
+    #[test]
+    fn resolve_unknown_reference() {
+        let seeder = Seeder::new(&());
+        let error = seeder.resolve::<()>("alice", json!({ "address_id": "@home" }));
+        assert!(matches!(
+            error,
+            Err(SeedError::UnknownReference { fixture, reference })
+                if fixture == "alice" && reference == "home"
+        ));
+    }
+
+    #[test]
+    fn check_name_of_other_table() {
+        let mut seeder = Seeder::new(&());
+        seeder
+            .ids
+            .insert("home".to_owned(), ("addresses", json!(1)));
+        assert!(seeder.check_name::<()>("addresses", "home").is_ok());
+        assert!(seeder.check_name::<()>("contacts", "alice").is_ok());
+        assert!(matches!(
+            seeder.check_name::<()>("contacts", "home"),
+            Err(SeedError::DuplicateName { fixture }) if fixture == "home"
+        ));
+    }
+}
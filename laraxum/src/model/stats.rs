@@ -0,0 +1,33 @@
+//! Values of statistics in query parameters.
+//!
+//! The columns of a statistic are separated by commas as one string,
+//! like `?group_by=status&sum=total,tax`.
+
+use serde::{
+    Deserialize, Deserializer,
+    de::{DeserializeOwned, Error as _, IntoDeserializer, value},
+};
+
+/// Columns separated by commas, like `?sum=total,tax`.
+#[derive(Clone, Debug)]
+pub struct Columns<T>(pub Vec<T>);
+impl<'de, T> Deserialize<'de> for Columns<T>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        let columns = value
+            .split(',')
+            .filter(|column| !column.is_empty())
+            .map(|column| {
+                let column = IntoDeserializer::<value::Error>::into_deserializer(column);
+                T::deserialize(column).map_err(D::Error::custom)
+            });
+        let columns: Result<Vec<T>, D::Error> = columns.collect();
+        columns.map(Self)
+    }
+}
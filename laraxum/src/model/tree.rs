@@ -0,0 +1,207 @@
+//! Trees of records that refer to a parent record in the same table.
+//!
+//! The records of a tree are loaded with one recursive query,
+//! then the ancestors, descendants or subtree are built from them.
+//! A cycle in the records doesn't loop forever, each record is only used once.
+
+use super::Model;
+use crate::Error;
+
+use core::hash::Hash;
+use std::collections::HashMap;
+
+/// A table with a foreign key column to a parent record in the same table.
+///
+/// It can be implemented for each foreign key column.
+/// The `AggregateBy` type generic is a marker type for the foreign key column.
+pub trait Tree<AggregateBy>: Model {
+    /// Return the ancestors of a record, starting with its parent.
+    async fn get_ancestors(db: &Self::Db, id: Self::Id) -> Result<Vec<Self::Response>, Error>;
+    /// Return all descendants of a record.
+    async fn get_descendants(db: &Self::Db, id: Self::Id) -> Result<Vec<Self::Response>, Error>;
+    /// Return a record with its descendants nested in it.
+    async fn get_subtree(db: &Self::Db, id: Self::Id) -> Result<Node<Self::Response>, Error>;
+}
+
+/// A record in a tree with its children.
+#[derive(Debug, serde::Serialize)]
+pub struct Node<T> {
+    pub record: T,
+    pub children: Vec<Self>,
+}
+
+/// Build the ancestors of a record from `(parent, record)` rows, starting with its parent.
+///
+/// # Errors
+/// - The record isn't in the rows.
+pub fn ancestors<Id, T>(
+    id: &Id,
+    rows: Vec<(Option<Id>, T)>,
+    record_id: impl Fn(&T) -> Id,
+) -> Result<Vec<T>, Error>
+where
+    Id: Eq + Hash,
+{
+    let mut rows: HashMap<Id, (Option<Id>, T)> = rows
+        .into_iter()
+        .map(|(parent, record)| (record_id(&record), (parent, record)))
+        .collect();
+    let (mut parent, _) = rows.remove(id).ok_or(Error::NotFound)?;
+    let mut ancestors = vec![];
+    while let Some((grandparent, record)) = parent.and_then(|parent| rows.remove(&parent)) {
+        ancestors.push(record);
+        parent = grandparent;
+    }
+    Ok(ancestors)
+}
+
+/// Build the descendants of a record from `(parent, record)` rows.
+pub fn descendants<Id, T>(
+    id: &Id,
+    rows: Vec<(Option<Id>, T)>,
+    record_id: impl Fn(&T) -> Id,
+) -> Vec<T>
+where
+    Id: Eq,
+{
+    rows.into_iter()
+        .map(|(_, record)| record)
+        .filter(|record| record_id(record) != *id)
+        .collect()
+}
+
+/// Build the subtree of a record from `(parent, record)` rows.
+///
+/// # Errors
+/// - The record isn't in the rows.
+pub fn subtree<Id, T>(
+    id: &Id,
+    rows: Vec<(Option<Id>, T)>,
+    record_id: impl Fn(&T) -> Id,
+) -> Result<Node<T>, Error>
+where
+    Id: Eq + Hash,
+{
+    fn node<Id, T>(
+        record: T,
+        children: &mut HashMap<Id, Vec<T>>,
+        record_id: &impl Fn(&T) -> Id,
+    ) -> Node<T>
+    where
+        Id: Eq + Hash,
+    {
+        let record_children = children.remove(&record_id(&record)).unwrap_or_default();
+        let record_children = record_children
+            .into_iter()
+            .map(|child| node(child, children, record_id))
+            .collect();
+        Node {
+            record,
+            children: record_children,
+        }
+    }
+
+    let mut root = None;
+    let mut children = HashMap::<Id, Vec<T>>::new();
+    for (parent, record) in rows {
+        if record_id(&record) == *id {
+            root = Some(record);
+        } else if let Some(parent) = parent {
+            children.entry(parent).or_default().push(record);
+        }
+    }
+    let root = root.ok_or(Error::NotFound)?;
+    Ok(node(root, &mut children, &record_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Row = (Option<u64>, (u64, &'static str));
+
+    fn record_id(record: &(u64, &'static str)) -> u64 {
+        record.0
+    }
+
+    fn ids(node: &Node<(u64, &'static str)>) -> (u64, Vec<(u64, Vec<u64>)>) {
+        let children = node
+            .children
+            .iter()
+            .map(|child| {
+                let grandchildren = child.children.iter().map(|child| child.record.0).collect();
+                (child.record.0, grandchildren)
+            })
+            .collect();
+        (node.record.0, children)
+    }
+
+    /// `1 -> 2 -> 3 -> 1`
+    fn cycle() -> Vec<Row> {
+        vec![
+            (Some(3), (1, "a")),
+            (Some(1), (2, "b")),
+            (Some(2), (3, "c")),
+        ]
+    }
+
+    #[test]
+    fn ancestors_starting_with_parent() {
+        let rows = vec![(None, (1, "a")), (Some(1), (2, "b")), (Some(2), (3, "c"))];
+        let ancestors = ancestors(&3, rows, record_id).unwrap();
+        assert_eq!(ancestors, [(2, "b"), (1, "a")]);
+    }
+
+    #[test]
+    fn ancestors_of_cycle_terminate() {
+        let ancestors = ancestors(&1, cycle(), record_id).unwrap();
+        assert_eq!(ancestors, [(3, "c"), (2, "b")]);
+    }
+
+    #[test]
+    fn ancestors_of_missing_record() {
+        assert!(matches!(
+            ancestors(&4, cycle(), record_id),
+            Err(Error::NotFound)
+        ));
+    }
+
+    #[test]
+    fn descendants_without_record() {
+        let descendants = descendants(&1, cycle(), record_id);
+        assert_eq!(descendants, [(2, "b"), (3, "c")]);
+    }
+
+    #[test]
+    fn subtree_nests_children() {
+        let rows = vec![
+            (None, (1, "a")),
+            (Some(1), (2, "b")),
+            (Some(1), (3, "c")),
+            (Some(2), (4, "d")),
+        ];
+        let subtree = subtree(&1, rows, record_id).unwrap();
+        assert_eq!(ids(&subtree), (1, vec![(2, vec![4]), (3, vec![])]));
+    }
+
+    #[test]
+    fn subtree_of_cycle_terminates() {
+        let subtree = subtree(&1, cycle(), record_id).unwrap();
+        assert_eq!(ids(&subtree), (1, vec![(2, vec![3])]));
+        let [child] = &*subtree.children else {
+            panic!("one child");
+        };
+        let [grandchild] = &*child.children else {
+            panic!("one grandchild");
+        };
+        assert!(grandchild.children.is_empty());
+    }
+
+    #[test]
+    fn subtree_of_missing_record() {
+        assert!(matches!(
+            subtree(&4, cycle(), record_id),
+            Err(Error::NotFound)
+        ));
+    }
+}
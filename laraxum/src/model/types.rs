@@ -47,6 +47,12 @@ macro_rules! transparent_encode_decode {
                 <$inner as $crate::model::types::Encode>::encode(encode)
             }
         }
+        impl $crate::model::types::Unselected for $ty {
+            #[inline]
+            fn unselected() -> Self {
+                ($decode)(<$inner as $crate::model::types::Unselected>::unselected())
+            }
+        }
         $( $crate::transparent_encode_decode! { $($tt)+ } )?
     };
 }
@@ -189,6 +195,32 @@ pub trait Encode {
     type Encode;
     fn encode(self) -> Self::Encode;
 }
+/// A placeholder for a value that isn't selected from the database.
+///
+/// It is never in the response, see [fields](crate::model::fields).
+pub trait Unselected {
+    fn unselected() -> Self;
+}
+impl<T> Unselected for Option<T> {
+    #[inline]
+    fn unselected() -> Self {
+        None
+    }
+}
+
+/// Implement [Unselected] with the default value.
+macro_rules! unselected_default {
+    { $($ty:ty),* $(,)? } => {
+        $(
+            impl Unselected for $ty {
+                #[inline]
+                fn unselected() -> Self {
+                    <$ty as ::core::default::Default>::default()
+                }
+            }
+        )*
+    };
+}
 
 crate::transparent_encode_decode! {
     String,
@@ -203,6 +235,20 @@ crate::transparent_encode_decode! {
     f32,
     f64,
 }
+unselected_default! {
+    String,
+    bool,
+    u8,
+    i8,
+    u16,
+    i16,
+    u32,
+    i32,
+    u64,
+    i64,
+    f32,
+    f64,
+}
 #[cfg(feature = "time")]
 crate::transparent_encode_decode! {
     time::OffsetDateTime,
@@ -211,6 +257,38 @@ crate::transparent_encode_decode! {
     time::Time,
     time::Duration,
 }
+#[cfg(feature = "time")]
+unselected_default! {
+    time::Duration,
+}
+#[cfg(feature = "time")]
+impl Unselected for time::OffsetDateTime {
+    #[inline]
+    fn unselected() -> Self {
+        Self::UNIX_EPOCH
+    }
+}
+#[cfg(feature = "time")]
+impl Unselected for time::PrimitiveDateTime {
+    #[inline]
+    fn unselected() -> Self {
+        Self::new(time::Date::MIN, time::Time::MIDNIGHT)
+    }
+}
+#[cfg(feature = "time")]
+impl Unselected for time::Date {
+    #[inline]
+    fn unselected() -> Self {
+        Self::MIN
+    }
+}
+#[cfg(feature = "time")]
+impl Unselected for time::Time {
+    #[inline]
+    fn unselected() -> Self {
+        Self::MIDNIGHT
+    }
+}
 #[cfg(feature = "chrono")]
 crate::transparent_encode_decode! {
     chrono::DateTime::<chrono::Utc>,
@@ -220,6 +298,15 @@ crate::transparent_encode_decode! {
     chrono::NaiveTime,
     chrono::TimeDelta,
 }
+#[cfg(feature = "chrono")]
+unselected_default! {
+    chrono::DateTime::<chrono::Utc>,
+    chrono::DateTime::<chrono::Local>,
+    chrono::NaiveDateTime,
+    chrono::NaiveDate,
+    chrono::NaiveTime,
+    chrono::TimeDelta,
+}
 
 // mysql stores `bool`s as `i8`, so we need to convert it.
 impl Decode for bool {
@@ -151,7 +151,7 @@ impl TryFrom<&Type> for TyCompound {
     }
 }
 
-#[derive(darling::FromMeta)]
+#[derive(darling::FromMeta, Clone)]
 pub struct ColumnAttrTyCollection {
     #[darling(
         and_then = "crate::utils::syn::TokenStreamAttr::transform",
@@ -165,17 +165,38 @@ pub struct ColumnAttrTyCollection {
     pub aggregate_rs_ty: Option<Ident>,
 }
 
-#[derive(darling::FromMeta, Default)]
+#[derive(darling::FromMeta, Clone)]
+pub struct ColumnAttrTyChildren {
+    #[darling(
+        and_then = "crate::utils::syn::TokenStreamAttr::transform",
+        rename = "model"
+    )]
+    pub model_rs_name: Ident,
+    #[darling(
+        and_then = "crate::utils::syn::TokenStreamAttr::transform",
+        rename = "by"
+    )]
+    pub by_rs_name: Ident,
+}
+
+#[derive(darling::FromMeta, Default, Clone)]
 #[darling(default)]
 pub struct ColumnAttrTyCompound {
     pub many: Option<ColumnAttrTyCollection>,
+    #[darling(rename = "lazy")]
+    pub is_lazy: bool,
+    #[darling(rename = "nested")]
+    pub is_nested: bool,
+    pub join_depth: Option<usize>,
+    pub poly: Option<darling::util::PathList>,
 }
 
-#[derive(darling::FromMeta)]
+#[derive(darling::FromMeta, Clone)]
 #[darling(rename_all = "snake_case")]
 pub enum ColumnAttrTy {
     #[darling(rename = "foreign")]
     Compound(ColumnAttrTyCompound),
+    Children(ColumnAttrTyChildren),
 
     Id,
 
@@ -187,7 +208,7 @@ pub enum ColumnAttrTy {
     OnUpdate,
 }
 
-#[derive(darling::FromMeta, Default)]
+#[derive(darling::FromMeta, Default, Clone)]
 #[darling(default)]
 pub struct ColumnAttrResponse {
     pub name: Option<String>,
@@ -195,7 +216,7 @@ pub struct ColumnAttrResponse {
     pub skip: bool,
 }
 
-#[derive(darling::FromMeta, Default)]
+#[derive(darling::FromMeta, Default, Clone)]
 #[darling(default)]
 pub struct Validate {
     #[darling(skip)]
@@ -207,7 +228,7 @@ pub struct Validate {
     pub matches: Option<syn::PatRange>,
 }
 
-#[derive(darling::FromMeta, Default)]
+#[derive(darling::FromMeta, Default, Clone)]
 #[darling(default)]
 pub struct ColumnAttrRequest {
     pub name: Option<String>,
@@ -225,9 +246,19 @@ pub enum ColumnAttrAggregateFilter {
     Lt,
     Gte,
     Lte,
+    Ne,
+    In,
+    Between,
+    IsNull,
+    NotNull,
+    StartsWith,
+    EndsWith,
+    Ilike,
+    /// full-text search over this column and the other `columns`
+    Search,
 }
 impl ColumnAttrAggregateFilter {
-    pub fn parameter(&self) -> Option<&'static str> {
+    pub const fn parameter(self) -> Option<&'static str> {
         match self {
             Self::None => None,
             Self::Eq => Some("eq"),
@@ -236,14 +267,36 @@ impl ColumnAttrAggregateFilter {
             Self::Lt => Some("lt"),
             Self::Gte => Some("gte"),
             Self::Lte => Some("lte"),
+            Self::Ne => Some("ne"),
+            Self::In => Some("in"),
+            Self::Between => Some("between"),
+            Self::IsNull => Some("is_null"),
+            Self::NotNull => Some("not_null"),
+            Self::StartsWith => Some("starts_with"),
+            Self::EndsWith => Some("ends_with"),
+            Self::Ilike => Some("ilike"),
+            Self::Search => Some("search"),
         }
     }
-    pub fn is_none(&self) -> bool {
+    pub const fn is_none(self) -> bool {
         matches!(self, Self::None)
     }
-    pub fn is_eq(&self) -> bool {
+    pub const fn is_eq(self) -> bool {
         matches!(self, Self::Eq)
     }
+    pub const fn is_in(self) -> bool {
+        matches!(self, Self::In)
+    }
+    pub const fn is_search(self) -> bool {
+        matches!(self, Self::Search)
+    }
+    /// If the column is compared to a value of the type of the column.
+    pub const fn is_value(self) -> bool {
+        !matches!(
+            self,
+            Self::None | Self::In | Self::Between | Self::IsNull | Self::NotNull | Self::Search
+        )
+    }
 }
 
 #[derive(darling::FromMeta, Default, Clone, Copy)]
@@ -254,19 +307,30 @@ pub enum ColumnAttrAggregateLimit {
     Limit,
     Page {
         per_page: u64,
+        /// the client can choose how many per page, up to this many
+        #[darling(default)]
+        max_per_page: Option<u64>,
+        /// the records are in an envelope with the pagination, instead of in headers
+        #[darling(rename = "envelope", default)]
+        is_envelope: bool,
     },
+    /// keyset pagination, after the column and ID of the last record
+    Cursor,
 }
 impl ColumnAttrAggregateLimit {
-    pub fn parameter(&self) -> Option<&'static str> {
+    pub const fn parameter(self) -> Option<&'static str> {
         match self {
             Self::None => None,
-            Self::Limit => Some("limit"),
+            Self::Limit | Self::Cursor => Some("limit"),
             Self::Page { .. } => Some("page"),
         }
     }
+    pub const fn is_cursor(self) -> bool {
+        matches!(self, Self::Cursor)
+    }
 }
 
-#[derive(darling::FromMeta)]
+#[derive(darling::FromMeta, Clone)]
 pub struct ColumnAttrAggregate {
     #[darling(
         rename = "name",
@@ -275,6 +339,9 @@ pub struct ColumnAttrAggregate {
     pub rs_name: Ident,
     #[darling(default)]
     pub filter: ColumnAttrAggregateFilter,
+    /// the other columns that are searched with this column
+    #[darling(default)]
+    pub columns: darling::util::PathList,
     #[darling(rename = "sort", default)]
     pub is_sort: bool,
     #[darling(default)]
@@ -283,7 +350,7 @@ pub struct ColumnAttrAggregate {
     pub is_pub: bool,
 }
 
-#[derive(darling::FromAttributes, Default)]
+#[derive(darling::FromAttributes, Default, Clone)]
 #[darling(attributes(db), forward_attrs(doc, allow), default)]
 pub struct ColumnAttr {
     pub name: Option<String>,
@@ -305,6 +372,10 @@ pub struct ColumnAttr {
     pub aggregates: Vec<ColumnAttrAggregate>,
     #[darling(and_then = "crate::utils::syn::TokenStreamAttr::transform_option")]
     pub struct_name: Option<Ident>,
+    #[darling(rename = "embed")]
+    pub is_embed: bool,
+    #[darling(rename = "stat")]
+    pub is_stat: darling::util::Flag,
     pub attrs: Vec<Attribute>,
 }
 impl ColumnAttr {
@@ -313,6 +384,7 @@ impl ColumnAttr {
     }
 }
 
+#[derive(Clone)]
 pub struct Column {
     pub rs_name: Ident,
     pub rs_ty: Box<Type>,
@@ -375,6 +447,13 @@ pub struct TableAttr {
         and_then = "crate::utils::syn::TokenStreamAttr::transform_option"
     )]
     pub aggregate_rs_name: Option<Ident>,
+    #[darling(
+        rename = "filter_name",
+        and_then = "crate::utils::syn::TokenStreamAttr::transform_option"
+    )]
+    pub filter_rs_name: Option<Ident>,
+    #[darling(rename = "embed", default)]
+    pub is_embed: bool,
 
     pub attrs: Vec<Attribute>,
     // TODO: this was removed for simplicity, add it back
@@ -434,6 +513,18 @@ impl TryFrom<Item> for Table {
 #[derive(darling::FromMeta)]
 pub struct DbAttr {
     pub name: Option<String>,
+    #[darling(rename = "checked", default = "Self::is_checked_default")]
+    pub is_checked: bool,
+    #[darling(default = "Self::join_depth_default")]
+    pub join_depth: usize,
+}
+impl DbAttr {
+    const fn is_checked_default() -> bool {
+        !cfg!(feature = "unchecked")
+    }
+    const fn join_depth_default() -> usize {
+        3
+    }
 }
 impl TryFrom<proc_macro2::TokenStream> for DbAttr {
     type Error = syn::Error;
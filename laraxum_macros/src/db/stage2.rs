@@ -9,6 +9,7 @@ const TABLE_MUST_NOT_HAVE_ID: &str = "table must not have an ID";
 const TABLE_MUST_NOT_HAVE_MULTIPLE_IDS: &str = "table must not have multiple IDs";
 const TABLE_MUST_IMPLEMENT_MODEL: &str = "table must implement model to implement controller";
 const TABLE_MUST_HAVE_TWO_COLUMNS: &str = "table must have two columns";
+const PIVOT_COLUMN_MUST_NOT_BE_FOREIGN_KEY: &str = "pivot column must not be foreign key";
 const TABLE_DOES_NOT_EXIST: &str = "table does not exist";
 const TABLE_MUST_NOT_IMPLEMENT_CONTROLLER: &str = "table must not implement controller";
 const ID_MUST_BE_INT: &str = "ID must be int";
@@ -18,6 +19,23 @@ const COLUMN_MUST_NOT_BE_OPTIONAL: &str = "column must not be optional";
 const COLUMN_MUST_NOT_BE_UNIQUE: &str = "column must not be unique";
 const COLUMN_MUST_BE_VEC: &str = "column must be Vec";
 const COLUMN_MUST_SPECIFY_INTERMEDIATE_TABLE: &str = "column must specify intermediate table";
+const COLUMN_MUST_BE_FOREIGN_KEY: &str = "column must be foreign key to this table";
+const COLUMN_MUST_NOT_BE_LAZY: &str = "column must not be lazy";
+const COLUMN_MUST_NOT_BE_NESTED: &str = "column must not be nested";
+const COLUMN_MUST_NOT_BE_EMBEDDED: &str = "column must not be embedded";
+const EMBED_DOES_NOT_EXIST: &str = "embedded struct does not exist";
+const EMBED_MUST_NOT_BE_TABLE: &str = "embedded struct must not have table attributes";
+const EMBED_COLUMN_MUST_BE_VALUE: &str = "embedded column must be value";
+const POLY_MUST_HAVE_TABLES: &str = "polymorphic foreign key must have tables";
+const POLY_IDS_MUST_BE_SAME_TYPE: &str =
+    "tables of polymorphic foreign key must have IDs of the same type";
+const CURSOR_COLUMN_MUST_BE_VALUE: &str = "cursor column must be value";
+const PER_PAGE_MUST_NOT_BE_MORE_THAN_MAX: &str = "per page must not be more than max per page";
+const COLUMN_DOES_NOT_EXIST: &str = "column does not exist";
+const COLUMNS_MUST_BE_SEARCHED: &str = "other columns must only be used by search filter";
+const STAT_COLUMN_MUST_BE_VALUE: &str = "stat column must be value";
+const STAT_COLUMN_MUST_NOT_BE_COUNT: &str = "stat column must not be named count";
+const FILTER_MUST_NOT_HAVE_CURSOR: &str = "public aggregator of filter must not have cursor";
 
 #[expect(non_camel_case_types)]
 #[derive(Clone)]
@@ -141,11 +159,18 @@ enum ColumnAttrTyElement {
     AutoTime(AutoTimeEvent),
 }
 
-pub use stage1::ColumnAttrTyCollection;
+pub use stage1::{ColumnAttrTyChildren, ColumnAttrTyCollection};
 
 enum ColumnAttrTyCompound {
-    One,
+    One {
+        is_lazy: bool,
+        is_nested: bool,
+        join_depth: Option<usize>,
+    },
     Many(ColumnAttrTyCollection),
+    Children(ColumnAttrTyChildren),
+    LazyMany,
+    NestedMany,
 }
 
 enum ColumnAttrTy {
@@ -158,12 +183,34 @@ impl From<Option<stage1::ColumnAttrTy>> for ColumnAttrTy {
         use ColumnAttrTyElement as CATE;
         use stage1::ColumnAttrTy as S1CAT;
         match attr_ty {
-            Some(S1CAT::Compound(stage1::ColumnAttrTyCompound { many: None })) => {
-                Self::Compound(CATC::One)
-            }
-            Some(S1CAT::Compound(stage1::ColumnAttrTyCompound { many: Some(many) })) => {
-                Self::Compound(CATC::Many(many))
-            }
+            Some(S1CAT::Compound(stage1::ColumnAttrTyCompound {
+                many: None,
+                is_lazy,
+                is_nested,
+                join_depth,
+                ..
+            })) => Self::Compound(CATC::One {
+                is_lazy,
+                is_nested,
+                join_depth,
+            }),
+            Some(S1CAT::Compound(stage1::ColumnAttrTyCompound {
+                many: Some(many),
+                is_lazy: false,
+                is_nested: false,
+                ..
+            })) => Self::Compound(CATC::Many(many)),
+            Some(S1CAT::Compound(stage1::ColumnAttrTyCompound {
+                many: Some(_),
+                is_lazy: true,
+                ..
+            })) => Self::Compound(CATC::LazyMany),
+            Some(S1CAT::Compound(stage1::ColumnAttrTyCompound {
+                many: Some(_),
+                is_nested: true,
+                ..
+            })) => Self::Compound(CATC::NestedMany),
+            Some(S1CAT::Children(children)) => Self::Compound(CATC::Children(children)),
 
             None => Self::Element(CATE::None),
             Some(S1CAT::Id) => Self::Element(CATE::Id),
@@ -189,6 +236,73 @@ pub enum TyElement {
     AutoTime(TyElementAutoTime),
 }
 impl TyElement {
+    fn try_new(
+        attr_ty_element: ColumnAttrTyElement,
+        real_rs_ty: &Type,
+        rs_ty: &Type,
+        is_unique: bool,
+    ) -> syn::Result<Self> {
+        use ColumnAttrTyElement as CATE;
+        let ty_element_value = stage1::TyElementValue::try_from(real_rs_ty)?;
+        let ty_element_value = TyElementValue::new(ty_element_value, is_unique);
+        match attr_ty_element {
+            CATE::None => Ok(Self::Value(ty_element_value)),
+            CATE::Id => {
+                let TyElementValue {
+                    ty,
+                    is_optional,
+                    is_unique,
+                } = ty_element_value;
+                if is_unique {
+                    return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_UNIQUE));
+                }
+                let AtomicTy::Int(ty) = ty else {
+                    return Err(syn::Error::new(rs_ty.span(), ID_MUST_BE_INT));
+                };
+                if is_optional {
+                    return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_OPTIONAL));
+                }
+                Ok(Self::Id(ty))
+            }
+            CATE::String(atomic_ty_string) => {
+                let TyElementValue {
+                    ty,
+                    is_optional,
+                    is_unique,
+                } = ty_element_value;
+                let AtomicTy::String(_) = ty else {
+                    return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_BE_STRING));
+                };
+
+                Ok(Self::Value(TyElementValue {
+                    ty: AtomicTy::String(atomic_ty_string),
+                    is_optional,
+                    is_unique,
+                }))
+            }
+            CATE::AutoTime(auto_time_event) => {
+                let TyElementValue {
+                    ty,
+                    is_optional,
+                    is_unique,
+                } = ty_element_value;
+                if is_unique {
+                    return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_UNIQUE));
+                }
+                let AtomicTy::Time(ty) = ty else {
+                    return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_BE_TIME));
+                };
+                if is_optional {
+                    return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_OPTIONAL));
+                }
+
+                Ok(Self::AutoTime(TyElementAutoTime {
+                    ty,
+                    event: auto_time_event,
+                }))
+            }
+        }
+    }
     pub const fn is_optional(&self) -> bool {
         matches!(self, Self::Value(value) if value.is_optional)
     }
@@ -217,6 +331,16 @@ impl TyElement {
     pub const fn is_updatable(&self) -> bool {
         matches!(self, Self::Value(_))
     }
+    /// If the column can be summed and averaged.
+    pub const fn is_number(&self) -> bool {
+        matches!(
+            self,
+            Self::Value(TyElementValue {
+                ty: AtomicTy::Int(_) | AtomicTy::Float(_),
+                ..
+            })
+        )
+    }
     pub const fn max_len(&self) -> Option<u16> {
         match self {
             Self::Value(TyElementValue {
@@ -229,8 +353,18 @@ impl TyElement {
 }
 
 pub enum TyCompoundMultiplicity {
-    One { is_optional: bool, is_unique: bool },
+    One {
+        is_optional: bool,
+        is_unique: bool,
+        /// if the response only has the id unless it's expanded
+        is_lazy: bool,
+        /// if the create request can have a request to create the foreign record
+        is_nested: bool,
+        /// overrides the `join_depth` of the database for this foreign key
+        join_depth: Option<usize>,
+    },
     Many(ColumnAttrTyCollection),
+    Children(ColumnAttrTyChildren),
 }
 impl TyCompoundMultiplicity {
     pub const fn is_optional(&self) -> bool {
@@ -246,6 +380,73 @@ pub struct TyCompound {
     pub multiplicity: TyCompoundMultiplicity,
 }
 impl TyCompound {
+    fn try_new(
+        attr_ty_compound: ColumnAttrTyCompound,
+        real_rs_ty: &Type,
+        is_unique: bool,
+    ) -> syn::Result<Self> {
+        use ColumnAttrTyCompound as CATC;
+        use TyCompoundMultiplicity as TCM;
+        use multiplicity::Multiplicity as M;
+        let stage1::TyCompound {
+            ty,
+            multiplicity: ty_compound_multiplicity,
+        } = stage1::TyCompound::try_from(real_rs_ty)?;
+        let ty_compound_multiplicity = match (attr_ty_compound, ty_compound_multiplicity) {
+            (
+                CATC::One {
+                    is_lazy,
+                    is_nested,
+                    join_depth,
+                },
+                M::One,
+            ) => TCM::One {
+                is_optional: false,
+                is_unique,
+                is_lazy,
+                is_nested,
+                join_depth,
+            },
+            (
+                CATC::One {
+                    is_lazy,
+                    is_nested,
+                    join_depth,
+                },
+                M::OneOrZero,
+            ) => TCM::One {
+                is_optional: true,
+                is_unique,
+                is_lazy,
+                is_nested,
+                join_depth,
+            },
+            (CATC::One { .. }, M::Many) => {
+                return Err(syn::Error::new(
+                    real_rs_ty.span(),
+                    COLUMN_MUST_SPECIFY_INTERMEDIATE_TABLE,
+                ));
+            }
+            (CATC::Many(many), M::Many) => TCM::Many(many),
+            (CATC::Children(children), M::Many) => TCM::Children(children),
+            (CATC::Many(_) | CATC::Children(_), _) => {
+                return Err(syn::Error::new(real_rs_ty.span(), COLUMN_MUST_BE_VEC));
+            }
+            (CATC::LazyMany, _) => {
+                return Err(syn::Error::new(real_rs_ty.span(), COLUMN_MUST_NOT_BE_LAZY));
+            }
+            (CATC::NestedMany, _) => {
+                return Err(syn::Error::new(
+                    real_rs_ty.span(),
+                    COLUMN_MUST_NOT_BE_NESTED,
+                ));
+            }
+        };
+        Ok(Self {
+            rs_ty_name: ty,
+            multiplicity: ty_compound_multiplicity,
+        })
+    }
     pub const fn is_optional(&self) -> bool {
         self.multiplicity.is_optional()
     }
@@ -298,6 +499,27 @@ pub use stage1::ColumnAttrRequest;
 pub use stage1::ColumnAttrResponse;
 pub use stage1::Validate;
 
+/// A column of an embedded struct, which is flattened into the table.
+pub struct ColumnEmbed {
+    /// the name of the field of the embedded struct in the rust struct, for example `address`
+    pub rs_name: Ident,
+    /// the name of the embedded struct, for example `Address`
+    pub rs_ty_name: Ident,
+    /// the name of the column in the embedded struct, for example `street`
+    pub column_rs_name: Ident,
+    /// the part of the polymorphic foreign key, if the embedded struct is one
+    pub poly: Option<ColumnPoly>,
+}
+
+/// A part of a polymorphic foreign key.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColumnPoly {
+    /// the column for the table of the foreign record
+    Table,
+    /// the column for the ID of the foreign record
+    Id,
+}
+
 pub struct Column {
     /// the name of the column in the database
     pub name: String,
@@ -319,6 +541,10 @@ pub struct Column {
     pub aggregates: Vec<ColumnAttrAggregate>,
     /// struct name
     pub struct_name: Option<Ident>,
+    /// the embedded struct of the column
+    pub embed: Option<ColumnEmbed>,
+    /// if there are statistics of the column
+    pub is_stat: bool,
 
     pub rs_attrs: Vec<Attribute>,
 }
@@ -327,7 +553,7 @@ impl TryFrom<stage1::Column> for Column {
     fn try_from(column: stage1::Column) -> Result<Self, Self::Error> {
         let stage1::Column {
             rs_name,
-            rs_ty,
+            mut rs_ty,
             attr:
                 stage1::ColumnAttr {
                     name,
@@ -340,6 +566,8 @@ impl TryFrom<stage1::Column> for Column {
                     borrow,
                     aggregates,
                     struct_name,
+                    is_embed: _,
+                    is_stat,
                     attrs: rs_attrs,
                 },
         } = column;
@@ -353,100 +581,32 @@ impl TryFrom<stage1::Column> for Column {
         let attr_ty = ColumnAttrTy::from(attr_ty);
         let ty = match attr_ty {
             ColumnAttrTy::Compound(attr_ty_compound) => {
-                use ColumnAttrTyCompound as CATC;
-                use TyCompoundMultiplicity as TCM;
-                use multiplicity::Multiplicity as M;
-                let stage1::TyCompound {
-                    ty,
-                    multiplicity: ty_compound_multiplicity,
-                } = stage1::TyCompound::try_from(real_rs_ty)?;
-                let ty_compound_multiplicity = match (attr_ty_compound, ty_compound_multiplicity) {
-                    (CATC::One, M::One) => TCM::One {
-                        is_optional: false,
-                        is_unique,
-                    },
-                    (CATC::One, M::OneOrZero) => TCM::One {
-                        is_optional: true,
-                        is_unique,
-                    },
-                    (CATC::One, M::Many) => {
-                        return Err(syn::Error::new(
-                            real_rs_ty.span(),
-                            COLUMN_MUST_SPECIFY_INTERMEDIATE_TABLE,
-                        ));
-                    }
-                    (CATC::Many(many), M::Many) => TCM::Many(many),
-                    (CATC::Many(_), _) => {
-                        return Err(syn::Error::new(real_rs_ty.span(), COLUMN_MUST_BE_VEC));
-                    }
-                };
-                TyMolecule::Compound(TyCompound {
-                    rs_ty_name: ty,
-                    multiplicity: ty_compound_multiplicity,
-                })
-            }
-            ColumnAttrTy::Element(attr_ty_element) => {
-                use ColumnAttrTyElement as CATE;
-                let ty_element_value = stage1::TyElementValue::try_from(real_rs_ty)?;
-                let ty_element_value = TyElementValue::new(ty_element_value, is_unique);
-                match attr_ty_element {
-                    CATE::None => TyMolecule::Element(TyElement::Value(ty_element_value)),
-                    CATE::Id => {
-                        let TyElementValue {
-                            ty,
-                            is_optional,
-                            is_unique,
-                        } = ty_element_value;
-                        if is_unique {
-                            return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_UNIQUE));
-                        }
-                        let AtomicTy::Int(ty) = ty else {
-                            return Err(syn::Error::new(rs_ty.span(), ID_MUST_BE_INT));
-                        };
-                        if is_optional {
-                            return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_OPTIONAL));
-                        }
-                        TyMolecule::Element(TyElement::Id(ty))
-                    }
-                    CATE::String(atomic_ty_string) => {
-                        let TyElementValue {
-                            ty,
-                            is_optional,
-                            is_unique,
-                        } = ty_element_value;
-                        let AtomicTy::String(_) = ty else {
-                            return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_BE_STRING));
-                        };
-
-                        TyMolecule::Element(TyElement::Value(TyElementValue {
-                            ty: AtomicTy::String(atomic_ty_string),
-                            is_optional,
-                            is_unique,
-                        }))
-                    }
-                    CATE::AutoTime(auto_time_event) => {
-                        let TyElementValue {
-                            ty,
-                            is_optional,
-                            is_unique,
-                        } = ty_element_value;
-                        if is_unique {
-                            return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_UNIQUE));
-                        }
-                        let AtomicTy::Time(ty) = ty else {
-                            return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_BE_TIME));
-                        };
-                        if is_optional {
-                            return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_OPTIONAL));
-                        }
-
-                        TyMolecule::Element(TyElement::AutoTime(TyElementAutoTime {
-                            ty,
-                            event: auto_time_event,
-                        }))
-                    }
+                let ty_compound = TyCompound::try_new(attr_ty_compound, real_rs_ty, is_unique)?;
+                // the response only has the id, which can be expanded to the foreign record
+                if let TyCompoundMultiplicity::One {
+                    is_optional,
+                    is_lazy: true,
+                    ..
+                } = ty_compound.multiplicity
+                {
+                    let ty = &ty_compound.rs_ty_name;
+                    let expand_rs_ty: Type = syn::parse_quote!(
+                        ::laraxum::model::Expand<<#ty as ::laraxum::Model>::Id, #ty>
+                    );
+                    rs_ty = Box::new(if is_optional {
+                        syn::parse_quote!(::core::option::Option<#expand_rs_ty>)
+                    } else {
+                        expand_rs_ty
+                    });
                 }
+                TyMolecule::Compound(ty_compound)
             }
+            ColumnAttrTy::Element(attr_ty_element) => TyMolecule::Element(TyElement::try_new(
+                attr_ty_element,
+                real_rs_ty,
+                &rs_ty,
+                is_unique,
+            )?),
         };
 
         let max_len_validate_rule = ty.max_len().map(usize::from);
@@ -463,6 +623,202 @@ impl TryFrom<stage1::Column> for Column {
             borrow,
             aggregates,
             struct_name,
+            embed: None,
+            is_stat: is_stat.is_present(),
+            rs_attrs,
+        })
+    }
+}
+
+/// Flatten the columns of an embedded struct into the table.
+///
+/// The name of the field is the prefix of the names of the columns, like `address_street`.
+fn embed_columns(column: stage1::Column, embeds: &[Embed]) -> syn::Result<Vec<Column>> {
+    let stage1::Column {
+        rs_name,
+        rs_ty,
+        attr,
+    } = column;
+    let embed_rs_ty_name = crate::utils::syn::parse_ident_from_type(&rs_ty)?;
+    let embed = embeds
+        .iter()
+        .find(|embed| &embed.rs_name == embed_rs_ty_name)
+        .ok_or_else(|| syn::Error::new(rs_ty.span(), EMBED_DOES_NOT_EXIST))?;
+    let prefix = attr.name.unwrap_or_else(|| rs_name.unraw().to_string());
+
+    let columns = embed.columns.iter().map(|embed_column| {
+        let mut embed_column = embed_column.clone();
+        let column_rs_name = embed_column.rs_name.clone();
+        let name = embed_column
+            .attr
+            .name
+            .take()
+            .unwrap_or_else(|| column_rs_name.unraw().to_string());
+        embed_column.attr.name = Some(fmt2::fmt! { { str } => {prefix} "_" {name} });
+        embed_column.rs_name =
+            quote::format_ident!("{}_{}", rs_name.unraw(), column_rs_name.unraw());
+
+        let mut column = Column::try_from(embed_column)?;
+        if !matches!(column.ty, TyMolecule::Element(TyElement::Value(_))) {
+            return Err(syn::Error::new(
+                column_rs_name.span(),
+                EMBED_COLUMN_MUST_BE_VALUE,
+            ));
+        }
+        column.embed = Some(ColumnEmbed {
+            rs_name: rs_name.clone(),
+            rs_ty_name: embed.rs_name.clone(),
+            column_rs_name,
+            poly: None,
+        });
+        Ok(column)
+    });
+    columns.try_collect_all()
+}
+
+/// A polymorphic foreign key, which refers to a record in one of multiple tables.
+pub struct Poly {
+    /// the name of the field in the rust struct, for example `owner`
+    pub rs_name: Ident,
+    /// the name of the enum, for example `CommentOwner`
+    pub rs_ty_name: Ident,
+    /// the type of the IDs of the foreign tables
+    pub id_rs_ty: Box<Type>,
+    /// the names of the foreign table structs, for example `User`
+    pub foreign_table_rs_names: Vec<Ident>,
+    /// the name of the column for the table of the foreign record, for example `owner_type`
+    pub table_name: String,
+    /// the name of the column for the ID of the foreign record, for example `owner_id`
+    pub id_name: String,
+}
+
+/// Flatten a polymorphic foreign key into a column for the table and a column for the ID.
+///
+/// The name of the field is the prefix of the names of the columns,
+/// like `owner_type` and `owner_id`.
+fn poly_columns(
+    column: stage1::Column,
+    poly: &darling::util::PathList,
+    ids: &[(Ident, Box<Type>)],
+) -> syn::Result<(Poly, Vec<Column>)> {
+    let stage1::Column {
+        rs_name,
+        rs_ty,
+        attr,
+    } = column;
+    if multiplicity::is_optional(&rs_ty).1 {
+        return Err(syn::Error::new(rs_ty.span(), COLUMN_MUST_NOT_BE_OPTIONAL));
+    }
+    let rs_ty_name = crate::utils::syn::parse_ident_from_type(&rs_ty)?.clone();
+
+    let foreign_table_rs_names = poly.iter().map(|path| path.require_ident().cloned());
+    let foreign_table_rs_names: Result<Vec<Ident>, syn::Error> =
+        foreign_table_rs_names.try_collect_all();
+    let foreign_table_rs_names = foreign_table_rs_names?;
+
+    let mut id_rs_ty: Option<&Type> = None;
+    for foreign_table_rs_name in &foreign_table_rs_names {
+        let (_, foreign_id_rs_ty) = ids
+            .iter()
+            .find(|(table_rs_name, _)| table_rs_name == foreign_table_rs_name)
+            .ok_or_else(|| syn::Error::new(foreign_table_rs_name.span(), TABLE_MUST_HAVE_ID))?;
+        match id_rs_ty {
+            None => id_rs_ty = Some(foreign_id_rs_ty),
+            Some(id_rs_ty) if id_rs_ty == &**foreign_id_rs_ty => {}
+            Some(_) => {
+                return Err(syn::Error::new(
+                    foreign_table_rs_name.span(),
+                    POLY_IDS_MUST_BE_SAME_TYPE,
+                ));
+            }
+        }
+    }
+    let Some(id_rs_ty) = id_rs_ty else {
+        return Err(syn::Error::new(rs_name.span(), POLY_MUST_HAVE_TABLES));
+    };
+
+    let prefix = attr.name.unwrap_or_else(|| rs_name.unraw().to_string());
+    let poly_column = |name: &str, ty, rs_ty: Type, poly| {
+        let column = stage1::Column {
+            rs_name: quote::format_ident!("{}_{}", rs_name.unraw(), name),
+            rs_ty: Box::new(rs_ty),
+            attr: stage1::ColumnAttr {
+                name: Some(fmt2::fmt! { { str } => {prefix} "_" {name} }),
+                ty,
+                is_mut: attr.is_mut,
+                ..Default::default()
+            },
+        };
+        let mut column = Column::try_from(column)?;
+        // the table is always one of the foreign tables, so it isn't validated
+        column.request.validate.max_len = None;
+        column.embed = Some(ColumnEmbed {
+            rs_name: rs_name.clone(),
+            rs_ty_name: rs_ty_name.clone(),
+            column_rs_name: quote::format_ident!("{}", name),
+            poly: Some(poly),
+        });
+        Ok::<Column, syn::Error>(column)
+    };
+    let table_column = poly_column(
+        "type",
+        Some(stage1::ColumnAttrTy::Varchar(255)),
+        syn::parse_quote!(String),
+        ColumnPoly::Table,
+    )?;
+    let id_column = poly_column("id", None, id_rs_ty.clone(), ColumnPoly::Id)?;
+
+    let poly = Poly {
+        rs_name,
+        rs_ty_name,
+        id_rs_ty: Box::new(id_rs_ty.clone()),
+        foreign_table_rs_names,
+        table_name: table_column.name.clone(),
+        id_name: id_column.name.clone(),
+    };
+    Ok((poly, vec![table_column, id_column]))
+}
+
+/// A struct with columns that is embedded in tables, instead of being a table itself.
+pub struct Embed {
+    /// the name for the struct, for example `Address`
+    pub rs_name: Ident,
+    /// the columns, which are flattened into the tables
+    pub columns: Vec<stage1::Column>,
+    /// attributes
+    pub rs_attrs: Vec<Attribute>,
+}
+impl TryFrom<stage1::Table> for Embed {
+    type Error = syn::Error;
+    fn try_from(table: stage1::Table) -> Result<Self, Self::Error> {
+        let stage1::Table {
+            rs_name,
+            columns,
+            attr:
+                stage1::TableAttr {
+                    model,
+                    controller,
+                    name,
+                    aggregate_rs_name,
+                    filter_rs_name,
+                    is_embed: _,
+                    attrs: rs_attrs,
+                },
+            rs_vis: _,
+        } = table;
+
+        if model.is_some()
+            || controller.is_some()
+            || name.is_some()
+            || aggregate_rs_name.is_some()
+            || filter_rs_name.is_some()
+        {
+            return Err(syn::Error::new(rs_name.span(), EMBED_MUST_NOT_BE_TABLE));
+        }
+
+        Ok(Self {
+            rs_name,
+            columns,
             rs_attrs,
         })
     }
@@ -482,6 +838,8 @@ pub enum Columns<T0, T1, C> {
     ManyModel {
         a: T1,
         b: T1,
+        /// the pivot columns
+        columns: Vec<T0>,
     },
 }
 impl<T, C> Columns<T, T, C> {
@@ -489,7 +847,7 @@ impl<T, C> Columns<T, T, C> {
         let (a, b, c) = match self {
             Self::CollectionOnly { columns } => (None, None, &**columns),
             Self::Model { id, columns, .. } => (Some(id), None, &**columns),
-            Self::ManyModel { a, b } => (Some(a), Some(b), &[][..]),
+            Self::ManyModel { a, b, columns } => (Some(a), Some(b), &**columns),
         };
         a.into_iter().chain(b).chain(c)
     }
@@ -501,9 +859,9 @@ impl<T0, T1, C> Columns<T0, T1, C> {
             _ => None,
         }
     }
-    pub const fn many_model(&self) -> Option<(&T1, &T1)> {
+    pub fn many_model(&self) -> Option<(&T1, &T1, &[T0])> {
         match self {
-            Self::ManyModel { a, b } => Some((a, b)),
+            Self::ManyModel { a, b, columns } => Some((a, b, columns)),
             _ => None,
         }
     }
@@ -525,16 +883,205 @@ pub struct Table {
     pub rs_name: Ident,
     /// the columns in the database
     pub columns: Columns<Column, Column, TableAttrController>,
+    /// the polymorphic foreign keys, which are flattened into the columns
+    pub polys: Vec<Poly>,
     /// the name of the aggregation
     pub aggregate_rs_name: Option<Ident>,
+    /// the name of the filter, which combines the public aggregations
+    pub filter_rs_name: Option<Ident>,
     /// visibility
     pub rs_vis: Visibility,
     /// attributes
     pub rs_attrs: Vec<Attribute>,
 }
-impl TryFrom<stage1::Table> for Table {
-    type Error = syn::Error;
-    fn try_from(table: stage1::Table) -> Result<Self, Self::Error> {
+/// The ID is split from the other columns, and a table has at most one ID.
+fn split_id(columns: impl Iterator<Item = Column>) -> syn::Result<(Option<Column>, Vec<Column>)> {
+    let mut id = None;
+    let columns = columns
+        .map(|column| {
+            if matches!(column.ty, TyMolecule::Element(TyElement::Id(_))) {
+                if id.is_some() {
+                    return Err(syn::Error::new(
+                        column.rs_name.span(),
+                        TABLE_MUST_NOT_HAVE_MULTIPLE_IDS,
+                    ));
+                }
+                id = Some(column);
+                Ok(None)
+            } else {
+                Ok(Some(column))
+            }
+        })
+        .filter_map(Result::transpose);
+    let columns: Result<Vec<Column>, syn::Error> = columns.try_collect_all();
+    let columns = columns?;
+    Ok((id, columns))
+}
+
+/// A many model is the two foreign keys of the pivot table, and the values of the pivot.
+fn many_model_columns(
+    span: proc_macro2::Span,
+    columns: Vec<Column>,
+) -> syn::Result<Columns<Column, Column, TableAttrController>> {
+    let mut columns = columns.into_iter();
+    let f_err = || syn::Error::new(span, TABLE_MUST_HAVE_TWO_COLUMNS);
+    let a = columns.next().ok_or_else(f_err)?;
+    let b = columns.next().ok_or_else(f_err)?;
+    let columns: Vec<Column> = columns.collect();
+    if let Some(column) = columns
+        .iter()
+        .find(|column| matches!(column.ty, TyMolecule::Compound(_)))
+    {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            PIVOT_COLUMN_MUST_NOT_BE_FOREIGN_KEY,
+        ));
+    }
+    if let Some(column) = [&a, &b]
+        .into_iter()
+        .chain(&columns)
+        .find(|column| column.embed.is_some())
+    {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            COLUMN_MUST_NOT_BE_EMBEDDED,
+        ));
+    }
+    Ok(Columns::ManyModel { a, b, columns })
+}
+
+const fn is_string(column: &Column) -> bool {
+    matches!(
+        column.ty,
+        TyMolecule::Element(TyElement::Value(TyElementValue {
+            ty: AtomicTy::String(_),
+            ..
+        }))
+    )
+}
+
+/// The statistics of a column are computed in sql, so it must be a value of this table.
+fn check_stat(column: &Column) -> syn::Result<()> {
+    if column.embed.is_some() {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            COLUMN_MUST_NOT_BE_EMBEDDED,
+        ));
+    }
+    if !matches!(column.ty, TyMolecule::Element(TyElement::Value(_))) {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            STAT_COLUMN_MUST_BE_VALUE,
+        ));
+    }
+    // the statistics already have the count of the records
+    if column.rs_name == "count" {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            STAT_COLUMN_MUST_NOT_BE_COUNT,
+        ));
+    }
+    Ok(())
+}
+
+/// The columns of a full-text search must be strings in this table.
+fn check_search(column: &Column, columns: &[Column]) -> syn::Result<()> {
+    for aggregate in &column.aggregates {
+        if !aggregate.filter.is_search() {
+            if let Some(other_column) = aggregate.columns.first() {
+                return Err(syn::Error::new(
+                    other_column.span(),
+                    COLUMNS_MUST_BE_SEARCHED,
+                ));
+            }
+            continue;
+        }
+        if !is_string(column) {
+            return Err(syn::Error::new(
+                column.rs_name.span(),
+                COLUMN_MUST_BE_STRING,
+            ));
+        }
+        for other_column in &*aggregate.columns {
+            let other = columns
+                .iter()
+                .find(|other| other_column.is_ident(&other.rs_name));
+            let Some(other) = other else {
+                return Err(syn::Error::new(other_column.span(), COLUMN_DOES_NOT_EXIST));
+            };
+            if !is_string(other) {
+                return Err(syn::Error::new(other_column.span(), COLUMN_MUST_BE_STRING));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The pages must fit the maximum, and the cursor is the column and the ID of the last record.
+fn check_limit(column: &Column, has_id: bool, has_filter: bool) -> syn::Result<()> {
+    if column.aggregates.iter().any(|aggregate| {
+        matches!(
+            aggregate.limit,
+            ColumnAttrAggregateLimit::Page {
+                per_page,
+                max_per_page: Some(max_per_page),
+                ..
+            } if per_page > max_per_page
+        )
+    }) {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            PER_PAGE_MUST_NOT_BE_MORE_THAN_MAX,
+        ));
+    }
+    if !column
+        .aggregates
+        .iter()
+        .any(|aggregate| aggregate.limit.is_cursor())
+    {
+        return Ok(());
+    }
+    if !has_id {
+        return Err(syn::Error::new(column.rs_name.span(), TABLE_MUST_HAVE_ID));
+    }
+    if column.embed.is_some() {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            COLUMN_MUST_NOT_BE_EMBEDDED,
+        ));
+    }
+    if !matches!(column.ty, TyMolecule::Element(_)) {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            CURSOR_COLUMN_MUST_BE_VALUE,
+        ));
+    }
+    if column.ty.is_optional() {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            COLUMN_MUST_NOT_BE_OPTIONAL,
+        ));
+    }
+    // the filter can be sorted by any columns, which the cursor can't continue after
+    if has_filter
+        && column
+            .aggregates
+            .iter()
+            .any(|aggregate| aggregate.is_pub && aggregate.limit.is_cursor())
+    {
+        return Err(syn::Error::new(
+            column.rs_name.span(),
+            FILTER_MUST_NOT_HAVE_CURSOR,
+        ));
+    }
+    Ok(())
+}
+impl Table {
+    fn try_new(
+        table: stage1::Table,
+        embeds: &[Embed],
+        ids: &[(Ident, Box<Type>)],
+    ) -> syn::Result<Self> {
         let stage1::Table {
             rs_name,
             columns,
@@ -544,6 +1091,8 @@ impl TryFrom<stage1::Table> for Table {
                     controller,
                     name,
                     aggregate_rs_name,
+                    filter_rs_name,
+                    is_embed: _,
                     attrs: rs_attrs,
                 },
             rs_vis,
@@ -551,28 +1100,39 @@ impl TryFrom<stage1::Table> for Table {
 
         let name = name.unwrap_or_else(|| rs_name.unraw().to_string());
 
-        let mut id = None;
-        let columns = columns
-            .into_iter()
-            .map(|column| {
-                let column = Column::try_from(column)?;
-                if matches!(column.ty, TyMolecule::Element(TyElement::Id(_))) {
-                    if id.is_some() {
-                        return Err(syn::Error::new(
-                            column.rs_name.span(),
-                            TABLE_MUST_NOT_HAVE_MULTIPLE_IDS,
-                        ));
-                    }
-                    id = Some(column);
-                    Ok(None)
-                } else {
-                    Ok(Some(column))
-                }
-            })
-            .filter_map(Result::transpose);
-        let columns: Result<Vec<Column>, syn::Error> = columns.try_collect_all();
+        // embedded structs and polymorphic foreign keys are flattened into the table
+        let mut polys = vec![];
+        let columns = columns.into_iter().map(|column| {
+            let poly = match column.attr.ty {
+                Some(stage1::ColumnAttrTy::Compound(stage1::ColumnAttrTyCompound {
+                    poly: Some(ref poly),
+                    ..
+                })) => Some(poly.clone()),
+                _ => None,
+            };
+            if column.attr.is_embed {
+                embed_columns(column, embeds)
+            } else if let Some(poly) = poly {
+                let (poly, columns) = poly_columns(column, &poly, ids)?;
+                polys.push(poly);
+                Ok(columns)
+            } else {
+                Column::try_from(column).map(|column| vec![column])
+            }
+        });
+        let columns: Result<Vec<Vec<Column>>, syn::Error> = columns.try_collect_all();
         let columns = columns?;
 
+        let (id, columns) = split_id(columns.into_iter().flatten())?;
+
+        for column in &columns {
+            if column.is_stat {
+                check_stat(column)?;
+            }
+            check_search(column, &columns)?;
+            check_limit(column, id.is_some(), filter_rs_name.is_some())?;
+        }
+
         let model = model.map(|model| model.many);
         let columns = match model {
             Some(false) => {
@@ -602,32 +1162,16 @@ impl TryFrom<stage1::Table> for Table {
                 return Err(syn::Error::new(rs_name.span(), TABLE_MUST_NOT_HAVE_ID));
             }
 
-            Some(true) => {
-                let mut columns = columns.into_iter();
-                let span = rs_name.span();
-                let f_err = || syn::Error::new(span, TABLE_MUST_HAVE_TWO_COLUMNS);
-                let many_model = Columns::ManyModel {
-                    a: columns.next().ok_or_else(f_err)?,
-                    b: columns.next().ok_or_else(f_err)?,
-                };
-                columns.next().map_or_else(
-                    || Ok(()),
-                    |column| {
-                        Err(syn::Error::new(
-                            column.rs_name.span(),
-                            TABLE_MUST_HAVE_TWO_COLUMNS,
-                        ))
-                    },
-                )?;
-                many_model
-            }
+            Some(true) => many_model_columns(rs_name.span(), columns)?,
         };
 
         Ok(Self {
             name,
             rs_name,
             columns,
+            polys,
             aggregate_rs_name,
+            filter_rs_name,
             rs_vis,
             rs_attrs,
         })
@@ -641,12 +1185,22 @@ pub struct Db {
     pub rs_name: Ident,
     /// the tables in the database
     pub tables: Vec<Table>,
+    /// the structs that are embedded in the tables
+    pub embeds: Vec<Embed>,
+    /// if queries are checked at compile time
+    pub is_checked: bool,
+    /// how many times a foreign key cycle is joined
+    pub join_depth: usize,
     /// visibility
     pub rs_vis: Visibility,
 }
 impl Db {
     pub fn try_new(db: stage1::Db, attr: stage1::DbAttr) -> syn::Result<Self> {
-        let stage1::DbAttr { name } = attr;
+        let stage1::DbAttr {
+            name,
+            is_checked,
+            join_depth,
+        } = attr;
         let stage1::Db {
             rs_name,
             tables,
@@ -655,7 +1209,28 @@ impl Db {
 
         let name = name.unwrap_or_else(|| rs_name.unraw().to_string());
 
-        let tables = tables.into_iter().map(Table::try_from);
+        let (embeds, tables): (Vec<stage1::Table>, Vec<stage1::Table>) =
+            tables.into_iter().partition(|table| table.attr.is_embed);
+
+        let embeds = embeds.into_iter().map(Embed::try_from);
+        let embeds: Result<Vec<Embed>, syn::Error> = embeds.try_collect_all();
+        let embeds = embeds?;
+
+        // the types of the IDs of the tables, for polymorphic foreign keys
+        let ids: Vec<(Ident, Box<Type>)> = tables
+            .iter()
+            .filter_map(|table| {
+                let id = table
+                    .columns
+                    .iter()
+                    .find(|column| matches!(column.attr.ty, Some(stage1::ColumnAttrTy::Id)))?;
+                Some((table.rs_name.clone(), id.rs_ty.clone()))
+            })
+            .collect();
+
+        let tables = tables
+            .into_iter()
+            .map(|table| Table::try_new(table, &embeds, &ids));
         let tables: Result<Vec<Table>, syn::Error> = tables.try_collect_all();
         let tables = tables?;
 
@@ -663,6 +1238,9 @@ impl Db {
             name,
             rs_name,
             tables,
+            embeds,
+            is_checked,
+            join_depth,
             rs_vis,
         })
     }
@@ -674,3 +1252,25 @@ pub fn find_table<'a>(tables: &'a [Table], ident: &Ident) -> syn::Result<&'a Tab
         .find(|table| &table.rs_name == ident)
         .ok_or_else(|| syn::Error::new(ident.span(), TABLE_DOES_NOT_EXIST))
 }
+
+/// Find the foreign key column in the child table that refers to the parent table.
+pub fn find_children_column<'a>(
+    child_table: &'a Table,
+    by_rs_name: &Ident,
+    parent_table_rs_name: &Ident,
+) -> syn::Result<&'a Column> {
+    child_table
+        .columns
+        .iter()
+        .find(|column| {
+            &column.rs_name == by_rs_name
+                && matches!(
+                    column.ty,
+                    TyMolecule::Compound(TyCompound {
+                        ref rs_ty_name,
+                        multiplicity: TyCompoundMultiplicity::One { .. },
+                    }) if rs_ty_name == parent_table_rs_name
+                )
+        })
+        .ok_or_else(|| syn::Error::new(by_rs_name.span(), COLUMN_MUST_BE_FOREIGN_KEY))
+}
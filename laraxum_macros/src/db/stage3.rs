@@ -5,17 +5,21 @@ pub use stage2::{
     DefaultValue, TyElementAutoTime,
 };
 
-use crate::utils::{borrow::CowBoxDeref, collections::TryCollectAll};
+use crate::utils::{borrow::CowBoxDeref, collections::TryCollectAll, multiplicity};
 
 use std::borrow::Cow;
 
-use syn::{Attribute, Ident, Type, Visibility};
+use syn::{Attribute, Ident, Type, Visibility, ext::IdentExt};
 
 const TABLE_MUST_HAVE_ID: &str = "table must have an ID";
 const TABLE_ID_MUST_BE_INT: &str = "table ID must be int";
 const COLUMN_MUST_NOT_BE_COLLECTION: &str = "column must not be many-to-many relationship";
+const FOREIGN_KEY_CYCLE_MUST_BE_OPTIONAL: &str = "foreign key cycle must be optional or lazy";
 // const COLUMN_MUST_HAVE_STRUCT_NAME: &str = "column must have struct name";
 
+/// the name of the table that remembers which fixtures have been seeded
+const SEED_TABLE_NAME: &str = "laraxum_seeds";
+
 fn name_extern((parent, child): (&str, &str)) -> String {
     fmt2::fmt! { { str } => {parent} "__" {child} }
 }
@@ -28,8 +32,26 @@ fn name_intern((parent, child): (&str, &str)) -> String {
 fn name_intern_extern(parent_child: (&str, &str)) -> (String, String) {
     (name_intern(parent_child), name_extern(parent_child))
 }
+/// The name of the field of a column in the response, or `None` if it is skipped.
+///
+/// The columns of an embedded struct are fields of it, like `address.street`.
+fn response_name(column: &stage2::Column) -> Option<String> {
+    if column.response.skip {
+        return None;
+    }
+    let name = match (&column.embed, &column.response.name) {
+        (Some(embed), _) => {
+            let (embed_name, column_name) = (embed.rs_name.unraw(), embed.column_rs_name.unraw());
+            format!("{embed_name}.{column_name}")
+        }
+        (None, Some(name)) => name.clone(),
+        (None, None) => column.rs_name.unraw().to_string(),
+    };
+    Some(name)
+}
 
 pub use stage2::TyElement;
+pub use stage2::{ColumnEmbed, ColumnPoly, Embed};
 
 pub struct TyCompound<'a> {
     pub foreign_table_name: &'a str,
@@ -85,7 +107,12 @@ pub struct ResponseColumnGetterElement<'a> {
     pub name_extern: String,
     pub is_optional: bool,
     pub rs_name: &'a Ident,
-    // pub rs_ty: &'a Type,
+    /// the type of the column in the rust struct, without `Option`
+    pub rs_ty: &'a Type,
+    /// the embedded struct of the column
+    pub embed: Option<&'a ColumnEmbed>,
+    /// the name of the field in the response, see `response_name`
+    pub name: Option<String>,
 }
 
 pub struct ResponseColumnGetterCompound<'a> {
@@ -98,29 +125,42 @@ pub struct ResponseColumnGetterCompound<'a> {
     // pub rs_ty: &'a Type,
     pub foreign_table_rs_name: &'a Ident,
     pub columns: Vec<ResponseColumnGetter<'a>>,
+    /// the name of the field in the response, see `response_name`
+    pub name: Option<String>,
+}
+
+/// A foreign key cycle that has been joined `join_depth` times,
+/// so it isn't joined again and is always `None`.
+pub struct ResponseColumnGetterTruncated<'a> {
+    pub name_intern: String,
+    pub rs_name: &'a Ident,
 }
 
 pub enum ResponseColumnGetterMolecule<'a> {
     Element(ResponseColumnGetterElement<'a>),
     Compound(ResponseColumnGetterCompound<'a>),
+    Truncated(ResponseColumnGetterTruncated<'a>),
 }
 impl ResponseColumnGetterMolecule<'_> {
     pub fn name_intern(&self) -> &str {
         match self {
             Self::Element(element) => &element.name_intern,
             Self::Compound(compound) => &compound.name_intern,
+            Self::Truncated(truncated) => &truncated.name_intern,
         }
     }
     pub const fn rs_name(&self) -> &Ident {
         match self {
             Self::Element(element) => element.rs_name,
             Self::Compound(compound) => compound.rs_name,
+            Self::Truncated(truncated) => truncated.rs_name,
         }
     }
     pub const fn is_optional(&self) -> bool {
         match self {
             Self::Element(element) => element.is_optional,
             Self::Compound(compound) => compound.is_optional,
+            Self::Truncated(_) => true,
         }
     }
 }
@@ -129,7 +169,12 @@ pub struct ResponseColumnGetterCollection<'a> {
     pub rs_name: &'a Ident,
     pub aggregate_rs_name: &'a Ident,
     pub table_id_name_extern: String,
+    pub table_id_rs_ty: &'a Type,
     pub many_foreign_table_rs_name: &'a Ident,
+    /// if the foreign table has a foreign key to this table instead of being a `ManyModel`
+    pub is_children: bool,
+    /// the name of the field in the response, see `response_name`
+    pub name: Option<String>,
 }
 
 pub enum ResponseColumnGetter<'a> {
@@ -164,6 +209,7 @@ pub struct ResponseColumnField<'a> {
     pub rs_ty: &'a Type,
     pub attr: &'a stage2::ColumnAttrResponse,
     pub rs_attrs: &'a [Attribute],
+    pub embed: Option<&'a ColumnEmbed>,
 }
 
 pub struct ResponseColumnMolecule<'a> {
@@ -184,6 +230,10 @@ pub struct RequestColumnSetterMolecule<'a> {
     pub is_optional: bool,
     pub is_mut: bool,
     pub validate: &'a Validate,
+    /// the foreign table, if the create request can have a request to create the foreign record
+    pub nested: Option<&'a Ident>,
+    /// the embedded struct of the column
+    pub embed: Option<&'a ColumnEmbed>,
 }
 
 pub struct RequestColumnSetterCollection<'a> {
@@ -197,6 +247,7 @@ pub struct RequestColumnField<'a> {
     pub rs_ty: CowBoxDeref<'a, Type>,
     pub attr: &'a stage2::ColumnAttrRequest,
     pub rs_attrs: &'a [Attribute],
+    pub embed: Option<&'a ColumnEmbed>,
 }
 
 pub struct RequestColumnMoleculeMutable<'a> {
@@ -251,6 +302,14 @@ pub use stage2::ColumnAttrAggregate;
 pub use stage2::ColumnAttrAggregateFilter;
 pub use stage2::ColumnAttrAggregateLimit;
 
+/// A foreign key column that can be expanded.
+pub struct ColumnExpand<'a> {
+    pub foreign_table_rs_name: &'a Ident,
+    pub is_optional: bool,
+    /// if the response only has the id unless it's expanded
+    pub is_lazy: bool,
+}
+
 pub struct ColumnMolecule<'a> {
     pub create: CreateColumn<'a>,
     pub response: ResponseColumnMolecule<'a>,
@@ -258,6 +317,9 @@ pub struct ColumnMolecule<'a> {
     pub aggregates: &'a [ColumnAttrAggregate],
     pub borrow: Option<Option<&'a Type>>,
     pub struct_name: Option<&'a Ident>,
+    pub expand: Option<ColumnExpand<'a>>,
+    /// if there are statistics of the column
+    pub is_stat: bool,
 }
 impl ColumnMolecule<'_> {
     pub const fn name(&self) -> &str {
@@ -270,7 +332,7 @@ impl ColumnMolecule<'_> {
 
 pub struct ColumnCollection<'a> {
     pub response: ResponseColumnCollection<'a>,
-    pub request: RequestColumnCollection<'a>,
+    pub request: Option<RequestColumnCollection<'a>>,
     pub struct_name: Option<&'a Ident>,
 }
 
@@ -325,7 +387,9 @@ impl<'a> ColumnRef<'a> {
                 .request
                 .as_ref()
                 .and_then(|request| request.field()),
-            Self::Collection(collection) => Some(&collection.request.field),
+            Self::Collection(collection) => {
+                collection.request.as_ref().map(|request| &request.field)
+            }
         }
     }
     pub const fn request_molecule(self) -> Option<&'a RequestColumnMolecule<'a>> {
@@ -337,7 +401,7 @@ impl<'a> ColumnRef<'a> {
     pub const fn request_collection(self) -> Option<&'a RequestColumnCollection<'a>> {
         match self {
             Self::Molecule(_) => None,
-            Self::Collection(collection) => Some(&collection.request),
+            Self::Collection(collection) => collection.request.as_ref(),
         }
     }
     pub fn request_setter_molecule(self) -> Option<&'a RequestColumnSetterMolecule<'a>> {
@@ -349,10 +413,12 @@ impl<'a> ColumnRef<'a> {
             Self::Collection(_) => None,
         }
     }
-    pub const fn request_setter_collection(self) -> Option<&'a RequestColumnSetterCollection<'a>> {
+    pub fn request_setter_collection(self) -> Option<&'a RequestColumnSetterCollection<'a>> {
         match self {
             Self::Molecule(_) => None,
-            Self::Collection(collection) => Some(&collection.request.setter),
+            Self::Collection(collection) => {
+                collection.request.as_ref().map(|request| &request.setter)
+            }
         }
     }
     pub const fn struct_name(self) -> Option<&'a Ident> {
@@ -366,8 +432,8 @@ impl<'a> ColumnRef<'a> {
             Self::Molecule(molecule) => molecule
                 .request
                 .as_ref()
-                .is_some_and(|request| request.is_mut()),
-            Self::Collection(_) => true,
+                .is_some_and(RequestColumnMolecule::is_mut),
+            Self::Collection(collection) => collection.request.is_some(),
         }
     }
 }
@@ -390,7 +456,7 @@ impl<T, C> Columns<T, T, C> {
     {
         match self {
             Self::CollectionOnly { columns } => {
-                let columns = columns.iter().map(|c| f(c));
+                let columns = columns.iter().map(&mut f);
                 let columns: Result<Vec<Column<'a>>, syn::Error> = columns.try_collect_all();
                 let columns = columns?;
                 Ok(Columns::CollectionOnly { columns })
@@ -411,12 +477,15 @@ impl<T, C> Columns<T, T, C> {
                     controller: controller.as_ref(),
                 })
             }
-            Self::ManyModel { a, b } => {
+            Self::ManyModel { a, b, columns } => {
                 let a = f(a)?;
                 let a = ColumnMolecule::try_from(a)?;
                 let b = f(b)?;
                 let b = ColumnMolecule::try_from(b)?;
-                Ok(Columns::ManyModel { a, b })
+                let columns = columns.iter().map(f);
+                let columns: Result<Vec<Column<'a>>, syn::Error> = columns.try_collect_all();
+                let columns = columns?;
+                Ok(Columns::ManyModel { a, b, columns })
             }
         }
     }
@@ -430,17 +499,49 @@ impl<'a, C> Columns<Column<'a>, ColumnMolecule<'a>, C> {
                 None,
                 columns.iter().map(ColumnRef::from),
             ),
-            Self::ManyModel { a, b } => (
+            Self::ManyModel { a, b, columns } => (
                 Some(ColumnRef::Molecule(a)),
                 Some(ColumnRef::Molecule(b)),
-                [].iter().map(ColumnRef::from),
+                columns.iter().map(ColumnRef::from),
             ),
         };
         a.into_iter().chain(b).chain(c)
     }
 }
 
+/// A foreign key column that the foreign table uses for a `children` column.
+pub struct TableChildren<'a> {
+    /// the foreign key column
+    pub name_intern: String,
+    pub aggregate_rs_name: &'a Ident,
+    pub foreign_table_id_rs_ty: &'a Type,
+}
+
+/// A foreign key column to a parent record in the same table.
+pub struct TableTree<'a> {
+    /// the foreign key column
+    pub name: &'a str,
+    pub name_intern: String,
+    pub aggregate_rs_name: &'a Ident,
+}
+
+/// A polymorphic foreign key, which refers to a record in one of multiple tables.
+pub struct TablePoly<'a> {
+    /// the name of the enum, for example `CommentOwner`
+    pub rs_ty_name: &'a Ident,
+    /// the type of the IDs of the foreign tables
+    pub id_rs_ty: &'a Type,
+    /// the foreign tables, with the name of the table struct and the name of the sql table
+    pub foreign_tables: Vec<(&'a Ident, &'a str)>,
+    /// the column for the table of the foreign record
+    pub table_name_intern: String,
+    /// the column for the ID of the foreign record
+    pub id_name_intern: String,
+}
+
 pub struct Table<'a> {
+    /// the name of the table without the database
+    pub name: &'a str,
     pub name_intern: String,
     pub name_extern: String,
     pub rs_name: &'a Ident,
@@ -448,10 +549,20 @@ pub struct Table<'a> {
     pub update_request_rs_name: Cow<'a, Ident>,
     pub patch_request_rs_name: Cow<'a, Ident>,
     pub request_error_rs_name: Cow<'a, Ident>,
+    /// the pivot columns of a `ManyModel` in a response
+    pub pivot_rs_name: Cow<'a, Ident>,
+    /// the pivot columns of a `ManyModel` in a request
+    pub pivot_request_rs_name: Cow<'a, Ident>,
     pub aggregate_rs_name: Option<&'a Ident>,
+    pub filter_rs_name: Option<&'a Ident>,
     pub db_rs_name: &'a Ident,
+    pub seed_table_name_intern: String,
+    pub is_checked: bool,
     pub rs_attrs: &'a [syn::Attribute],
     pub columns: Columns<Column<'a>, ColumnMolecule<'a>, &'a stage2::TableAttrController>,
+    pub children: Vec<TableChildren<'a>>,
+    pub trees: Vec<TableTree<'a>>,
+    pub polys: Vec<TablePoly<'a>>,
 }
 
 impl<'a> Table<'a> {
@@ -477,14 +588,21 @@ impl<'a> Table<'a> {
             )
         }
 
+        /// `parents` are the tables that have been joined to get here, including this table.
         fn traverse<'table: 'iter, 'iter>(
             table_name_extern: &str,
             table: &'table stage2::Table,
             db: &'iter stage2::Db,
+            parents: Vec<&'iter Ident>,
         ) -> impl Iterator<Item = syn::Result<ResponseColumnGetter<'iter>>> {
             table.columns.iter().map(move |column| {
                 let stage2::Column {
-                    name, rs_name, ty, ..
+                    name,
+                    rs_name,
+                    ty,
+                    rs_ty,
+                    embed,
+                    ..
                 } = column;
                 let name = &*name;
                 let (column_name_intern, column_name_extern) =
@@ -496,15 +614,39 @@ impl<'a> Table<'a> {
                             name_intern: column_name_intern,
                             name_extern: column_name_extern,
                             rs_name,
+                            rs_ty: multiplicity::is_optional(rs_ty).0,
                             is_optional: ty_element.is_optional(),
+                            embed: embed.as_ref(),
+                            name: response_name(column),
                         }),
                     ),
+                    stage2::TyMolecule::Compound(stage2::TyCompound {
+                        rs_ty_name: _,
+                        multiplicity:
+                            stage2::TyCompoundMultiplicity::One {
+                                is_optional,
+                                is_lazy: true,
+                                ..
+                            },
+                    }) => ResponseColumnGetter::Molecule(ResponseColumnGetterMolecule::Element(
+                        ResponseColumnGetterElement {
+                            name_intern: column_name_intern,
+                            name_extern: column_name_extern,
+                            rs_name,
+                            rs_ty: multiplicity::is_optional(rs_ty).0,
+                            is_optional,
+                            embed: None,
+                            name: response_name(column),
+                        },
+                    )),
                     stage2::TyMolecule::Compound(stage2::TyCompound {
                         rs_ty_name: ref foreign_table_rs_name,
                         multiplicity:
                             stage2::TyCompoundMultiplicity::One {
                                 is_optional,
-                                is_unique: _,
+                                is_lazy: false,
+                                join_depth,
+                                ..
                             },
                     }) => {
                         let foreign_table = stage2::find_table(&db.tables, foreign_table_rs_name)?;
@@ -512,6 +654,30 @@ impl<'a> Table<'a> {
                             syn::Error::new(foreign_table.rs_name.span(), TABLE_MUST_HAVE_ID)
                         })?;
 
+                        // foreign key cycles are only joined `join_depth` times
+                        let depth = parents
+                            .iter()
+                            .filter(|&&parent| parent == &foreign_table.rs_name)
+                            .count();
+                        if depth > join_depth.unwrap_or(db.join_depth) {
+                            if !is_optional {
+                                return Err(syn::Error::new(
+                                    rs_name.span(),
+                                    FOREIGN_KEY_CYCLE_MUST_BE_OPTIONAL,
+                                ));
+                            }
+                            return Ok(ResponseColumnGetter::Molecule(
+                                ResponseColumnGetterMolecule::Truncated(
+                                    ResponseColumnGetterTruncated {
+                                        name_intern: column_name_intern,
+                                        rs_name,
+                                    },
+                                ),
+                            ));
+                        }
+                        let mut parents = parents.clone();
+                        parents.push(&foreign_table.rs_name);
+
                         let foreign_table_name_intern =
                             name_intern((&db.name, &foreign_table.name));
                         let foreign_table_name_extern =
@@ -519,7 +685,8 @@ impl<'a> Table<'a> {
                         let foreign_table_id_name_intern =
                             name_intern((&*foreign_table_name_extern, &foreign_table_id.name));
 
-                        let columns = traverse(&foreign_table_name_extern, foreign_table, db);
+                        let columns =
+                            traverse(&foreign_table_name_extern, foreign_table, db, parents);
                         let columns: Result<Vec<ResponseColumnGetter>, syn::Error> =
                             columns.try_collect_all();
                         let columns = columns?;
@@ -533,6 +700,7 @@ impl<'a> Table<'a> {
                             foreign_table_rs_name: &foreign_table.rs_name,
                             is_optional,
                             columns,
+                            name: response_name(column),
                         };
                         ResponseColumnGetter::Molecule(ResponseColumnGetterMolecule::Compound(
                             compound,
@@ -556,7 +724,39 @@ impl<'a> Table<'a> {
                             rs_name,
                             aggregate_rs_name,
                             table_id_name_extern,
+                            table_id_rs_ty: &table_id.rs_ty,
                             many_foreign_table_rs_name,
+                            is_children: false,
+                            name: response_name(column),
+                        })
+                    }
+                    stage2::TyMolecule::Compound(stage2::TyCompound {
+                        rs_ty_name: _,
+                        multiplicity:
+                            stage2::TyCompoundMultiplicity::Children(stage2::ColumnAttrTyChildren {
+                                model_rs_name: ref child_table_rs_name,
+                                ref by_rs_name,
+                            }),
+                    }) => {
+                        let table_rs_name = &table.rs_name;
+                        let child_table = stage2::find_table(&db.tables, child_table_rs_name)?;
+                        let child_column =
+                            stage2::find_children_column(child_table, by_rs_name, table_rs_name)?;
+                        let table_id = table.columns.model().ok_or_else(|| {
+                            syn::Error::new(table_rs_name.span(), TABLE_MUST_HAVE_ID)
+                        })?;
+                        let table_id_name_extern = name_extern((table_name_extern, &table_id.name));
+                        ResponseColumnGetter::Collection(ResponseColumnGetterCollection {
+                            rs_name,
+                            aggregate_rs_name: child_column
+                                .struct_name
+                                .as_ref()
+                                .unwrap_or(table_rs_name),
+                            table_id_name_extern,
+                            table_id_rs_ty: &table_id.rs_ty,
+                            many_foreign_table_rs_name: child_table_rs_name,
+                            is_children: true,
+                            name: response_name(column),
                         })
                     }
                 };
@@ -578,6 +778,8 @@ impl<'a> Table<'a> {
                     ref borrow,
                     ref aggregates,
                     ref struct_name,
+                    ref embed,
+                    is_stat,
                     ref rs_attrs,
                 } = column;
                 let (column_name_intern, column_name_extern) =
@@ -600,6 +802,9 @@ impl<'a> Table<'a> {
                                         name_extern: column_name_extern,
                                         is_optional: ty_element.is_optional(),
                                         rs_name,
+                                        rs_ty: multiplicity::is_optional(rs_ty).0,
+                                        embed: embed.as_ref(),
+                                        name: response_name(column),
                                     },
                                 ),
                                 field: ResponseColumnField {
@@ -607,6 +812,7 @@ impl<'a> Table<'a> {
                                     rs_ty,
                                     attr: response,
                                     rs_attrs,
+                                    embed: embed.as_ref(),
                                 },
                             },
                             request: match ty_element {
@@ -618,6 +824,7 @@ impl<'a> Table<'a> {
                                                 rs_ty: CowBoxDeref::Borrowed(rs_ty),
                                                 attr: request,
                                                 rs_attrs,
+                                                embed: embed.as_ref(),
                                             },
                                             setter: RequestColumnSetterMolecule {
                                                 rs_name,
@@ -625,6 +832,8 @@ impl<'a> Table<'a> {
                                                 is_optional: ty_element.is_optional(),
                                                 is_mut,
                                                 validate: &request.validate,
+                                                nested: None,
+                                                embed: embed.as_ref(),
                                             },
                                             // is_mut,
                                         },
@@ -644,6 +853,8 @@ impl<'a> Table<'a> {
                             aggregates,
                             borrow,
                             struct_name,
+                            expand: None,
+                            is_stat,
                         })
                     }
                     stage2::TyMolecule::Compound(stage2::TyCompound {
@@ -652,6 +863,9 @@ impl<'a> Table<'a> {
                             stage2::TyCompoundMultiplicity::One {
                                 is_optional,
                                 is_unique,
+                                is_lazy,
+                                is_nested,
+                                ..
                             },
                     }) => {
                         let foreign_table = stage2::find_table(&db.tables, foreign_table_rs_name)?;
@@ -670,20 +884,38 @@ impl<'a> Table<'a> {
                         let foreign_table_id_name_intern =
                             name_intern((&*foreign_table_name_extern, &foreign_table_id.name));
 
-                        let columns = traverse(&foreign_table_name_extern, foreign_table, db);
-                        let columns: Result<Vec<ResponseColumnGetter>, syn::Error> =
-                            columns.try_collect_all();
-                        let columns = columns?;
+                        let getter = if is_lazy {
+                            ResponseColumnGetterMolecule::Element(ResponseColumnGetterElement {
+                                name_intern: column_name_intern,
+                                name_extern: column_name_extern,
+                                is_optional,
+                                rs_name,
+                                rs_ty: multiplicity::is_optional(rs_ty).0,
+                                embed: None,
+                                name: response_name(column),
+                            })
+                        } else {
+                            let columns = traverse(
+                                &foreign_table_name_extern,
+                                foreign_table,
+                                db,
+                                vec![&table.rs_name, &foreign_table.rs_name],
+                            );
+                            let columns: Result<Vec<ResponseColumnGetter>, syn::Error> =
+                                columns.try_collect_all();
+                            let columns = columns?;
 
-                        let compound = ResponseColumnGetterCompound {
-                            name_intern: column_name_intern,
-                            foreign_table_id_name_intern,
-                            foreign_table_name_intern,
-                            foreign_table_name_extern,
-                            rs_name,
-                            foreign_table_rs_name: &foreign_table.rs_name,
-                            is_optional,
-                            columns,
+                            ResponseColumnGetterMolecule::Compound(ResponseColumnGetterCompound {
+                                name_intern: column_name_intern,
+                                foreign_table_id_name_intern,
+                                foreign_table_name_intern,
+                                foreign_table_name_extern,
+                                rs_name,
+                                foreign_table_rs_name: &foreign_table.rs_name,
+                                is_optional,
+                                columns,
+                                name: response_name(column),
+                            })
                         };
 
                         Column::Molecule(ColumnMolecule {
@@ -703,8 +935,9 @@ impl<'a> Table<'a> {
                                     rs_ty,
                                     attr: response,
                                     rs_attrs,
+                                    embed: None,
                                 },
-                                getter: ResponseColumnGetterMolecule::Compound(compound),
+                                getter,
                             },
                             request: Some(RequestColumnMolecule::Mutable(
                                 RequestColumnMoleculeMutable {
@@ -716,6 +949,7 @@ impl<'a> Table<'a> {
                                         ),
                                         attr: request,
                                         rs_attrs,
+                                        embed: None,
                                     },
                                     setter: RequestColumnSetterMolecule {
                                         rs_name,
@@ -723,6 +957,8 @@ impl<'a> Table<'a> {
                                         is_optional,
                                         is_mut,
                                         validate: &request.validate,
+                                        nested: is_nested.then_some(&foreign_table.rs_name),
+                                        embed: None,
                                     },
                                     // is_mut,
                                 },
@@ -730,6 +966,12 @@ impl<'a> Table<'a> {
                             aggregates,
                             borrow,
                             struct_name,
+                            expand: Some(ColumnExpand {
+                                foreign_table_rs_name: &foreign_table.rs_name,
+                                is_optional,
+                                is_lazy,
+                            }),
+                            is_stat: false,
                         })
                     }
                     stage2::TyMolecule::Compound(stage2::TyCompound {
@@ -754,15 +996,19 @@ impl<'a> Table<'a> {
                                     rs_ty,
                                     attr: response,
                                     rs_attrs,
+                                    embed: None,
                                 },
                                 getter: ResponseColumnGetterCollection {
                                     rs_name,
                                     aggregate_rs_name,
                                     table_id_name_extern,
+                                    table_id_rs_ty: &table_id.rs_ty,
                                     many_foreign_table_rs_name,
+                                    is_children: false,
+                                    name: response_name(column),
                                 },
                             },
-                            request: RequestColumnCollection {
+                            request: Some(RequestColumnCollection {
                                 field: RequestColumnField {
                                     rs_name,
                                     rs_ty: CowBoxDeref::Owned(Box::new(rs_ty_collection_request(
@@ -771,13 +1017,57 @@ impl<'a> Table<'a> {
                                     ))),
                                     attr: request,
                                     rs_attrs,
+                                    embed: None,
                                 },
                                 setter: RequestColumnSetterCollection {
                                     rs_name,
                                     aggregate_rs_name,
                                     many_foreign_table_rs_name,
                                 },
+                            }),
+                            struct_name,
+                        })
+                    }
+                    stage2::TyMolecule::Compound(stage2::TyCompound {
+                        rs_ty_name: _,
+                        multiplicity:
+                            stage2::TyCompoundMultiplicity::Children(stage2::ColumnAttrTyChildren {
+                                model_rs_name: ref child_table_rs_name,
+                                ref by_rs_name,
+                            }),
+                    }) => {
+                        let table_rs_name = &table.rs_name;
+                        let child_table = stage2::find_table(&db.tables, child_table_rs_name)?;
+                        let child_column =
+                            stage2::find_children_column(child_table, by_rs_name, table_rs_name)?;
+                        let table_id = table.columns.model().ok_or_else(|| {
+                            syn::Error::new(table_rs_name.span(), TABLE_MUST_HAVE_ID)
+                        })?;
+                        let table_id_name_extern =
+                            name_extern((&table_name_extern, &table_id.name));
+                        Column::Collection(ColumnCollection {
+                            response: ResponseColumnCollection {
+                                field: ResponseColumnField {
+                                    rs_name,
+                                    rs_ty,
+                                    attr: response,
+                                    rs_attrs,
+                                    embed: None,
+                                },
+                                getter: ResponseColumnGetterCollection {
+                                    rs_name,
+                                    aggregate_rs_name: child_column
+                                        .struct_name
+                                        .as_ref()
+                                        .unwrap_or(table_rs_name),
+                                    table_id_name_extern,
+                                    table_id_rs_ty: &table_id.rs_ty,
+                                    many_foreign_table_rs_name: child_table_rs_name,
+                                    is_children: true,
+                                    name: response_name(column),
+                                },
                             },
+                            request: None,
                             struct_name,
                         })
                     }
@@ -787,11 +1077,91 @@ impl<'a> Table<'a> {
         );
         let columns = columns?;
 
+        let children = table
+            .columns
+            .iter()
+            .filter_map(|column| match column.ty {
+                stage2::TyMolecule::Compound(stage2::TyCompound {
+                    rs_ty_name: ref foreign_table_rs_name,
+                    multiplicity: stage2::TyCompoundMultiplicity::One { .. },
+                }) => Some((column, foreign_table_rs_name)),
+                _ => None,
+            })
+            .filter_map(|(column, foreign_table_rs_name)| {
+                let foreign_table = stage2::find_table(&db.tables, foreign_table_rs_name).ok()?;
+                let is_children = foreign_table.columns.iter().any(|foreign_column| {
+                    matches!(
+                        foreign_column.ty,
+                        stage2::TyMolecule::Compound(stage2::TyCompound {
+                            multiplicity:
+                                stage2::TyCompoundMultiplicity::Children(
+                                    stage2::ColumnAttrTyChildren {
+                                        ref model_rs_name,
+                                        ref by_rs_name,
+                                    },
+                                ),
+                            ..
+                        }) if model_rs_name == &table.rs_name && by_rs_name == &column.rs_name
+                    )
+                });
+                let foreign_table_id = foreign_table.columns.model()?;
+                is_children.then(|| TableChildren {
+                    name_intern: name_intern((&table_name_extern, &column.name)),
+                    aggregate_rs_name: column.struct_name.as_ref().unwrap_or(foreign_table_rs_name),
+                    foreign_table_id_rs_ty: &foreign_table_id.rs_ty,
+                })
+            })
+            .collect();
+
+        let trees = table
+            .columns
+            .iter()
+            .filter(|column| {
+                matches!(
+                    column.ty,
+                    stage2::TyMolecule::Compound(stage2::TyCompound {
+                        ref rs_ty_name,
+                        multiplicity: stage2::TyCompoundMultiplicity::One { .. },
+                    }) if rs_ty_name == &table.rs_name
+                )
+            })
+            .map(|column| TableTree {
+                name: &column.name,
+                name_intern: name_intern((&table_name_extern, &column.name)),
+                aggregate_rs_name: column.struct_name.as_ref().unwrap_or(&table.rs_name),
+            })
+            .collect();
+
+        let polys = table.polys.iter().map(|poly| {
+            let foreign_tables = poly
+                .foreign_table_rs_names
+                .iter()
+                .map(|foreign_table_rs_name| {
+                    let foreign_table = stage2::find_table(&db.tables, foreign_table_rs_name)?;
+                    Ok((foreign_table_rs_name, &*foreign_table.name))
+                });
+            let foreign_tables: Result<Vec<(&Ident, &str)>, syn::Error> =
+                foreign_tables.try_collect_all();
+            let foreign_tables = foreign_tables?;
+            Ok(TablePoly {
+                rs_ty_name: &poly.rs_ty_name,
+                id_rs_ty: &poly.id_rs_ty,
+                foreign_tables,
+                table_name_intern: name_intern((&table_name_extern, &poly.table_name)),
+                id_name_intern: name_intern((&table_name_extern, &poly.id_name)),
+            })
+        });
+        let polys: Result<Vec<TablePoly>, syn::Error> = polys.try_collect_all();
+        let polys = polys?;
+
         let create_request_rs_name = quote::format_ident!("{}CreateRequest", table.rs_name);
         let update_request_rs_name = quote::format_ident!("{}UpdateRequest", table.rs_name);
         let patch_request_rs_name = quote::format_ident!("{}PatchRequest", table.rs_name);
         let request_error_rs_name = quote::format_ident!("{}RequestError", table.rs_name);
+        let pivot_rs_name = quote::format_ident!("{}Pivot", table.rs_name);
+        let pivot_request_rs_name = quote::format_ident!("{}PivotRequest", table.rs_name);
         Ok(Self {
+            name: &table.name,
             name_intern: table_name_intern,
             name_extern: table_name_extern,
             rs_name: &table.rs_name,
@@ -799,10 +1169,18 @@ impl<'a> Table<'a> {
             update_request_rs_name: Cow::Owned(update_request_rs_name),
             patch_request_rs_name: Cow::Owned(patch_request_rs_name),
             request_error_rs_name: Cow::Owned(request_error_rs_name),
+            pivot_rs_name: Cow::Owned(pivot_rs_name),
+            pivot_request_rs_name: Cow::Owned(pivot_request_rs_name),
             aggregate_rs_name: table.aggregate_rs_name.as_ref(),
+            filter_rs_name: table.filter_rs_name.as_ref(),
             db_rs_name: &db.rs_name,
+            seed_table_name_intern: name_intern((&db.name, SEED_TABLE_NAME)),
+            is_checked: db.is_checked,
             rs_attrs: &*table.rs_attrs,
             columns,
+            children,
+            trees,
+            polys,
         })
     }
 }
@@ -814,6 +1192,12 @@ pub struct Db<'a> {
     pub rs_name: &'a Ident,
     /// the tables in the database
     pub tables: Vec<Table<'a>>,
+    /// the structs that are embedded in the tables
+    pub embeds: &'a [Embed],
+    /// the name of the table that remembers which fixtures have been seeded
+    pub seed_table_name_intern: String,
+    /// if queries are checked at compile time
+    pub is_checked: bool,
     /// visibility
     pub rs_vis: &'a Visibility,
 }
@@ -832,6 +1216,9 @@ impl<'a> TryFrom<&'a stage2::Db> for Db<'a> {
             name: &db.name,
             rs_name: &db.rs_name,
             tables,
+            embeds: &db.embeds,
+            seed_table_name_intern: name_intern((&db.name, SEED_TABLE_NAME)),
+            is_checked: db.is_checked,
             rs_vis: &db.rs_vis,
         })
     }
@@ -5,7 +5,7 @@ use crate::utils::syn::from_str_to_rs_ident;
 use std::{borrow::Cow, vec};
 
 use quote::{ToTokens, quote};
-use syn::{Ident, Type};
+use syn::{Ident, Type, ext::IdentExt};
 
 impl stage3::AtomicTyInt {
     const fn ty(&self) -> &'static str {
@@ -231,16 +231,25 @@ impl fmt2::write_to::WriteTo for stage3::CreateColumn<'_> {
     }
 }
 
+/// the name of the column with the one in a batch of many values
+const BATCH_ONE_NAME: &str = "laraxum_one";
+/// the name of the recursive table with the identifiers of a tree
+const TREE_NAME: &str = "laraxum_tree";
+/// the name of a record that is joined to a tree
+const TREE_NODE_NAME: &str = "laraxum_node";
+
 struct ResponseColumnGetterElement<'a> {
     element: &'a stage3::ResponseColumnGetterElement<'a>,
     parent_optional: bool,
+    is_checked: bool,
 }
 impl fmt2::write_to::WriteTo for ResponseColumnGetterElement<'_> {
     fn write_to<W>(&self, w: &mut W) -> Result<(), W::Error>
     where
         W: fmt2::write::Write + ?Sized,
     {
-        if self.parent_optional || self.element.is_optional {
+        // the `!` override is only understood by the checked query macros
+        if self.parent_optional || self.element.is_optional || !self.is_checked {
             fmt2::fmt! { (? w) =>
                 {self.element.name_intern}
                 " AS "
@@ -267,21 +276,242 @@ impl fmt2::write_to::WriteTo for ResponseColumnGetterElement<'_> {
     }
 }
 
+/// The path of a record in the response, to select its fields at runtime,
+/// see `::laraxum::model::fields`.
+#[derive(Clone, Copy)]
+struct Projection<'a> {
+    /// the path of the record, which is empty for the table,
+    /// or `None` if the record is never selected, like when its field is skipped
+    path: Option<&'a str>,
+    /// the columns that are selected whenever the record is, like its ID
+    required: &'a [&'a str],
+}
+/// When a column or a relation of a record is selected.
+enum Selected {
+    Always,
+    By(String),
+    Never,
+}
+impl Projection<'_> {
+    /// The path of a field of the record, or `None` if the field is never selected.
+    fn field(self, name: Option<&str>) -> Option<String> {
+        let (path, name) = (self.path?, name?);
+        if path.is_empty() {
+            Some(name.to_owned())
+        } else {
+            Some(format!("{path}.{name}"))
+        }
+    }
+    fn element(self, element: &stage3::ResponseColumnGetterElement) -> Selected {
+        if self.required.contains(&&*element.name_intern) {
+            match self.path {
+                Some("") => Selected::Always,
+                Some(path) => Selected::By(path.to_owned()),
+                None => Selected::Never,
+            }
+        } else {
+            self.field(element.name.as_deref())
+                .map_or(Selected::Never, Selected::By)
+        }
+    }
+}
+/// If a field is selected at runtime.
+fn is_selected(path: &str) -> proc_macro2::TokenStream {
+    quote! { ::laraxum::model::fields::is_selected(fields, #path) }
+}
+/// A placeholder for a field that isn't selected.
+fn unselected() -> proc_macro2::TokenStream {
+    quote! { ::laraxum::model::types::Unselected::unselected() }
+}
+
+#[derive(Clone, Copy)]
 enum Sort {
     Ascending,
     Descending,
 }
 
-fn create_table<'columns>(
-    table_name_intern: &str,
-    columns: impl IntoIterator<Item = stage3::ColumnRef<'columns>>,
-) -> String {
-    let create_columns = columns.into_iter().filter_map(|column| column.create());
+fn create_table(table: &stage3::Table) -> String {
+    let create_columns = table.columns.iter().filter_map(stage3::ColumnRef::create);
 
+    // a full-text search needs an index with exactly the columns that are searched
+    let searches = searches(table);
+    #[cfg(feature = "mysql")]
+    {
+        fmt2::fmt! { { str } =>
+            "CREATE TABLE IF NOT EXISTS " {table.name_intern} " ("
+                @..join(create_columns => "," => |column| {column})
+                @..(searches => |search_columns|
+                    ",FULLTEXT ("
+                        @..join(search_columns => "," => |column| {column.name()})
+                    ")"
+                )
+            ");"
+        }
+    }
+    #[cfg(feature = "postgres")]
+    {
+        fmt2::fmt! { { str } =>
+            "CREATE TABLE IF NOT EXISTS " {table.name_intern} " ("
+                @..join(create_columns => "," => |column| {column})
+            ");"
+            @..(searches => |search_columns|
+                "CREATE INDEX IF NOT EXISTS " {search_name(table.name, &search_columns)}
+                " ON " {table.name_intern}
+                " USING GIN (to_tsvector('simple',"
+                    {search_document(&search_columns, stage3::ColumnMolecule::name)}
+                "));"
+            )
+        }
+    }
+    // the search is an FTS5 table with the columns of the table, kept up to date with triggers
+    #[cfg(feature = "sqlite")]
+    {
+        fmt2::fmt! { { str } =>
+            "CREATE TABLE IF NOT EXISTS " {table.name_intern} " ("
+                @..join(create_columns => "," => |column| {column})
+            ");"
+            @..(searches => |search_columns|
+                {create_search_table(table, &search_columns)}
+            )
+        }
+    }
+    #[cfg(not(any(feature = "mysql", feature = "sqlite", feature = "postgres")))]
+    unimplemented!();
+}
+/// The columns of each full-text search of a table, without duplicates.
+fn searches<'columns>(
+    table: &'columns stage3::Table<'columns>,
+) -> Vec<Vec<&'columns stage3::ColumnMolecule<'columns>>> {
+    let mut searches: Vec<Vec<&stage3::ColumnMolecule>> = vec![];
+    let molecules = table.columns.iter().filter_map(|column| match column {
+        stage3::ColumnRef::Molecule(molecule) => Some(molecule),
+        stage3::ColumnRef::Collection(_) => None,
+    });
+    for column in molecules {
+        let aggregates = column
+            .aggregates
+            .iter()
+            .filter(|aggregate| aggregate.filter.is_search());
+        for aggregate in aggregates {
+            let search_columns = search_columns(table, column, aggregate);
+            let is_duplicate = searches.iter().any(|search| {
+                search
+                    .iter()
+                    .map(|column| column.name())
+                    .eq(search_columns.iter().map(|column| column.name()))
+            });
+            if !is_duplicate {
+                searches.push(search_columns);
+            }
+        }
+    }
+    searches
+}
+/// The columns of a full-text search, which are the column and the other columns.
+fn search_columns<'columns>(
+    table: &'columns stage3::Table<'columns>,
+    column: &'columns stage3::ColumnMolecule<'columns>,
+    aggregate: &stage3::ColumnAttrAggregate,
+) -> Vec<&'columns stage3::ColumnMolecule<'columns>> {
+    let other_columns = aggregate.columns.iter().filter_map(|other_column| {
+        table.columns.iter().find_map(|column| match column {
+            stage3::ColumnRef::Molecule(molecule)
+                if other_column.is_ident(molecule.response.field.rs_name) =>
+            {
+                Some(molecule)
+            }
+            _ => None,
+        })
+    });
+    core::iter::once(column).chain(other_columns).collect()
+}
+/// The name of the index or FTS5 table of a full-text search.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+fn search_name(table_name: &str, search_columns: &[&stage3::ColumnMolecule]) -> String {
+    fmt2::fmt! { { str } =>
+        {table_name} @..(search_columns => |column| "_" {column.name()}) "_search"
+    }
+}
+/// The text of the columns of a full-text search, as one string.
+#[cfg(feature = "postgres")]
+fn search_document<'columns>(
+    search_columns: &[&'columns stage3::ColumnMolecule<'columns>],
+    name: impl Fn(&'columns stage3::ColumnMolecule<'columns>) -> &'columns str,
+) -> String {
+    fmt2::fmt! { { str } =>
+        @..join(search_columns => "||' '||" => |column| "COALESCE(" {name(column)} ",'')")
+    }
+}
+/// Create the FTS5 table of a full-text search, with triggers to copy the records into it.
+#[cfg(feature = "sqlite")]
+fn create_search_table(
+    table: &stage3::Table,
+    search_columns: &[&stage3::ColumnMolecule],
+) -> String {
+    let search_table_name = search_name(table.name, search_columns);
+    let search_table_name_intern = search_name(&table.name_intern, search_columns);
+    let insert = fmt2::fmt! { { str } =>
+        "INSERT INTO " {search_table_name_intern} " (rowid"
+            @..(search_columns => |column| "," {column.name()})
+        ") VALUES (new.rowid"
+            @..(search_columns => |column| ",new." {column.name()})
+        ");"
+    };
+    // the old values are deleted with the `delete` command of FTS5
+    let delete = fmt2::fmt! { { str } =>
+        "INSERT INTO " {search_table_name_intern} " (" {search_table_name} ",rowid"
+            @..(search_columns => |column| "," {column.name()})
+        ") VALUES ('delete',old.rowid"
+            @..(search_columns => |column| ",old." {column.name()})
+        ");"
+    };
     fmt2::fmt! { { str } =>
-        "CREATE TABLE IF NOT EXISTS " {table_name_intern} " ("
-            @..join(create_columns => "," => |column| {column})
+        "CREATE VIRTUAL TABLE IF NOT EXISTS " {search_table_name_intern} " USING fts5("
+            @..join(search_columns => "," => |column| {column.name()})
+            ",content='" {table.name} "'"
         ");"
+        "CREATE TRIGGER IF NOT EXISTS " {search_table_name_intern} "_insert"
+        " AFTER INSERT ON " {table.name} " BEGIN " {insert} " END;"
+        "CREATE TRIGGER IF NOT EXISTS " {search_table_name_intern} "_delete"
+        " AFTER DELETE ON " {table.name} " BEGIN " {delete} " END;"
+        "CREATE TRIGGER IF NOT EXISTS " {search_table_name_intern} "_update"
+        " AFTER UPDATE ON " {table.name} " BEGIN " {delete} {insert} " END;"
+    }
+}
+/// The column of the filter of an aggregator, which is multiple columns for a full-text search.
+///
+/// For a full-text search, this is what is searched:
+/// - `mysql`: the columns, separated by commas
+/// - `postgres`: the text of the columns, see `search_document`
+/// - `sqlite`: the `rowid` of the record and the FTS5 table, separated by a comma
+fn filter_name_intern<'columns>(
+    table: &'columns stage3::Table<'columns>,
+    column: &'columns stage3::ColumnMolecule<'columns>,
+    aggregate: &stage3::ColumnAttrAggregate,
+) -> String {
+    if aggregate.filter.is_search() {
+        let search_columns = search_columns(table, column, aggregate);
+        #[cfg(feature = "mysql")]
+        {
+            fmt2::fmt! { { str } =>
+                @..join(search_columns => "," => |column| {column.name_intern()})
+            }
+        }
+        #[cfg(feature = "postgres")]
+        {
+            search_document(&search_columns, stage3::ColumnMolecule::name_intern)
+        }
+        #[cfg(feature = "sqlite")]
+        {
+            fmt2::fmt! { { str } =>
+                {table.name_extern} ".rowid,"
+                {search_name(&table.name_intern, &search_columns)}
+            }
+        }
+        #[cfg(not(any(feature = "mysql", feature = "sqlite", feature = "postgres")))]
+        unimplemented!();
+    } else {
+        String::from(column.name_intern())
     }
 }
 fn delete_table(table_name_intern: &str) -> String {
@@ -289,7 +519,153 @@ fn delete_table(table_name_intern: &str) -> String {
         "DROP TABLE " {table_name_intern} ";"
     }
 }
-fn get(
+/// The condition of a filter, without the `WHERE`.
+fn filter_condition(
+    aggregate_filter: stage3::ColumnAttrAggregateFilter,
+    filter_column_name_intern: &str,
+) -> Option<String> {
+    match aggregate_filter {
+        stage3::ColumnAttrAggregateFilter::None => None,
+        stage3::ColumnAttrAggregateFilter::Eq => {
+            Some(fmt2::fmt! { { str } => {filter_column_name_intern} "=?" })
+        }
+        stage3::ColumnAttrAggregateFilter::Like => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " LIKE CONCAT('%', ?, '%')"
+        }),
+        stage3::ColumnAttrAggregateFilter::Gt => {
+            Some(fmt2::fmt! { { str } => {filter_column_name_intern} ">?" })
+        }
+        stage3::ColumnAttrAggregateFilter::Lt => {
+            Some(fmt2::fmt! { { str } => {filter_column_name_intern} "<?" })
+        }
+        stage3::ColumnAttrAggregateFilter::Gte => {
+            Some(fmt2::fmt! { { str } => {filter_column_name_intern} ">=?" })
+        }
+        stage3::ColumnAttrAggregateFilter::Lte => {
+            Some(fmt2::fmt! { { str } => {filter_column_name_intern} "<=?" })
+        }
+        stage3::ColumnAttrAggregateFilter::Ne => {
+            Some(fmt2::fmt! { { str } => {filter_column_name_intern} "<>?" })
+        }
+        // the values are one string separated by commas, so the query is built at runtime
+        // with a placeholder for each value, see `InFilter`
+        stage3::ColumnAttrAggregateFilter::In => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " IN (?)"
+        }),
+        stage3::ColumnAttrAggregateFilter::Between => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " BETWEEN ? AND ?"
+        }),
+        stage3::ColumnAttrAggregateFilter::IsNull => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " IS NULL"
+        }),
+        stage3::ColumnAttrAggregateFilter::NotNull => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " IS NOT NULL"
+        }),
+        stage3::ColumnAttrAggregateFilter::StartsWith => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " LIKE CONCAT(?, '%')"
+        }),
+        stage3::ColumnAttrAggregateFilter::EndsWith => Some(fmt2::fmt! { { str } =>
+            {filter_column_name_intern} " LIKE CONCAT('%', ?)"
+        }),
+        stage3::ColumnAttrAggregateFilter::Ilike => Some(fmt2::fmt! { { str } =>
+            "LOWER(" {filter_column_name_intern} ") LIKE CONCAT('%', LOWER(?), '%')"
+        }),
+        // what is searched depends on the database, see `filter_name_intern`
+        // and the records are ordered by relevance if there is no `ORDER BY`, see `relevance`
+        stage3::ColumnAttrAggregateFilter::Search => {
+            #[cfg(feature = "mysql")]
+            {
+                Some(fmt2::fmt! { { str } =>
+                    "MATCH (" {filter_column_name_intern} ") AGAINST (?)"
+                })
+            }
+            // the value is words and not the syntax of `to_tsquery`
+            #[cfg(feature = "postgres")]
+            {
+                Some(fmt2::fmt! { { str } =>
+                    "to_tsvector('simple'," {filter_column_name_intern} ")"
+                    " @@ plainto_tsquery('simple',?)"
+                })
+            }
+            #[cfg(feature = "sqlite")]
+            {
+                let (rowid, search_table_name) = filter_column_name_intern
+                    .split_once(',')
+                    .unwrap_or((filter_column_name_intern, filter_column_name_intern));
+                Some(fmt2::fmt! { { str } =>
+                    {rowid} " IN (SELECT rowid FROM " {search_table_name}
+                    " WHERE " {search_table_name} " MATCH ?)"
+                })
+            }
+            #[cfg(not(any(feature = "mysql", feature = "sqlite", feature = "postgres")))]
+            unimplemented!();
+        }
+    }
+}
+/// The relevance of a record to a full-text search, which is higher if it is more relevant.
+fn relevance(filter_column_name_intern: &str) -> String {
+    #[cfg(feature = "mysql")]
+    {
+        fmt2::fmt! { { str } =>
+            "MATCH (" {filter_column_name_intern} ") AGAINST (?)"
+        }
+    }
+    #[cfg(feature = "postgres")]
+    {
+        fmt2::fmt! { { str } =>
+            "ts_rank(to_tsvector('simple'," {filter_column_name_intern} "),"
+            "plainto_tsquery('simple',?))"
+        }
+    }
+    // the rank of FTS5 is lower if it is more relevant
+    #[cfg(feature = "sqlite")]
+    {
+        let (rowid, search_table_name) = filter_column_name_intern
+            .split_once(',')
+            .unwrap_or((filter_column_name_intern, filter_column_name_intern));
+        fmt2::fmt! { { str } =>
+            "(SELECT -rank FROM " {search_table_name}
+            " WHERE " {search_table_name} " MATCH ? AND rowid=" {rowid} ")"
+        }
+    }
+    #[cfg(not(any(feature = "mysql", feature = "sqlite", feature = "postgres")))]
+    unimplemented!();
+}
+/// The type of the value of a filter in the query parameters.
+fn aggregate_filter_rs_ty(
+    aggregate_filter: stage3::ColumnAttrAggregateFilter,
+    column_rs_ty: &syn::Type,
+) -> syn::Type {
+    match aggregate_filter {
+        stage3::ColumnAttrAggregateFilter::In | stage3::ColumnAttrAggregateFilter::Search => {
+            syn::parse_quote!(::std::string::String)
+        }
+        stage3::ColumnAttrAggregateFilter::Between => {
+            syn::parse_quote!(::laraxum::model::filter::Between<#column_rs_ty>)
+        }
+        stage3::ColumnAttrAggregateFilter::IsNull | stage3::ColumnAttrAggregateFilter::NotNull => {
+            syn::parse_quote!(::laraxum::model::filter::Flag)
+        }
+        _ => column_rs_ty.clone(),
+    }
+}
+/// The arguments of a filter for the placeholders in its condition.
+fn filter_args(
+    aggregate_filter: stage3::ColumnAttrAggregateFilter,
+    value: &proc_macro2::TokenStream,
+) -> Vec<proc_macro2::TokenStream> {
+    match aggregate_filter {
+        stage3::ColumnAttrAggregateFilter::None
+        | stage3::ColumnAttrAggregateFilter::IsNull
+        | stage3::ColumnAttrAggregateFilter::NotNull => vec![],
+        stage3::ColumnAttrAggregateFilter::Between => {
+            vec![quote! { #value.start }, quote! { #value.end }]
+        }
+        _ => vec![value.clone()],
+    }
+}
+/// Get the records with the condition of the filter, without ordering or limiting them.
+fn get_where(
     table_name_intern: &str,
     table_name_extern: &str,
     (response_getter_column_elements, response_getter_column_compounds): (
@@ -297,9 +673,6 @@ fn get(
         &[&stage3::ResponseColumnGetterCompound],
     ),
     aggregate_filter: Option<(stage3::ColumnAttrAggregateFilter, &str)>,
-    aggregate_sort: Option<(Sort, &str)>,
-    aggregate_limit: Option<stage3::ColumnAttrAggregateLimit>,
-    is_one: bool,
 ) -> String {
     let mut get = fmt2::fmt! { { str } =>
         "SELECT "
@@ -314,31 +687,32 @@ fn get(
             {compound.name_intern} "=" {compound.foreign_table_id_name_intern}
         )
     };
-    if let Some((aggregate_filter, filter_column_name_intern)) = aggregate_filter {
-        match aggregate_filter {
-            stage3::ColumnAttrAggregateFilter::None => {}
-            stage3::ColumnAttrAggregateFilter::Eq => {
-                fmt2::fmt! { (get) => " WHERE " {filter_column_name_intern} "=?" };
-            }
-            stage3::ColumnAttrAggregateFilter::Like => {
-                fmt2::fmt! { (get) =>
-                    " WHERE " {filter_column_name_intern} " LIKE CONCAT('%', ?, '%')"
-                };
-            }
-            stage3::ColumnAttrAggregateFilter::Gt => {
-                fmt2::fmt! { (get) => " WHERE " {filter_column_name_intern} ">?" };
-            }
-            stage3::ColumnAttrAggregateFilter::Lt => {
-                fmt2::fmt! { (get) => " WHERE " {filter_column_name_intern} "<?" };
-            }
-            stage3::ColumnAttrAggregateFilter::Gte => {
-                fmt2::fmt! { (get) => " WHERE " {filter_column_name_intern} ">=?" };
-            }
-            stage3::ColumnAttrAggregateFilter::Lte => {
-                fmt2::fmt! { (get) => " WHERE " {filter_column_name_intern} "<=?" };
-            }
-        }
+    let filter = aggregate_filter.and_then(|(aggregate_filter, filter_column_name_intern)| {
+        filter_condition(aggregate_filter, filter_column_name_intern)
+    });
+    if let Some(filter) = filter {
+        fmt2::fmt! { (get) => " WHERE " {filter} };
     }
+    get
+}
+fn get(
+    table_name_intern: &str,
+    table_name_extern: &str,
+    response_getters: (
+        &[ResponseColumnGetterElement],
+        &[&stage3::ResponseColumnGetterCompound],
+    ),
+    aggregate_filter: Option<(stage3::ColumnAttrAggregateFilter, &str)>,
+    aggregate_sort: Option<(Sort, &str)>,
+    aggregate_limit: Option<stage3::ColumnAttrAggregateLimit>,
+    is_one: bool,
+) -> String {
+    let mut get = get_where(
+        table_name_intern,
+        table_name_extern,
+        response_getters,
+        aggregate_filter,
+    );
     if let Some((aggregate_sort, sort_column_name_intern)) = aggregate_sort {
         match aggregate_sort {
             Sort::Ascending => {
@@ -348,23 +722,54 @@ fn get(
                 fmt2::fmt! { (get) => " ORDER BY " {sort_column_name_intern} " DESC" };
             }
         }
+    } else if let Some((stage3::ColumnAttrAggregateFilter::Search, filter_column_name_intern)) =
+        aggregate_filter
+    {
+        // a search without sorting is ordered by relevance,
+        // which binds the value of the filter again
+        fmt2::fmt! { (get) => " ORDER BY " {relevance(filter_column_name_intern)} " DESC" };
     }
     if is_one {
         fmt2::fmt! { (get) => " LIMIT 1" };
     } else if let Some(aggregate_limit) = aggregate_limit {
         match aggregate_limit {
             stage3::ColumnAttrAggregateLimit::None => {}
-            stage3::ColumnAttrAggregateLimit::Limit => {
+            stage3::ColumnAttrAggregateLimit::Limit | stage3::ColumnAttrAggregateLimit::Cursor => {
                 fmt2::fmt! { (get) => " LIMIT ?" };
             }
-            stage3::ColumnAttrAggregateLimit::Page { per_page } => {
+            stage3::ColumnAttrAggregateLimit::Page {
+                per_page,
+                max_per_page: None,
+                ..
+            } => {
                 // the `OFFSET` is set in the parameter as `OFFSET * per_page`
                 fmt2::fmt! { (get) => " LIMIT " {per_page} " OFFSET ? " };
             }
+            stage3::ColumnAttrAggregateLimit::Page {
+                max_per_page: Some(_),
+                ..
+            } => {
+                // the `LIMIT` is chosen by the client
+                fmt2::fmt! { (get) => " LIMIT ? OFFSET ? " };
+            }
         }
     }
     get
 }
+/// Count the records of an aggregation, for the total of page based pagination.
+fn count(
+    table_name_intern: &str,
+    table_name_extern: &str,
+    (aggregate_filter, filter_column_name_intern): (stage3::ColumnAttrAggregateFilter, &str),
+) -> String {
+    let mut count = fmt2::fmt! { { str } =>
+        "SELECT COUNT(*) FROM " {table_name_intern} " AS " {table_name_extern}
+    };
+    if let Some(filter) = filter_condition(aggregate_filter, filter_column_name_intern) {
+        fmt2::fmt! { (count) => " WHERE " {filter} };
+    }
+    count
+}
 fn get_all(
     table_name_intern: &str,
     table_name_extern: &str,
@@ -421,6 +826,101 @@ fn get_many(
         false,
     )
 }
+/// Get the records of a polymorphic foreign key, by the table and the ID of the foreign record.
+fn get_many_poly(
+    table_name_intern: &str,
+    table_name_extern: &str,
+    response_getters: (
+        &[ResponseColumnGetterElement],
+        &[&stage3::ResponseColumnGetterCompound],
+    ),
+    (poly_table_name_intern, poly_id_name_intern): (&str, &str),
+) -> String {
+    let mut get = get_all(table_name_intern, table_name_extern, response_getters);
+    fmt2::fmt! { (get) =>
+        " WHERE " {poly_table_name_intern} "=? AND " {poly_id_name_intern} "=?"
+    };
+    get
+}
+/// Get the many values for multiple ones.
+///
+/// The ones are bound at runtime and the list must be closed with `)`.
+fn get_many_batch(
+    table_name_intern: &str,
+    table_name_extern: &str,
+    (response_getter_column_elements, response_getter_column_compounds): (
+        &[ResponseColumnGetterElement],
+        &[&stage3::ResponseColumnGetterCompound],
+    ),
+    one_name_intern: &str,
+) -> String {
+    fmt2::fmt! { { str } =>
+        "SELECT " {one_name_intern} " AS " {BATCH_ONE_NAME} ","
+        @..join(response_getter_column_elements => "," => |element|
+            {element}
+        )
+        " FROM " {table_name_intern} " AS " {table_name_extern}
+        @..(response_getter_column_compounds => |compound|
+            " LEFT JOIN "
+            {compound.foreign_table_name_intern} " AS " {compound.foreign_table_name_extern}
+            " ON "
+            {compound.name_intern} "=" {compound.foreign_table_id_name_intern}
+        )
+        " WHERE " {one_name_intern} " IN ("
+    }
+}
+/// Get the `(parent, record)` rows of a tree with a recursive query.
+///
+/// The tree starts with the records where `start_name` is the identifier,
+/// then adds the `next_name` of the records where `match_name` is in the tree.
+fn get_tree(
+    table_name_intern: &str,
+    table_name_extern: &str,
+    (response_getter_column_elements, response_getter_column_compounds): (
+        &[ResponseColumnGetterElement],
+        &[&stage3::ResponseColumnGetterCompound],
+    ),
+    (table_id_name, table_id_name_intern): (&str, &str),
+    parent_name_intern: &str,
+    (start_name, next_name, match_name): (&str, &str, &str),
+) -> String {
+    let tree = tree(
+        table_name_intern,
+        table_id_name,
+        (start_name, next_name, match_name),
+    );
+    fmt2::fmt! { { str } =>
+        {tree}
+        " SELECT " {parent_name_intern} " AS " {BATCH_ONE_NAME} ","
+        @..join(response_getter_column_elements => "," => |element|
+            {element}
+        )
+        " FROM " {table_name_intern} " AS " {table_name_extern}
+        @..(response_getter_column_compounds => |compound|
+            " LEFT JOIN "
+            {compound.foreign_table_name_intern} " AS " {compound.foreign_table_name_extern}
+            " ON "
+            {compound.name_intern} "=" {compound.foreign_table_id_name_intern}
+        )
+        " WHERE " {table_id_name_intern} " IN (SELECT id FROM " {TREE_NAME} ")"
+    }
+}
+/// The recursive `WITH` of the IDs in a tree, see `get_tree`.
+fn tree(
+    table_name_intern: &str,
+    table_id_name: &str,
+    (start_name, next_name, match_name): (&str, &str, &str),
+) -> String {
+    fmt2::fmt! { { str } =>
+        "WITH RECURSIVE " {TREE_NAME} " (id) AS ("
+        "SELECT " {table_id_name} " FROM " {table_name_intern} " WHERE " {start_name} "=?"
+        " UNION "
+        "SELECT " {TREE_NODE_NAME} "." {next_name}
+        " FROM " {table_name_intern} " AS " {TREE_NODE_NAME}
+        " JOIN " {TREE_NAME} " ON " {TREE_NODE_NAME} "." {match_name} "=" {TREE_NAME} ".id"
+        ")"
+    }
+}
 fn get_sort_asc_desc(
     table_name_intern: &str,
     table_name_extern: &str,
@@ -454,6 +954,65 @@ fn get_sort_asc_desc(
         ),
     )
 }
+/// Get the records after a cursor, which is the column and the ID of the last record.
+///
+/// Without a cursor, it gets the first records.
+fn get_cursor(
+    table_name_intern: &str,
+    table_name_extern: &str,
+    response_getters: (
+        &[ResponseColumnGetterElement],
+        &[&stage3::ResponseColumnGetterCompound],
+    ),
+    aggregate_filter: (stage3::ColumnAttrAggregateFilter, &str),
+    (aggregate_sort, sort_column_name_intern): (Sort, &str),
+    id_name_intern: &str,
+    is_after: bool,
+) -> String {
+    let mut get = get_where(
+        table_name_intern,
+        table_name_extern,
+        response_getters,
+        Some(aggregate_filter),
+    );
+    if is_after {
+        let keyword = match filter_condition(aggregate_filter.0, aggregate_filter.1) {
+            Some(_) => " AND ",
+            None => " WHERE ",
+        };
+        get.push_str(keyword);
+        get.push_str(&cursor_after(
+            aggregate_sort,
+            sort_column_name_intern,
+            id_name_intern,
+        ));
+    }
+    let direction = match aggregate_sort {
+        Sort::Ascending => " ASC",
+        Sort::Descending => " DESC",
+    };
+    fmt2::fmt! { (get) =>
+        " ORDER BY "
+        {sort_column_name_intern} {direction} ","
+        {id_name_intern} {direction}
+        " LIMIT ?"
+    };
+    get
+}
+/// The condition of the records after the cursor, in the order of the column and the ID.
+fn cursor_after(
+    aggregate_sort: Sort,
+    sort_column_name_intern: &str,
+    id_name_intern: &str,
+) -> String {
+    let operator = match aggregate_sort {
+        Sort::Ascending => ">",
+        Sort::Descending => "<",
+    };
+    fmt2::fmt! { { str } =>
+        "(" {sort_column_name_intern} "," {id_name_intern} ")" {operator} "(?,?)"
+    }
+}
 pub const fn request_setter_column<'a>(
     column: &'a stage3::RequestColumnMolecule<'a>,
 ) -> (&'a str, &'a str) {
@@ -478,6 +1037,53 @@ where
         ")"
     }
 }
+/// Create many rows with one query.
+///
+/// The rows are pushed at runtime with `QueryBuilder::push_values`.
+fn create_many<'columns, I>(table_name_intern: &str, columns: I) -> String
+where
+    I: IntoIterator<Item = &'columns stage3::RequestColumnMolecule<'columns>>,
+{
+    let request_columns = columns.into_iter().map(request_setter_column);
+    fmt2::fmt! { { str } =>
+        "INSERT INTO " {table_name_intern} " ("
+            @..join(request_columns => "," => |column| {column.0})
+        ") "
+    }
+}
+/// Get the many values of a one.
+fn get_many_values(table_name_intern: &str, one_name: &str, many_name: &str) -> String {
+    fmt2::fmt! { { str } =>
+        "SELECT " {many_name} " FROM " {table_name_intern}
+        " WHERE " {one_name} "=?"
+    }
+}
+/// Delete many values of a one with one query.
+///
+/// The `(one, many)` rows are pushed at runtime with `QueryBuilder::push_tuples`.
+fn delete_many_values(table_name_intern: &str, one_name: &str, many_name: &str) -> String {
+    fmt2::fmt! { { str } =>
+        "DELETE FROM " {table_name_intern}
+        " WHERE (" {one_name} "," {many_name} ") IN "
+    }
+}
+/// Update the pivot columns of a many value of a one.
+fn update_pivot<'columns, I>(
+    table_name_intern: &str,
+    one_name: &str,
+    many_name: &str,
+    columns: I,
+) -> String
+where
+    I: IntoIterator<Item = &'columns stage3::RequestColumnMolecule<'columns>>,
+{
+    let request_columns = columns.into_iter().map(request_setter_column);
+    fmt2::fmt! { { str } =>
+        "UPDATE " {table_name_intern} " SET "
+        @..join(request_columns => "," => |column| {column.0} "=" {column.1})
+        " WHERE " {one_name} "=? AND " {many_name} "=?"
+    }
+}
 fn update_one<'columns, I>(table_name_intern: &str, id_name: &str, columns: I) -> String
 where
     I: IntoIterator<Item = &'columns stage3::RequestColumnMolecule<'columns>>,
@@ -502,10 +1108,39 @@ fn delete_one(table_name_intern: &str, id_name: &str) -> String {
         " WHERE " {id_name} "=?"
     }
 }
-
+fn create_seed_table(seed_table_name_intern: &str) -> String {
+    #[cfg(feature = "mysql")]
+    let id_ty = "BIGINT UNSIGNED";
+    #[cfg(feature = "sqlite")]
+    let id_ty = "INTEGER";
+    #[cfg(feature = "postgres")]
+    let id_ty = "BIGINT";
+    fmt2::fmt! { { str } =>
+        "CREATE TABLE IF NOT EXISTS " {seed_table_name_intern} " ("
+            "table_name VARCHAR(255) NOT NULL,"
+            "name VARCHAR(255) NOT NULL,"
+            "id " {id_ty} " NOT NULL,"
+            "PRIMARY KEY (table_name,name)"
+        ");"
+    }
+}
+fn get_seeded(seed_table_name_intern: &str) -> String {
+    fmt2::fmt! { { str } =>
+        "SELECT id FROM " {seed_table_name_intern}
+        " WHERE table_name=? AND name=?"
+    }
+}
+fn create_seeded(seed_table_name_intern: &str) -> String {
+    fmt2::fmt! { { str } =>
+        "INSERT INTO " {seed_table_name_intern}
+        " (table_name,name,id) VALUES (?,?,?)"
+    }
+}
+
 fn flatten_internal<'columns>(
     response_getter_columns: impl IntoIterator<Item = stage3::ResponseColumnGetterRef<'columns>>,
     parent_optional: bool,
+    is_checked: bool,
     response_getter_column_elements: &mut Vec<ResponseColumnGetterElement<'columns>>,
     response_getter_column_compounds: &mut Vec<
         &'columns stage3::ResponseColumnGetterCompound<'columns>,
@@ -519,6 +1154,7 @@ fn flatten_internal<'columns>(
                 response_getter_column_elements.push(ResponseColumnGetterElement {
                     element,
                     parent_optional,
+                    is_checked,
                 });
             }
             stage3::ResponseColumnGetterRef::Molecule(
@@ -533,16 +1169,21 @@ fn flatten_internal<'columns>(
                 flatten_internal(
                     compound_columns,
                     parent_optional,
+                    is_checked,
                     response_getter_column_elements,
                     response_getter_column_compounds,
                 );
             }
-            stage3::ResponseColumnGetterRef::Collection(_) => {}
+            stage3::ResponseColumnGetterRef::Molecule(
+                stage3::ResponseColumnGetterMolecule::Truncated(_),
+            )
+            | stage3::ResponseColumnGetterRef::Collection(_) => {}
         }
     }
 }
 fn flatten<'columns>(
     response_getter_columns: impl Iterator<Item = stage3::ResponseColumnGetterRef<'columns>>,
+    is_checked: bool,
 ) -> (
     Vec<ResponseColumnGetterElement<'columns>>,
     Vec<&'columns stage3::ResponseColumnGetterCompound<'columns>>,
@@ -552,6 +1193,7 @@ fn flatten<'columns>(
     flatten_internal(
         response_getter_columns,
         false,
+        is_checked,
         &mut response_getter_column_elements,
         &mut response_getter_column_compounds,
     );
@@ -572,14 +1214,132 @@ fn serde_rename_rs_attr(serde_name: &str) -> proc_macro2::TokenStream {
     }
 }
 
+/// A field of a struct, which is either a column or the columns of an embedded struct.
+enum EmbedGroup<'a, T> {
+    Column(T),
+    Embed(&'a stage3::ColumnEmbed, Vec<T>),
+}
+
+/// Group the columns of each embedded struct into one field.
+///
+/// The columns of an embedded struct are next to each other, because they are flattened in place.
+fn embed_groups<'a, T>(
+    columns: impl IntoIterator<Item = (Option<&'a stage3::ColumnEmbed>, T)>,
+) -> Vec<EmbedGroup<'a, T>> {
+    let mut groups: Vec<EmbedGroup<T>> = vec![];
+    for (embed, column) in columns {
+        let last = match groups.last_mut() {
+            Some(EmbedGroup::Embed(last, columns))
+                if embed.is_some_and(|embed| embed.rs_name == last.rs_name) =>
+            {
+                Some(columns)
+            }
+            _ => None,
+        };
+        if let Some(columns) = last {
+            columns.push(column);
+        } else if let Some(embed) = embed {
+            groups.push(EmbedGroup::Embed(embed, vec![column]));
+        } else {
+            groups.push(EmbedGroup::Column(column));
+        }
+    }
+    groups
+}
+
+/// The fields of a struct, with the columns of each embedded struct in one field.
+fn embed_fields<'a>(
+    fields: impl IntoIterator<Item = (Option<&'a stage3::ColumnEmbed>, proc_macro2::TokenStream)>,
+    embed_field: impl Fn(&stage3::ColumnEmbed) -> proc_macro2::TokenStream,
+) -> impl Iterator<Item = proc_macro2::TokenStream> {
+    embed_groups(fields)
+        .into_iter()
+        .map(move |group| match group {
+            EmbedGroup::Column(field) => field,
+            EmbedGroup::Embed(embed, _) => embed_field(embed),
+        })
+}
+
+/// The field of an embedded struct in a create or update request.
+fn embed_request_field(embed: &stage3::ColumnEmbed) -> proc_macro2::TokenStream {
+    let stage3::ColumnEmbed {
+        rs_name,
+        rs_ty_name,
+        ..
+    } = embed;
+    quote! { pub #rs_name: #rs_ty_name, }
+}
+
+/// The value of a column of an embedded struct.
+///
+/// A polymorphic foreign key is split into the table and the ID of the foreign record.
+fn embed_value(
+    embed: &stage3::ColumnEmbed,
+    value: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match embed.poly {
+        Some(stage3::ColumnPoly::Table) => quote! {
+            ::std::string::String::from(::laraxum::model::Poly::table(&#value))
+        },
+        Some(stage3::ColumnPoly::Id) => quote! {
+            ::laraxum::model::Poly::id(&#value)
+        },
+        None => {
+            let column_rs_name = &embed.column_rs_name;
+            quote! { #value.#column_rs_name }
+        }
+    }
+}
+
+/// The value of a column in a request, which is in its embedded struct if it has one.
+fn request_value(
+    request: &proc_macro2::TokenStream,
+    setter: &stage3::RequestColumnSetterMolecule,
+) -> proc_macro2::TokenStream {
+    if let Some(embed) = setter.embed {
+        let embed_rs_name = &embed.rs_name;
+        embed_value(embed, &quote! { #request.#embed_rs_name })
+    } else {
+        let rs_name = setter.rs_name;
+        quote! { #request.#rs_name }
+    }
+}
+
+/// Access a column of a row.
+///
+/// Checked queries return a record with a field for each column,
+/// unchecked queries return a row that is decoded by the name of the column.
+fn response_field_access(
+    name_extern: &str,
+    rs_ty: &syn::Type,
+    is_nullable: bool,
+    is_checked: bool,
+) -> proc_macro2::TokenStream {
+    if is_checked {
+        let name_extern = from_str_to_rs_ident(name_extern);
+        quote! {
+            response.#name_extern
+        }
+    } else {
+        let decode_ty = quote! {
+            <#rs_ty as ::laraxum::model::types::Decode>::Decode
+        };
+        let decode_ty = if is_nullable {
+            quote! { ::core::option::Option<#decode_ty> }
+        } else {
+            decode_ty
+        };
+        quote! {
+            ::sqlx::Row::try_get::<#decode_ty, _>(&response, #name_extern)?
+        }
+    }
+}
+
 fn response_getter_column(
-    name_extern: &Ident,
+    field_access: &proc_macro2::TokenStream,
     is_optional: bool,
     is_parent_optional: bool,
 ) -> proc_macro2::TokenStream {
-    let field_access = quote! {
-        response.#name_extern
-    };
     if is_optional {
         quote! {
             if let ::core::option::Option::Some(v) = #field_access {
@@ -603,16 +1363,94 @@ fn response_getter_column(
     }
 }
 
+/// Decode a record from a row.
+///
+/// With a projection, the fields that aren't selected are placeholders, see `Projection`.
 fn response_getter_compound<'columns>(
     table_ty: &Ident,
     columns: impl IntoIterator<Item = stage3::ResponseColumnGetterRef<'columns>>,
     parent_optional: bool,
+    is_checked: bool,
+    is_batched: bool,
+    projection: Option<Projection<'_>>,
 ) -> proc_macro2::TokenStream {
     let columns = columns.into_iter().map(|column| {
-        let rs_name = column.rs_name();
-        let response_getter = response_getter(column, parent_optional);
-        quote! {
+        let embed = match column {
+            stage3::ResponseColumnGetterRef::Molecule(
+                stage3::ResponseColumnGetterMolecule::Element(element),
+            ) => element.embed,
+            _ => None,
+        };
+        let rs_name = embed.map_or_else(
+            || column.rs_name().clone(),
+            |embed| embed.column_rs_name.clone(),
+        );
+        let response_getter = match column {
+            // filled in afterwards, see `batch_many`
+            stage3::ResponseColumnGetterRef::Collection(_) if is_batched => {
+                quote! { ::std::vec::Vec::new() }
+            }
+            // the children of a foreign record refer back to it, so they would never stop loading
+            stage3::ResponseColumnGetterRef::Collection(collection) if collection.is_children => {
+                quote! { ::std::vec::Vec::new() }
+            }
+            column => response_getter(column, parent_optional, is_checked, is_batched, projection),
+        };
+        (embed, (rs_name, response_getter))
+    });
+    // the columns of an embedded struct are in a struct of their own
+    let columns = embed_groups(columns).into_iter().map(|group| match group {
+        EmbedGroup::Column((rs_name, response_getter)) => quote! {
             #rs_name: #response_getter
+        },
+        // the table and the ID are decoded into the enum of the polymorphic foreign key
+        EmbedGroup::Embed(
+            stage3::ColumnEmbed {
+                rs_name,
+                rs_ty_name,
+                poly: Some(_),
+                ..
+            },
+            columns,
+        ) => {
+            let mut response_getters = columns.iter().map(|(_, response_getter)| response_getter);
+            let table = response_getters.next();
+            let id = response_getters.next();
+            let response_getter = quote! {{
+                let table: ::std::string::String = #table;
+                ::laraxum::model::poly::decode::<#rs_ty_name>(&table, #id)?
+            }};
+            let response_getter = match projection {
+                Some(projection) => match projection.field(Some(&rs_name.unraw().to_string())) {
+                    Some(path) => {
+                        let is_selected = is_selected(&path);
+                        let unselected = unselected();
+                        quote! {
+                            if #is_selected { #response_getter } else { #unselected }
+                        }
+                    }
+                    None => unselected(),
+                },
+                None => response_getter,
+            };
+            quote! {
+                #rs_name: #response_getter
+            }
+        }
+        EmbedGroup::Embed(
+            stage3::ColumnEmbed {
+                rs_name,
+                rs_ty_name,
+                ..
+            },
+            columns,
+        ) => {
+            let columns = columns
+                .iter()
+                .map(|(rs_name, response_getter)| quote! { #rs_name: #response_getter });
+            quote! {
+                #rs_name: #rs_ty_name { #( #columns ),* }
+            }
         }
     });
 
@@ -624,19 +1462,14 @@ fn response_getter_compound<'columns>(
 fn response_getter(
     column: stage3::ResponseColumnGetterRef<'_>,
     is_parent_optional: bool,
+    is_checked: bool,
+    is_batched: bool,
+    projection: Option<Projection<'_>>,
 ) -> proc_macro2::TokenStream {
     match column {
         stage3::ResponseColumnGetterRef::Molecule(
             stage3::ResponseColumnGetterMolecule::Element(element),
-        ) => {
-            let &stage3::ResponseColumnGetterElement {
-                ref name_extern,
-                is_optional,
-                ..
-            } = element;
-            let name_extern = from_str_to_rs_ident(name_extern);
-            response_getter_column(&name_extern, is_optional, is_parent_optional)
-        }
+        ) => response_getter_element(element, is_parent_optional, is_checked, projection),
         stage3::ResponseColumnGetterRef::Molecule(
             stage3::ResponseColumnGetterMolecule::Compound(compound),
         ) => {
@@ -647,55 +1480,177 @@ fn response_getter(
                 ..
             } = compound;
             let is_parent_optional = is_parent_optional || is_optional;
+            // the relations of the foreign record are loaded by its ID, see `batch_many_compound`
+            let is_batched = is_batched && compound_id_rs_name(compound).is_some();
+            let path = projection.and_then(|projection| projection.field(compound.name.as_deref()));
+            let required = [&*compound.foreign_table_id_name_intern];
+            let compound_projection = projection.map(|_| Projection {
+                path: path.as_deref(),
+                required: &required,
+            });
 
             let getter = response_getter_compound(
                 rs_ty_name,
                 columns.iter().map(stage3::ResponseColumnGetterRef::from),
                 is_parent_optional,
+                is_checked,
+                is_batched,
+                compound_projection,
             );
+            // the foreign record can be boxed, like when it's in a foreign key cycle
+            let getter = quote! { ::core::convert::From::from(#getter) };
             if is_optional {
                 // catch any returns in the closure, else return `Ok(Some(T))`
-                quote! {
+                let getter = quote! {
                     (async || {
                         ::core::result::Result::Ok::<_, ::sqlx::Error>(
                             ::core::option::Option::Some(#getter)
                         )
                     })().await?
+                };
+                // a foreign record that isn't selected isn't joined
+                match (projection, path) {
+                    (None, _) => getter,
+                    (Some(_), Some(path)) => {
+                        let is_selected = is_selected(&path);
+                        quote! {
+                            if #is_selected { #getter } else { ::core::option::Option::None }
+                        }
+                    }
+                    (Some(_), None) => quote! { ::core::option::Option::None },
                 }
             } else {
                 getter
             }
         }
+        stage3::ResponseColumnGetterRef::Molecule(
+            stage3::ResponseColumnGetterMolecule::Truncated(_),
+        ) => {
+            quote! { ::core::option::Option::None }
+        }
         stage3::ResponseColumnGetterRef::Collection(collection) => {
-            let &stage3::ResponseColumnGetterCollection {
-                rs_name: _,
-                aggregate_rs_name: table_rs_name,
-                ref table_id_name_extern,
-                many_foreign_table_rs_name,
-            } = collection;
-            let one_id = {
-                let table_id_name_extern = from_str_to_rs_ident(table_id_name_extern);
-                response_getter_column(&table_id_name_extern, false, is_parent_optional)
-            };
-            quote! {
-                ::core::result::Result::map_err(
-                    <#many_foreign_table_rs_name as ::laraxum::ManyModel::<
-                        #table_rs_name,
-                    >>::get_many(
-                        db,
-                        #one_id,
-                    ).await,
-                    |_| {
-                        ::sqlx::Error::ColumnNotFound(
-                            ::std::string::String::from(#table_id_name_extern)
-                        )
-                    }
-                )?
+            response_getter_collection(collection, is_parent_optional, is_checked, projection)
+        }
+    }
+}
+
+fn response_getter_element(
+    element: &stage3::ResponseColumnGetterElement,
+    is_parent_optional: bool,
+    is_checked: bool,
+    projection: Option<Projection<'_>>,
+) -> proc_macro2::TokenStream {
+    let &stage3::ResponseColumnGetterElement {
+        ref name_extern,
+        is_optional,
+        rs_ty,
+        ..
+    } = element;
+    let field_access = response_field_access(
+        name_extern,
+        rs_ty,
+        is_optional || is_parent_optional,
+        is_checked,
+    );
+    let getter = response_getter_column(&field_access, is_optional, is_parent_optional);
+    match projection.map(|projection| projection.element(element)) {
+        None | Some(Selected::Always) => getter,
+        Some(Selected::By(path)) => {
+            let is_selected = is_selected(&path);
+            let unselected = unselected();
+            quote! { if #is_selected { #getter } else { #unselected } }
+        }
+        Some(Selected::Never) => unselected(),
+    }
+}
+
+fn response_getter_collection(
+    collection: &stage3::ResponseColumnGetterCollection,
+    is_parent_optional: bool,
+    is_checked: bool,
+    projection: Option<Projection<'_>>,
+) -> proc_macro2::TokenStream {
+    let stage3::ResponseColumnGetterCollection {
+        table_id_name_extern,
+        table_id_rs_ty,
+        ..
+    } = collection;
+    let get_many = collection_getter_fn(collection, false);
+    let one_id = {
+        let field_access = response_field_access(
+            table_id_name_extern,
+            table_id_rs_ty,
+            is_parent_optional,
+            is_checked,
+        );
+        response_getter_column(&field_access, false, is_parent_optional)
+    };
+    let many = quote! {
+        ::core::result::Result::map_err(
+            #get_many(
+                db,
+                #one_id,
+            ).await,
+            |_| {
+                ::sqlx::Error::ColumnNotFound(
+                    ::std::string::String::from(#table_id_name_extern)
+                )
             }
+        )?
+    };
+    let many = collection_values(collection, &many);
+    match projection.map(|projection| projection.field(collection.name.as_deref())) {
+        None => many,
+        Some(Some(path)) => {
+            let is_selected = is_selected(&path);
+            quote! { if #is_selected { #many } else { ::std::vec::Vec::new() } }
+        }
+        Some(None) => quote! { ::std::vec::Vec::new() },
+    }
+}
+
+/// Convert the values of a collection into the type of the field.
+///
+/// The values of a `ManyModel` with pivot columns can be converted into the foreign records,
+/// so the field doesn't have to include the pivot columns.
+fn collection_values(
+    collection: &stage3::ResponseColumnGetterCollection,
+    many: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if collection.is_children {
+        many.clone()
+    } else {
+        quote! {
+            ::core::iter::Iterator::collect(::core::iter::Iterator::map(
+                ::core::iter::IntoIterator::into_iter(#many),
+                ::core::convert::Into::into,
+            ))
         }
     }
 }
 
+/// The function of the foreign table that returns the values of a collection.
+fn collection_getter_fn(
+    collection: &stage3::ResponseColumnGetterCollection,
+    is_batch: bool,
+) -> proc_macro2::TokenStream {
+    let &stage3::ResponseColumnGetterCollection {
+        aggregate_rs_name,
+        many_foreign_table_rs_name,
+        is_children,
+        ..
+    } = collection;
+    let (collection_trait, get_many) = match (is_children, is_batch) {
+        (false, false) => (quote! { ManyModel }, quote! { get_many }),
+        (false, true) => (quote! { ManyModel }, quote! { get_many_batch }),
+        (true, false) => (quote! { Children }, quote! { get_children }),
+        (true, true) => (quote! { Children }, quote! { get_children_batch }),
+    };
+    quote! {
+        <#many_foreign_table_rs_name as ::laraxum::#collection_trait::<#aggregate_rs_name>>::#get_many
+    }
+}
+
 fn response_getter_fn(getter: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
     quote! {
         async |response| match response {
@@ -704,43 +1659,1762 @@ fn response_getter_fn(getter: &proc_macro2::TokenStream) -> proc_macro2::TokenSt
         }
     }
 }
-
-fn transform_response_one(
+
+/// Create a query with its arguments.
+///
+/// Checked queries are verified against the database at compile time,
+/// unchecked queries bind their arguments at runtime.
+fn query<T>(
+    sql: &str,
+    args: impl IntoIterator<Item = T>,
+    is_checked: bool,
+) -> proc_macro2::TokenStream
+where
+    T: quote::ToTokens,
+{
+    let args = args.into_iter();
+    if is_checked {
+        quote! {
+            ::sqlx::query!(#sql #(, #args)*)
+        }
+    } else {
+        quote! {
+            ::sqlx::query(#sql) #(.bind(#args))*
+        }
+    }
+}
+
+/// A query of a single value, like a count.
+fn query_scalar<T>(
+    sql: &str,
+    args: impl IntoIterator<Item = T>,
+    rs_ty: &Type,
+    is_checked: bool,
+) -> proc_macro2::TokenStream
+where
+    T: quote::ToTokens,
+{
+    let args = args.into_iter();
+    if is_checked {
+        quote! {
+            ::sqlx::query_scalar!(#sql #(, #args)*)
+        }
+    } else {
+        quote! {
+            ::sqlx::query_scalar::<_, #rs_ty>(#sql) #(.bind(#args))*
+        }
+    }
+}
+
+/// Run a query with the timeout of the database and log it if it's slow.
+///
+/// The SQL is usually a literal, but it can be built at runtime, see `filter_aggregate`.
+fn run_query(
+    future: &proc_macro2::TokenStream,
+    sql: impl quote::ToTokens,
+    table_name: &str,
+) -> proc_macro2::TokenStream {
+    quote! {
+        ::laraxum::model::QueryOptions::run(&db.query, #table_name, #sql, #future).await
+    }
+}
+fn execute_query(
+    response: &proc_macro2::TokenStream,
+    sql: &str,
+    table_name: &str,
+) -> proc_macro2::TokenStream {
+    run_query(&quote! { #response.execute(&db.pool) }, sql, table_name)
+}
+
+/// Load the many-to-many relations of all records in `response` at once,
+/// instead of a query for each record.
+///
+/// The relations of the foreign records that are joined are loaded at once as well,
+/// see `batch_many_compound`.
+///
+/// With a projection, only the relations that are selected are loaded, see `Projection`.
+fn batch_many<'columns>(
+    table_id_rs_name: Option<&Ident>,
+    columns: impl IntoIterator<Item = stage3::ResponseColumnGetterRef<'columns>>,
+    projection: Option<Projection<'_>>,
+) -> Option<proc_macro2::TokenStream> {
+    let batches = columns
+        .into_iter()
+        .filter_map(|column| match column {
+            stage3::ResponseColumnGetterRef::Collection(collection) => {
+                let table_id_rs_name = table_id_rs_name?;
+                let rs_name = collection.rs_name;
+                let get_many_batch = collection_getter_fn(collection, true);
+                let many = collection_values(collection, &quote! { many });
+                let batch = quote! {{
+                    let ones: ::std::vec::Vec<_> = ::core::iter::Iterator::collect(
+                        ::core::iter::Iterator::map(response.iter(), |response| {
+                            response.#table_id_rs_name
+                        }),
+                    );
+                    let many = #get_many_batch(db, &ones).await?;
+                    for (response, many) in ::core::iter::Iterator::zip(response.iter_mut(), many) {
+                        response.#rs_name = #many;
+                    }
+                }};
+                batch_selected(batch, projection, collection.name.as_deref())
+            }
+            stage3::ResponseColumnGetterRef::Molecule(
+                stage3::ResponseColumnGetterMolecule::Compound(compound),
+            ) => batch_many_compound(compound, projection),
+            stage3::ResponseColumnGetterRef::Molecule(_) => None,
+        })
+        .collect::<Vec<_>>();
+    (!batches.is_empty()).then(|| {
+        quote! { #(#batches)* }
+    })
+}
+
+/// Load the many-to-many relations of the foreign records of a column in `response` at once.
+///
+/// A foreign record can be joined for more than one record, but its relations can't be cloned,
+/// so only the first of each foreign record is loaded at once and the others one by one.
+/// The children of a foreign record aren't loaded, see `response_getter_compound`.
+fn batch_many_compound(
+    compound: &stage3::ResponseColumnGetterCompound,
+    projection: Option<Projection<'_>>,
+) -> Option<proc_macro2::TokenStream> {
+    let table_id_rs_name = compound_id_rs_name(compound)?;
+    let path = match projection {
+        Some(projection) => Some(projection.field(compound.name.as_deref())?),
+        None => None,
+    };
+    let required = [&*compound.foreign_table_id_name_intern];
+    let projection = projection.map(|_| Projection {
+        path: path.as_deref(),
+        required: &required,
+    });
+    let batches = compound
+        .columns
+        .iter()
+        .filter_map(|column| match column {
+            stage3::ResponseColumnGetter::Collection(collection) if !collection.is_children => {
+                let rs_name = collection.rs_name;
+                let get_many = collection_getter_fn(collection, false);
+                let get_many_batch = collection_getter_fn(collection, true);
+                let many = collection_values(collection, &quote! { many });
+                let batch = quote! {{
+                    let mut ones = ::std::collections::HashSet::new();
+                    let (response, duplicates): (::std::vec::Vec<_>, ::std::vec::Vec<_>) =
+                        ::core::iter::Iterator::partition(response.iter_mut(), |response| {
+                            ones.insert(::laraxum::model::types::Encode::encode(
+                                response.#table_id_rs_name,
+                            ))
+                        });
+                    let ones: ::std::vec::Vec<_> = ::core::iter::Iterator::collect(
+                        ::core::iter::Iterator::map(response.iter(), |response| {
+                            response.#table_id_rs_name
+                        }),
+                    );
+                    let many = #get_many_batch(db, &ones).await?;
+                    for (response, many) in ::core::iter::Iterator::zip(
+                        ::core::iter::IntoIterator::into_iter(response),
+                        many,
+                    ) {
+                        response.#rs_name = #many;
+                    }
+                    for response in duplicates {
+                        let many = #get_many(db, response.#table_id_rs_name).await?;
+                        response.#rs_name = #many;
+                    }
+                }};
+                batch_selected(batch, projection, collection.name.as_deref())
+            }
+            stage3::ResponseColumnGetter::Molecule(
+                stage3::ResponseColumnGetterMolecule::Compound(compound),
+            ) => batch_many_compound(compound, projection),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if batches.is_empty() {
+        return None;
+    }
+    let rs_name = compound.rs_name;
+    let foreign_record = if compound.is_optional {
+        quote! { response.#rs_name.as_mut() }
+    } else {
+        quote! { ::core::option::Option::Some(&mut response.#rs_name) }
+    };
+    Some(quote! {{
+        let mut response: ::std::vec::Vec<_> = ::core::iter::Iterator::collect(
+            ::core::iter::Iterator::filter_map(response.iter_mut(), |response| #foreign_record),
+        );
+        #(#batches)*
+    }})
+}
+
+/// Only load the relations of a field if it is selected, see `Projection`.
+fn batch_selected(
+    batch: proc_macro2::TokenStream,
+    projection: Option<Projection<'_>>,
+    name: Option<&str>,
+) -> Option<proc_macro2::TokenStream> {
+    let Some(projection) = projection else {
+        return Some(batch);
+    };
+    let is_selected = is_selected(&projection.field(name)?);
+    Some(quote! {
+        if #is_selected #batch
+    })
+}
+
+/// The field of the ID of a foreign record, if its relations are loaded by it.
+fn compound_id_rs_name<'columns>(
+    compound: &'columns stage3::ResponseColumnGetterCompound<'columns>,
+) -> Option<&'columns Ident> {
+    let table_id_name_extern = compound.columns.iter().find_map(|column| match column {
+        stage3::ResponseColumnGetter::Collection(collection) if !collection.is_children => {
+            Some(&collection.table_id_name_extern)
+        }
+        _ => None,
+    })?;
+    compound.columns.iter().find_map(|column| match column {
+        stage3::ResponseColumnGetter::Molecule(stage3::ResponseColumnGetterMolecule::Element(
+            element,
+        )) if element.name_extern == *table_id_name_extern => Some(element.rs_name),
+        _ => None,
+    })
+}
+
+/// The columns and joins of the selected fields of a record,
+/// see `::laraxum::model::fields::select_sql`.
+fn select_columns<'columns>(
+    columns: impl IntoIterator<Item = stage3::ResponseColumnGetterRef<'columns>>,
+    projection: Projection<'_>,
+    (columns_select, joins_select): (
+        &mut Vec<proc_macro2::TokenStream>,
+        &mut Vec<proc_macro2::TokenStream>,
+    ),
+) {
+    for column in columns {
+        match column {
+            stage3::ResponseColumnGetterRef::Molecule(
+                stage3::ResponseColumnGetterMolecule::Element(element),
+            ) => {
+                let path = match projection.element(element) {
+                    Selected::Always => quote! { ::core::option::Option::None },
+                    Selected::By(path) => quote! { ::core::option::Option::Some(#path) },
+                    Selected::Never => continue,
+                };
+                let sql = fmt2::fmt! { { str } =>
+                    {element.name_intern} " AS " {element.name_extern}
+                };
+                columns_select.push(quote! {
+                    ::laraxum::model::fields::SelectColumn { path: #path, sql: #sql }
+                });
+            }
+            stage3::ResponseColumnGetterRef::Molecule(
+                stage3::ResponseColumnGetterMolecule::Compound(compound),
+            ) => {
+                let Some(path) = projection.field(compound.name.as_deref()) else {
+                    continue;
+                };
+                let sql = fmt2::fmt! { { str } =>
+                    " LEFT JOIN "
+                    {compound.foreign_table_name_intern} " AS " {compound.foreign_table_name_extern}
+                    " ON "
+                    {compound.name_intern} "=" {compound.foreign_table_id_name_intern}
+                };
+                joins_select.push(quote! {
+                    ::laraxum::model::fields::SelectJoin { path: #path, sql: #sql }
+                });
+                let required = [&*compound.foreign_table_id_name_intern];
+                select_columns(
+                    compound
+                        .columns
+                        .iter()
+                        .map(stage3::ResponseColumnGetterRef::from),
+                    Projection {
+                        path: Some(&path),
+                        required: &required,
+                    },
+                    (columns_select, joins_select),
+                );
+            }
+            stage3::ResponseColumnGetterRef::Molecule(
+                stage3::ResponseColumnGetterMolecule::Truncated(_),
+            )
+            | stage3::ResponseColumnGetterRef::Collection(_) => {}
+        }
+    }
+}
+
+/// The query of an aggregator with an `in` filter, which is built at runtime
+/// with a placeholder for each value of the filter, see `::laraxum::model::filter::in_values`.
+///
+/// The filter is the first condition, so its values are bound before the other arguments.
+#[derive(Clone, Copy)]
+struct InFilter<'a> {
+    /// the column, like `database__users.city`
+    column: &'a str,
+    /// the values of the filter in the request, separated by commas
+    value: &'a proc_macro2::TokenStream,
+}
+impl InFilter<'_> {
+    /// Split the values of the filter into `values`.
+    fn values(self) -> proc_macro2::TokenStream {
+        let value = self.value;
+        quote! {
+            let values = ::laraxum::model::filter::in_values(&#value)?;
+        }
+    }
+    /// The SQL with a placeholder for each value in `values`, which replaces the one in `sql`.
+    fn sql(self, sql: &str) -> proc_macro2::TokenStream {
+        let column = self.column;
+        let condition = fmt2::fmt! { { str } => {column} " IN (?)" };
+        let (before, after) = sql.split_once(&*condition).unwrap_or((sql, ""));
+        quote! {
+            [
+                #before,
+                &*::laraxum::model::filter::in_condition(#column, values.len()),
+                #after,
+            ]
+            .concat()
+        }
+    }
+    /// Bind the values in `values` to `query`, and then `args`.
+    fn query<T>(
+        query: &proc_macro2::TokenStream,
+        args: impl IntoIterator<Item = T>,
+    ) -> proc_macro2::TokenStream
+    where
+        T: quote::ToTokens,
+    {
+        let args = args.into_iter();
+        quote! {
+            ::core::iter::Iterator::fold(
+                ::core::iter::IntoIterator::into_iter(values),
+                #query,
+                |query, value| query.bind(value),
+            )
+            #(.bind(#args))*
+        }
+    }
+}
+
+/// A query of the selected fields of a table, which is built at runtime,
+/// see `::laraxum::model::fields`.
+///
+/// The rows are always decoded at runtime, and the fields that aren't selected are placeholders.
+struct SelectFields<'a> {
+    /// the columns and joins of a query of all fields, which are replaced by the selected ones,
+    /// of the checked and the unchecked queries
+    prefixes: [String; 2],
+    from: String,
+    columns: Vec<proc_macro2::TokenStream>,
+    joins: Vec<proc_macro2::TokenStream>,
+    table_name_intern: &'a str,
+    response_getter: proc_macro2::TokenStream,
+    batch: Option<proc_macro2::TokenStream>,
+}
+impl<'a> SelectFields<'a> {
+    /// `required` are the columns of the table that are always selected, like its ID.
+    fn new(table: &'a stage3::Table, required: &[&str]) -> Self {
+        let projection = Projection {
+            path: Some(""),
+            required,
+        };
+        let columns = || table.columns.iter().map(stage3::ColumnRef::response_getter);
+        let (mut columns_select, mut joins_select) = (vec![], vec![]);
+        select_columns(
+            columns(),
+            projection,
+            (&mut columns_select, &mut joins_select),
+        );
+        let response_getter = response_getter_compound(
+            table.rs_name,
+            columns(),
+            false,
+            false,
+            true,
+            Some(projection),
+        );
+        let table_id_rs_name = table
+            .columns
+            .model()
+            .map(|table_id| table_id.response.field.rs_name);
+        let prefixes = [true, false].map(|is_checked| {
+            let (response_getter_elements, response_getter_compounds) =
+                flatten(columns(), is_checked);
+            get_all(
+                &table.name_intern,
+                &table.name_extern,
+                (&response_getter_elements, &response_getter_compounds),
+            )
+        });
+        Self {
+            prefixes,
+            from: fmt2::fmt! { { str } => " FROM " {table.name_intern} " AS " {table.name_extern} },
+            columns: columns_select,
+            joins: joins_select,
+            table_name_intern: &table.name_intern,
+            response_getter: response_getter_fn(&response_getter),
+            batch: batch_many(table_id_rs_name, columns(), Some(projection)),
+        }
+    }
+    /// Get the selected fields with the conditions of `sql`, which is a query of all fields.
+    fn transform_response<T>(
+        &self,
+        sql: &str,
+        args: impl IntoIterator<Item = T>,
+        is_one: bool,
+        in_filter: Option<InFilter<'_>>,
+    ) -> proc_macro2::TokenStream
+    where
+        T: quote::ToTokens,
+    {
+        let rest = self
+            .prefixes
+            .iter()
+            .find_map(|prefix| sql.strip_prefix(prefix.as_str()))
+            .unwrap_or_default();
+        let args = args.into_iter();
+        let Some(in_filter) = in_filter else {
+            let select = self.select(rest);
+            let query = quote! { ::sqlx::query(&sql) #(.bind(#args))* };
+            let response = self.response(&query, is_one);
+            return quote! {{
+                let sql = #select;
+                #response
+            }};
+        };
+        let values = in_filter.values();
+        let rest = in_filter.sql(rest);
+        let select = self.select(quote! { &rest });
+        let response = self.response(
+            &InFilter::query(&quote! { ::sqlx::query(&sql) }, args),
+            is_one,
+        );
+        quote! {{
+            #values
+            let rest = #rest;
+            let sql = #select;
+            #response
+        }}
+    }
+    /// The SQL of the selected fields followed by `rest`, like the conditions.
+    fn select(&self, rest: impl quote::ToTokens) -> proc_macro2::TokenStream {
+        let Self {
+            from,
+            columns,
+            joins,
+            ..
+        } = self;
+        quote! {
+            ::laraxum::model::fields::select_sql(
+                fields,
+                &[#( #columns ),*],
+                #from,
+                &[#( #joins ),*],
+                #rest,
+            )
+        }
+    }
+    /// Decode the selected fields of the rows of `query`, whose SQL is `sql`.
+    fn response(&self, query: &proc_macro2::TokenStream, is_one: bool) -> proc_macro2::TokenStream {
+        transform_response(
+            query,
+            (quote! { &sql }, self.table_name_intern),
+            &self.response_getter,
+            self.batch.as_ref(),
+            is_one,
+        )
+    }
+}
+
+/// Expand the lazy foreign keys in the paths of relations, see `::laraxum::model::expand`.
+fn expand<'columns>(
+    columns: impl IntoIterator<Item = stage3::ColumnRef<'columns>>,
+) -> Option<proc_macro2::TokenStream> {
+    let columns = columns
+        .into_iter()
+        .filter_map(|column| match column {
+            stage3::ColumnRef::Molecule(molecule) => Some(molecule),
+            stage3::ColumnRef::Collection(_) => None,
+        })
+        .filter_map(|column| {
+            let field = &column.response.field;
+            if field.attr.skip {
+                return None;
+            }
+            let name = field
+                .attr
+                .name
+                .clone()
+                .unwrap_or_else(|| field.rs_name.to_string());
+            Some((field.rs_name, name, column.expand.as_ref()?))
+        })
+        .collect::<Vec<_>>();
+    if columns.is_empty() {
+        return None;
+    }
+
+    let is_expandables = columns.iter().map(|&(_, ref name, expand)| {
+        let foreign_table_rs_name = expand.foreign_table_rs_name;
+        quote! {
+            (#name, ::core::option::Option::None) => true,
+            (#name, ::core::option::Option::Some(path)) => {
+                <#foreign_table_rs_name as ::laraxum::Collection>::is_expandable(path)
+            }
+        }
+    });
+    let expands = columns
+        .iter()
+        .map(|&(rs_name, ref name, expand)| expand_column(rs_name, name, expand));
+
+    Some(quote! {
+        fn is_expandable(path: &str) -> bool {
+            let (name, path) = match str::split_once(path, '.') {
+                ::core::option::Option::Some((name, path)) => {
+                    (name, ::core::option::Option::Some(path))
+                }
+                ::core::option::Option::None => (path, ::core::option::Option::None),
+            };
+            match (name, path) {
+                #( #is_expandables )*
+                _ => false,
+            }
+        }
+        async fn expand(
+            db: &Self::Db,
+            mut response: ::std::vec::Vec<&mut Self::Response>,
+            paths: &[&str],
+        )
+            -> ::core::result::Result<(), ::laraxum::Error>
+        {
+            #( #expands )*
+            ::core::result::Result::Ok(())
+        }
+    })
+}
+
+/// Expand the foreign records of a column, if its name is in the paths.
+fn expand_column(
+    rs_name: &Ident,
+    name: &str,
+    expand: &stage3::ColumnExpand,
+) -> proc_macro2::TokenStream {
+    let stage3::ColumnExpand {
+        foreign_table_rs_name,
+        is_optional,
+        is_lazy,
+    } = *expand;
+    // the joined foreign record can be boxed, like when it's in a foreign key cycle
+    let borrow_mut = |response| {
+        if is_lazy {
+            response
+        } else {
+            quote! {
+                ::core::borrow::BorrowMut::<#foreign_table_rs_name>::borrow_mut(#response)
+            }
+        }
+    };
+    let response = if is_optional {
+        let response = borrow_mut(quote! { response });
+        quote! {
+            ::core::iter::Iterator::filter_map(
+                response.iter_mut(),
+                |response| response.#rs_name.as_mut().map(|response| #response),
+            )
+        }
+    } else {
+        let response = borrow_mut(quote! { &mut response.#rs_name });
+        quote! {
+            ::core::iter::Iterator::map(
+                response.iter_mut(),
+                |response| #response,
+            )
+        }
+    };
+    let expand = if is_lazy {
+        quote! {
+            ::laraxum::model::expand::expand::<#foreign_table_rs_name>(db, response, &paths)
+        }
+    } else {
+        quote! {
+            <#foreign_table_rs_name as ::laraxum::Collection>::expand(db, response, &paths)
+        }
+    };
+    quote! {
+        if let ::core::option::Option::Some(paths) =
+            ::laraxum::model::expand::sub_paths(paths, #name)
+        {
+            let response = ::core::iter::Iterator::collect(#response);
+            // relations can refer back to this table, so the future must be boxed
+            ::std::boxed::Box::pin(#expand).await?;
+        }
+    }
+}
+
+/// The fields of the response that can be selected, see `::laraxum::model::fields`,
+/// and the names of the fields, like the headers of a CSV export.
+///
+/// The fields of relations are validated by the foreign table.
+/// The values of a `ManyModel` aren't a table of their own, so any of their fields can be selected.
+fn fields<'columns>(
+    columns: impl IntoIterator<Item = stage3::ColumnRef<'columns>>,
+) -> proc_macro2::TokenStream {
+    let columns = columns.into_iter().filter_map(|column| {
+        let is_field = |foreign_table_rs_name: &Ident| {
+            quote! { <#foreign_table_rs_name as ::laraxum::Collection>::is_field(path) }
+        };
+        let (field, is_foreign_field) = match column {
+            stage3::ColumnRef::Molecule(molecule) => (
+                &molecule.response.field,
+                molecule
+                    .expand
+                    .as_ref()
+                    .map(|expand| is_field(expand.foreign_table_rs_name)),
+            ),
+            stage3::ColumnRef::Collection(collection) => {
+                let getter = &collection.response.getter;
+                let is_foreign_field = if getter.is_children {
+                    is_field(getter.many_foreign_table_rs_name)
+                } else {
+                    quote! { !path.is_empty() }
+                };
+                (&collection.response.field, Some(is_foreign_field))
+            }
+        };
+        if field.attr.skip {
+            return None;
+        }
+        let name = match (field.embed, &field.attr.name) {
+            (Some(embed), _) => embed.column_rs_name.unraw().to_string(),
+            (None, Some(name)) => name.clone(),
+            (None, None) => field.rs_name.unraw().to_string(),
+        };
+        Some((field.embed, (name, is_foreign_field)))
+    });
+
+    let groups = embed_groups(columns);
+
+    let names = groups.iter().map(|group| match group {
+        EmbedGroup::Column((name, _)) => name.clone(),
+        EmbedGroup::Embed(embed, _) => embed.rs_name.unraw().to_string(),
+    });
+    let is_fields = groups.iter().map(|group| match group {
+        EmbedGroup::Column((name, Some(is_foreign_field))) => quote! {
+            (#name, ::core::option::Option::None) => true,
+            (#name, ::core::option::Option::Some(path)) => #is_foreign_field,
+        },
+        EmbedGroup::Column((name, None)) => quote! {
+            (#name, ::core::option::Option::None) => true,
+        },
+        // the fields of an embedded struct are its columns
+        EmbedGroup::Embed(embed, columns) => {
+            let name = embed.rs_name.unraw().to_string();
+            let column_names = columns.iter().map(|(column_name, _)| column_name);
+            quote! {
+                (#name, ::core::option::Option::None) => true,
+                (#name, ::core::option::Option::Some(#( #column_names )|*)) => true,
+            }
+        }
+    });
+
+    quote! {
+        const FIELDS: &'static [&'static str] = &[#( #names ),*];
+        fn is_field(path: &str) -> bool {
+            let (name, path) = match str::split_once(path, '.') {
+                ::core::option::Option::Some((name, path)) => {
+                    (name, ::core::option::Option::Some(path))
+                }
+                ::core::option::Option::None => (path, ::core::option::Option::None),
+            };
+            match (name, path) {
+                #( #is_fields )*
+                _ => false,
+            }
+        }
+    }
+}
+
+/// The public aggregators of a table, which are combined in its filter, see `filter_aggregate`.
+struct Filter<'a> {
+    /// the filters with the type of their value and their condition,
+    /// which can be in multiple aggregators
+    filters: Vec<(Ident, stage3::ColumnAttrAggregateFilter, syn::Type, String)>,
+    /// the searches with their relevance, see `relevance`
+    searches: Vec<(Ident, String)>,
+    /// the columns that can be sorted by, with their ascending and descending `ORDER BY`
+    sorts: Vec<(&'a Ident, String, String)>,
+    limit: bool,
+    /// how many records per page, how many at most and if they are in an envelope,
+    /// like the first aggregator with pages
+    page: Option<(u64, Option<u64>, bool)>,
+}
+impl<'a> Filter<'a> {
+    fn new(table: &'a stage3::Table) -> Self {
+        let aggregates = table
+            .columns
+            .iter()
+            .filter_map(|column| match column {
+                stage3::ColumnRef::Molecule(molecule) => Some(molecule),
+                stage3::ColumnRef::Collection(_) => None,
+            })
+            .flat_map(|column| {
+                column
+                    .aggregates
+                    .iter()
+                    .filter(|aggregate| aggregate.is_pub)
+                    .map(move |aggregate| (column, aggregate))
+            });
+
+        let mut filter = Self {
+            filters: vec![],
+            searches: vec![],
+            sorts: vec![],
+            limit: false,
+            page: None,
+        };
+        for (column, aggregate) in aggregates {
+            let column_response_name = column.response.field.rs_name;
+            let filter_name_intern = filter_name_intern(table, column, aggregate);
+            let condition = aggregate
+                .filter
+                .parameter()
+                .zip(filter_condition(aggregate.filter, &filter_name_intern));
+            if let Some((parameter_name, condition)) = condition {
+                let rs_name =
+                    quote::format_ident!("filter_{}_{}", column_response_name, parameter_name);
+                if !filter
+                    .filters
+                    .iter()
+                    .any(|(filter_rs_name, _, _, _)| filter_rs_name == &rs_name)
+                {
+                    if aggregate.filter.is_search() {
+                        filter
+                            .searches
+                            .push((rs_name.clone(), relevance(&filter_name_intern)));
+                    }
+                    let rs_ty =
+                        aggregate_filter_rs_ty(aggregate.filter, column.response.field.rs_ty);
+                    filter
+                        .filters
+                        .push((rs_name, aggregate.filter, rs_ty, condition));
+                }
+            }
+            if aggregate.is_sort
+                && !filter
+                    .sorts
+                    .iter()
+                    .any(|(sort_rs_name, _, _)| *sort_rs_name == column_response_name)
+            {
+                let name_intern = column.name_intern();
+                filter.sorts.push((
+                    column_response_name,
+                    fmt2::fmt! { { str } => {name_intern} " ASC" },
+                    fmt2::fmt! { { str } => {name_intern} " DESC" },
+                ));
+            }
+            match aggregate.limit {
+                stage3::ColumnAttrAggregateLimit::None => {}
+                stage3::ColumnAttrAggregateLimit::Limit
+                | stage3::ColumnAttrAggregateLimit::Cursor => {
+                    filter.limit = true;
+                }
+                stage3::ColumnAttrAggregateLimit::Page {
+                    per_page,
+                    max_per_page,
+                    is_envelope,
+                } => {
+                    filter
+                        .page
+                        .get_or_insert((per_page, max_per_page, is_envelope));
+                }
+            }
+        }
+        filter
+    }
+
+    /// The request of the filter, with the columns that can be sorted by,
+    /// like `?sort=-created_at,name`.
+    fn request(&self, filter_rs_name: &Ident, sort_rs_name: &Ident) -> proc_macro2::TokenStream {
+        let filter_fields = self.filters.iter().map(|(rs_name, _, rs_ty, _)| {
+            quote! { pub #rs_name: ::core::option::Option<#rs_ty>, }
+        });
+        let sort_token_stream = (!self.sorts.is_empty()).then(|| {
+            let variants = self.sorts.iter().map(|(rs_name, _, _)| {
+                let name = rs_name.unraw().to_string();
+                quote! {
+                    #[serde(rename = #name)]
+                    #rs_name
+                }
+            });
+            quote! {
+                #[derive(::core::clone::Clone, ::serde::Deserialize)]
+                #[allow(non_camel_case_types)]
+                pub enum #sort_rs_name {
+                    #( #variants, )*
+                }
+            }
+        });
+        let sort_field = sort_token_stream.is_some().then(|| {
+            quote! {
+                pub sort: ::core::option::Option<::laraxum::model::filter::SortBy<#sort_rs_name>>,
+            }
+        });
+        let page_field = self.page.map(|_| {
+            quote! { pub page: ::core::option::Option<u64>, }
+        });
+        let per_page_field = self.is_per_page().then(|| {
+            quote! { pub per_page: ::core::option::Option<u64>, }
+        });
+        let limit_field = self.limit.then(|| {
+            quote! { pub limit: ::core::option::Option<u64>, }
+        });
+        // the request is cloned to count the records
+        quote! {
+            #sort_token_stream
+            #[derive(::core::clone::Clone, ::serde::Deserialize)]
+            pub struct #filter_rs_name {
+                #( #filter_fields )*
+                #sort_field
+                #page_field
+                #per_page_field
+                #limit_field
+            }
+        }
+    }
+
+    /// If the client can choose how many records per page.
+    fn is_per_page(&self) -> bool {
+        self.page
+            .is_some_and(|(_, max_per_page, _)| max_per_page.is_some())
+    }
+    /// How many records per page, which the client can choose up to the max.
+    fn per_page(&self) -> Option<proc_macro2::TokenStream> {
+        let (per_page, max_per_page, _) = self.page?;
+        Some(if let Some(max_per_page) = max_per_page {
+            quote! {
+                ::core::cmp::Ord::min(
+                    ::core::option::Option::unwrap_or(request.per_page, #per_page),
+                    #max_per_page,
+                )
+            }
+        } else {
+            quote! { #per_page }
+        })
+    }
+
+    /// Append the conditions of the filters that are in the request to `sql`,
+    /// and bind their values to `query`.
+    fn conditions(&self) -> [proc_macro2::TokenStream; 2] {
+        if self.filters.is_empty() {
+            return [quote! {}, quote! {}];
+        }
+        let conditions = self
+            .filters
+            .iter()
+            .map(|(rs_name, aggregate_filter, _, condition)| {
+                if aggregate_filter.is_in() {
+                    // the values are split, with a placeholder for each of them, see `InFilter`
+                    let column = condition.strip_suffix(" IN (?)").unwrap_or(condition);
+                    return quote! {
+                        let #rs_name = ::core::option::Option::map(
+                            ::core::option::Option::as_deref(&request.#rs_name),
+                            ::laraxum::model::filter::in_values,
+                        );
+                        let #rs_name = ::core::option::Option::transpose(#rs_name)?;
+                        if let ::core::option::Option::Some(values) = &#rs_name {
+                            ::std::vec::Vec::push(
+                                &mut conditions,
+                                ::std::borrow::Cow::Owned(
+                                    ::laraxum::model::filter::in_condition(#column, values.len()),
+                                ),
+                            );
+                        }
+                    };
+                }
+                quote! {
+                    if ::core::option::Option::is_some(&request.#rs_name) {
+                        ::std::vec::Vec::push(
+                            &mut conditions,
+                            ::std::borrow::Cow::Borrowed(#condition),
+                        );
+                    }
+                }
+            });
+        let where_sql = quote! {
+            let mut conditions: ::std::vec::Vec<::std::borrow::Cow<'static, str>> =
+                ::std::vec::Vec::new();
+            #( #conditions )*
+            if !conditions.is_empty() {
+                sql.push_str(" WHERE ");
+                sql.push_str(&conditions.join(" AND "));
+            }
+        };
+        let binds = self
+            .filters
+            .iter()
+            .filter_map(|(rs_name, aggregate_filter, _, _)| {
+                if aggregate_filter.is_in() {
+                    return Some(quote! {
+                        let query = if let ::core::option::Option::Some(values) = #rs_name {
+                            ::core::iter::Iterator::fold(
+                                ::core::iter::IntoIterator::into_iter(values),
+                                query,
+                                |query, value| query.bind(value),
+                            )
+                        } else {
+                            query
+                        };
+                    });
+                }
+                let args = filter_args(*aggregate_filter, &rs_name.to_token_stream());
+                (!args.is_empty()).then(|| {
+                    let setters = args.iter().map(|arg| request_setter(arg, false));
+                    quote! {
+                        let query =
+                            if let ::core::option::Option::Some(#rs_name) = request.#rs_name {
+                                query #( .bind(#setters) )*
+                            } else {
+                                query
+                            };
+                    }
+                })
+            });
+        [where_sql, quote! { #( #binds )* }]
+    }
+
+    /// Append the `ORDER BY` to `sql` and bind the value of the relevance to `query`.
+    ///
+    /// The columns in the request are sorted by, else the first search by relevance.
+    fn order_by(&self, sort_rs_name: &Ident) -> [proc_macro2::TokenStream; 2] {
+        let is_sorted = (!self.sorts.is_empty()).then(|| {
+            let sorts = self.sorts.iter().map(|(rs_name, asc, desc)| {
+                quote! {
+                    (#sort_rs_name::#rs_name, ::laraxum::model::Sort::Ascending) => #asc,
+                    (#sort_rs_name::#rs_name, ::laraxum::model::Sort::Descending) => #desc,
+                }
+            });
+            quote! {
+                let is_sorted = match &request.sort {
+                    ::core::option::Option::Some(sort) if !sort.0.is_empty() => {
+                        let sorts = ::core::iter::Iterator::map(sort.0.iter(), |(column, sort)| {
+                            match (column, sort) {
+                                #( #sorts )*
+                            }
+                        });
+                        let sorts: ::std::vec::Vec<&str> = ::core::iter::Iterator::collect(sorts);
+                        sql.push_str(" ORDER BY ");
+                        sql.push_str(&sorts.join(","));
+                        true
+                    }
+                    _ => false,
+                };
+            }
+        });
+        if self.searches.is_empty() {
+            return [quote! { #is_sorted }, quote! {}];
+        }
+        // the value of the search is bound again for its relevance
+        let relevances = self.searches.iter().map(|(rs_name, relevance)| {
+            let order_by = fmt2::fmt! { { str } => " ORDER BY " {relevance} " DESC" };
+            quote! {
+                if let ::core::option::Option::Some(value) = &request.#rs_name {
+                    sql.push_str(#order_by);
+                    ::core::option::Option::Some(::core::clone::Clone::clone(value))
+                }
+            }
+        });
+        let if_is_sorted = is_sorted
+            .as_ref()
+            .map(|_| quote! { if is_sorted { ::core::option::Option::None } else });
+        let order_by_sql = quote! {
+            #is_sorted
+            let relevance: ::core::option::Option<::std::string::String> =
+                #if_is_sorted #( #relevances else )* { ::core::option::Option::None };
+        };
+        let bind = quote! {
+            let query = if let ::core::option::Option::Some(relevance) = relevance {
+                query.bind(relevance)
+            } else {
+                query
+            };
+        };
+        [order_by_sql, bind]
+    }
+
+    /// Append the `LIMIT` to `sql` and bind its values to `query`.
+    ///
+    /// The page is used instead of the limit if there are both.
+    fn limit(&self) -> [proc_macro2::TokenStream; 2] {
+        let page = self.page.map(|(per_page, max_per_page, _)| {
+            let limit_sql = if max_per_page.is_some() {
+                String::from(" LIMIT ? OFFSET ?")
+            } else {
+                fmt2::fmt! { { str } => " LIMIT " {per_page} " OFFSET ?" }
+            };
+            let per_page_bind = max_per_page.map(|_| quote! { .bind(per_page) });
+            let per_page = self.per_page();
+            (
+                quote! { page },
+                limit_sql,
+                quote! { query #per_page_bind .bind(page * per_page) },
+                quote! { let per_page: u64 = #per_page; },
+            )
+        });
+        let limit = self.limit.then(|| {
+            (
+                quote! { limit },
+                String::from(" LIMIT ?"),
+                quote! { query.bind(limit) },
+                quote! {},
+            )
+        });
+        let limits = page.into_iter().chain(limit).collect::<Vec<_>>();
+        if limits.is_empty() {
+            return [quote! {}, quote! {}];
+        }
+        let limit_sql = limits.iter().map(|(rs_name, limit_sql, _, _)| {
+            quote! {
+                if ::core::option::Option::is_some(&request.#rs_name) {
+                    sql.push_str(#limit_sql);
+                }
+            }
+        });
+        let binds = limits.iter().map(|(rs_name, _, bind, per_page)| {
+            quote! {
+                if let ::core::option::Option::Some(#rs_name) = request.#rs_name {
+                    #per_page
+                    #bind
+                }
+            }
+        });
+        [
+            quote! { #( #limit_sql )else* },
+            quote! {
+                let query = #( #binds )else* else {
+                    query
+                };
+            },
+        ]
+    }
+    /// The pagination of the page in the request, with the total of the records,
+    /// which are counted with the same conditions.
+    fn pagination(
+        &self,
+        table: &stage3::Table,
+        [where_sql, filter_binds]: [&proc_macro2::TokenStream; 2],
+    ) -> Option<proc_macro2::TokenStream> {
+        let (_, _, is_envelope) = self.page?;
+        let per_page = self.per_page();
+        let count = fmt2::fmt! { { str } =>
+            "SELECT COUNT(*) FROM " {table.name_intern} " AS " {table.name_extern}
+        };
+        let sql_mut = (!where_sql.is_empty()).then(|| quote! { mut });
+        let count_run = run_query(
+            &quote! { query.fetch_one(<Self::Db>::read_pool(db)) },
+            quote! { &*sql },
+            &table.name_intern,
+        );
+        Some(quote! {
+            let pagination = match request.page {
+                ::core::option::Option::Some(page) => {
+                    let per_page: u64 = #per_page;
+                    let total = {
+                        let request = ::core::clone::Clone::clone(&request);
+                        let #sql_mut sql = ::std::string::String::from(#count);
+                        #where_sql
+                        let query = ::sqlx::query_scalar::<_, i64>(&sql);
+                        #filter_binds
+                        let total: i64 = #count_run?;
+                        <u64 as ::core::convert::TryFrom<i64>>::try_from(total).unwrap_or_default()
+                    };
+                    ::core::option::Option::Some(::laraxum::model::page::Pagination {
+                        page,
+                        per_page,
+                        total,
+                        is_envelope: #is_envelope,
+                        query: ::core::option::Option::None,
+                    })
+                }
+                ::core::option::Option::None => ::core::option::Option::None,
+            };
+        })
+    }
+}
+
+/// Get the records with any combination of the public aggregators of a table.
+///
+/// The conditions are joined with `AND`, so the SQL is built at runtime
+/// and the rows are always decoded at runtime.
+fn filter_aggregate(
+    table: &stage3::Table,
+    filter_rs_name: &Ident,
+    batch: Option<&proc_macro2::TokenStream>,
+    select_fields: &SelectFields,
+) -> proc_macro2::TokenStream {
+    let table_rs_name = table.rs_name;
+    let table_name_intern = &*table.name_intern;
+    let response_getters = table.columns.iter().map(stage3::ColumnRef::response_getter);
+    let response_getter = response_getter_compound(
+        table.rs_name,
+        response_getters.clone(),
+        false,
+        false,
+        true,
+        None,
+    );
+    let response_getter = response_getter_fn(&response_getter);
+    let (response_getter_elements, response_getter_compounds) = flatten(response_getters, false);
+    let get_all = get_all(
+        &table.name_intern,
+        &table.name_extern,
+        (&response_getter_elements, &response_getter_compounds),
+    );
+
+    let filter = Filter::new(table);
+    let sort_rs_name = quote::format_ident!("{}Sort", filter_rs_name);
+    let request = filter.request(filter_rs_name, &sort_rs_name);
+    let [where_sql, filter_binds] = filter.conditions();
+    let [order_by_sql, relevance_bind] = filter.order_by(&sort_rs_name);
+    let [limit_sql, limit_binds] = filter.limit();
+
+    let response = transform_response_many(
+        &quote! { query },
+        (quote! { &*sql }, table_name_intern),
+        &response_getter,
+        batch,
+    );
+    let response_fields = select_fields.response(&quote! { query }, false);
+    let select_fields = select_fields.select("");
+    let sql_mut = (!where_sql.is_empty() || !order_by_sql.is_empty() || !limit_sql.is_empty())
+        .then(|| quote! { mut });
+    let aggregate_many = |select: &dyn quote::ToTokens, response: &proc_macro2::TokenStream| {
+        quote! {
+            let #sql_mut sql: ::std::string::String = #select;
+            #where_sql
+            #order_by_sql
+            #limit_sql
+            let query = ::sqlx::query(&sql);
+            #filter_binds
+            #relevance_bind
+            #limit_binds
+            #response
+        }
+    };
+    let aggregate_many_fields = aggregate_many(&select_fields, &response_fields);
+    let aggregate_many =
+        aggregate_many(&quote! { ::std::string::String::from(#get_all) }, &response);
+
+    let pagination = filter.pagination(table, [&where_sql, &filter_binds]);
+    let aggregate_page_fields = filter_aggregate_page(filter_rs_name, pagination.as_ref(), true);
+    let aggregate_page = filter_aggregate_page(filter_rs_name, pagination.as_ref(), false);
+
+    quote! {
+        #request
+
+        impl ::laraxum::AggregateMany<#filter_rs_name> for #table_rs_name {
+            type OneRequest<'b> = #filter_rs_name;
+            type ManyResponse = Self;
+            async fn aggregate_many<'a>(
+                db: &Self::Db,
+                request: Self::OneRequest<'a>,
+            )
+                -> ::core::result::Result<
+                    ::std::vec::Vec<Self::ManyResponse>,
+                    ::laraxum::Error,
+                >
+            {
+                #aggregate_many
+            }
+            async fn aggregate_many_fields(
+                db: &Self::Db,
+                request: Self::OneRequest<'_>,
+                fields: &[&str],
+            )
+                -> ::core::result::Result<
+                    ::std::vec::Vec<Self::ManyResponse>,
+                    ::laraxum::Error,
+                >
+            {
+                #aggregate_many_fields
+            }
+            #aggregate_page
+            #aggregate_page_fields
+        }
+    }
+}
+
+/// Get a page of the records of a filter, see `filter_aggregate`.
+fn filter_aggregate_page(
+    filter_rs_name: &Ident,
+    pagination: Option<&proc_macro2::TokenStream>,
+    is_fields: bool,
+) -> proc_macro2::TokenStream {
+    let (aggregate_page, fields, aggregate_many) = if is_fields {
+        (
+            quote! { aggregate_page_fields },
+            quote! { fields: &[&str], },
+            quote! { aggregate_many_fields(db, request, fields) },
+        )
+    } else {
+        (
+            quote! { aggregate_page },
+            quote! {},
+            quote! { aggregate_many(db, request) },
+        )
+    };
+    let pagination_field = if pagination.is_some() {
+        quote! { pagination }
+    } else {
+        quote! { pagination: ::core::option::Option::None }
+    };
+    quote! {
+        async fn #aggregate_page(
+            db: &Self::Db,
+            request: Self::OneRequest<'_>,
+            #fields
+        )
+            -> ::core::result::Result<
+                ::laraxum::model::Page<Self::ManyResponse>,
+                ::laraxum::Error,
+            >
+        {
+            #pagination
+            let data = <Self as ::laraxum::AggregateMany<#filter_rs_name>>::#aggregate_many.await?;
+            ::core::result::Result::Ok(::laraxum::model::Page {
+                data,
+                next_cursor: ::core::option::Option::None,
+                #pagination_field,
+            })
+        }
+    }
+}
+
+/// If the records of a table can be imported, see `::laraxum::Import`.
+///
+/// The rows of a CSV are flat, so the create request can't have nested records,
+/// embedded structs or relations.
+fn is_import(table: &stage3::Table) -> bool {
+    table.columns.iter().all(|column| {
+        column.request_setter_collection().is_none()
+            && column
+                .request_setter_molecule()
+                .is_none_or(|setter| setter.nested.is_none() && setter.embed.is_none())
+    })
+}
+/// If any column of a table has statistics.
+fn is_stats(table: &stage3::Table) -> bool {
+    table.columns.iter().any(|column| match column {
+        stage3::ColumnRef::Molecule(molecule) => molecule.is_stat,
+        stage3::ColumnRef::Collection(_) => false,
+    })
+}
+/// Cast a number to a float, like a sum or an average, which is decoded as `f64`.
+fn cast_float(expression: &str) -> String {
+    #[cfg(feature = "mysql")]
+    {
+        fmt2::fmt! { { str } => "CAST(" {expression} " AS DOUBLE)" }
+    }
+    #[cfg(feature = "postgres")]
+    {
+        fmt2::fmt! { { str } => "CAST(" {expression} " AS DOUBLE PRECISION)" }
+    }
+    #[cfg(feature = "sqlite")]
+    {
+        fmt2::fmt! { { str } => "CAST(" {expression} " AS REAL)" }
+    }
+    #[cfg(not(any(feature = "mysql", feature = "sqlite", feature = "postgres")))]
+    unimplemented!();
+}
+/// The alias of the count of the records in the statistics,
+/// which can't be the name of another statistic.
+const STATS_COUNT: &str = "__laraxum_count";
+
+/// A statistic of a column, like the sum of `total` in `sum_total`.
+struct Stat {
+    /// the name of the field in the response and in the SQL, like `sum_total`
+    name: String,
+    /// the parameter of the request, like `sum`
+    parameter: Ident,
+    /// the variant of the column in the request, like `OrderStatsNumber::total`
+    column: proc_macro2::TokenStream,
+    /// the selected value, like `CAST(SUM(orders.total) AS DOUBLE)`
+    select: String,
+    /// the column is grouped by instead of aggregated
+    group_by: Option<String>,
+    rs_ty: proc_macro2::TokenStream,
+    decode_ty: proc_macro2::TokenStream,
+}
+impl Stat {
+    /// The statistics of a column, which can be summed and averaged if it's a number.
+    fn of_column(
+        (rs_name, element, is_number): StatColumn<'_>,
+        column_rs_name: &Ident,
+        number_rs_name: &Ident,
+    ) -> Vec<Self> {
+        let name = rs_name.unraw().to_string();
+        let name_intern = &*element.name_intern;
+        let rs_ty = element.rs_ty;
+        let mut kinds = vec![
+            ("group_by", "", String::from(name_intern)),
+            (
+                "min",
+                "min_",
+                fmt2::fmt! { { str } => "MIN(" {name_intern} ")" },
+            ),
+            (
+                "max",
+                "max_",
+                fmt2::fmt! { { str } => "MAX(" {name_intern} ")" },
+            ),
+        ];
+        if is_number {
+            kinds.extend([
+                (
+                    "sum",
+                    "sum_",
+                    cast_float(&fmt2::fmt! { { str } => "SUM(" {name_intern} ")" }),
+                ),
+                (
+                    "avg",
+                    "avg_",
+                    cast_float(&fmt2::fmt! { { str } => "AVG(" {name_intern} ")" }),
+                ),
+            ]);
+        }
+        kinds
+            .into_iter()
+            .map(|(parameter, prefix, select)| {
+                let stat_name = fmt2::fmt! { { str } => {prefix} {name} };
+                let is_float = parameter == "sum" || parameter == "avg";
+                let (enum_rs_name, rs_ty, decode_ty) = if is_float {
+                    (number_rs_name, quote! { f64 }, quote! { f64 })
+                } else {
+                    (
+                        column_rs_name,
+                        quote! { #rs_ty },
+                        quote! { <#rs_ty as ::laraxum::model::types::Decode>::Decode },
+                    )
+                };
+                Self {
+                    select: fmt2::fmt! { { str } => {select} " AS " {stat_name} },
+                    name: stat_name,
+                    parameter: from_str_to_rs_ident(parameter),
+                    column: quote! { #enum_rs_name::#rs_name },
+                    group_by: (parameter == "group_by").then(|| String::from(name_intern)),
+                    rs_ty,
+                    decode_ty,
+                }
+            })
+            .collect()
+    }
+
+    fn rs_name(&self) -> Ident {
+        from_str_to_rs_ident(&self.name)
+    }
+    /// The field of the statistic in the response.
+    fn field(&self) -> proc_macro2::TokenStream {
+        let rs_name = self.rs_name();
+        let rs_ty = &self.rs_ty;
+        quote! {
+            #[serde(skip_serializing_if = "::core::option::Option::is_none")]
+            pub #rs_name: ::core::option::Option<#rs_ty>,
+        }
+    }
+    /// If the statistic is in the request.
+    fn is_requested(&self) -> proc_macro2::TokenStream {
+        let rs_name = self.rs_name();
+        let parameter = &self.parameter;
+        let column = &self.column;
+        quote! {
+            let #rs_name = ::core::option::Option::is_some_and(
+                ::core::option::Option::as_ref(&request.#parameter),
+                |columns| columns.0.contains(&#column),
+            );
+        }
+    }
+    /// Select the statistic and group by it, if it's requested.
+    fn sql(&self) -> proc_macro2::TokenStream {
+        let rs_name = self.rs_name();
+        let select = &self.select;
+        let group_by = self.group_by.as_ref().map(|group_by| {
+            quote! {
+                ::std::vec::Vec::push(&mut group_by, #group_by);
+            }
+        });
+        quote! {
+            if #rs_name {
+                ::std::vec::Vec::push(&mut select, #select);
+                #group_by
+            }
+        }
+    }
+    /// The value of the statistic in a row, if it's requested.
+    fn value(&self) -> proc_macro2::TokenStream {
+        let rs_name = self.rs_name();
+        let name = &self.name;
+        let decode_ty = &self.decode_ty;
+        quote! {
+            #rs_name: if #rs_name {
+                ::core::option::Option::map(
+                    ::sqlx::Row::try_get::<::core::option::Option<#decode_ty>, _>(
+                        &response,
+                        #name,
+                    )?,
+                    ::laraxum::model::types::Decode::decode,
+                )
+            } else {
+                ::core::option::Option::None
+            },
+        }
+    }
+}
+
+/// A column with statistics, and if it's a number.
+type StatColumn<'a> = (&'a Ident, &'a stage3::ResponseColumnGetterElement<'a>, bool);
+/// The columns of a table with statistics.
+fn stats_columns<'a>(table: &'a stage3::Table<'a>) -> Vec<StatColumn<'a>> {
+    table
+        .columns
+        .iter()
+        .filter_map(|column| match column {
+            stage3::ColumnRef::Molecule(molecule) => Some(molecule),
+            stage3::ColumnRef::Collection(_) => None,
+        })
+        .filter(|column| column.is_stat)
+        .filter_map(
+            |column| match (&column.response.getter, &column.create.ty) {
+                (
+                    stage3::ResponseColumnGetterMolecule::Element(element),
+                    stage3::TyMolecule::Element(ty),
+                ) => Some((column.response.field.rs_name, element, ty.is_number())),
+                _ => None,
+            },
+        )
+        .collect()
+}
+/// The request of the statistics, with the columns that can be chosen for each statistic.
+fn stats_request(
+    columns: &[StatColumn<'_>],
+    [request_rs_name, column_rs_name, number_rs_name]: [&Ident; 3],
+) -> proc_macro2::TokenStream {
+    let variant = |rs_name: &Ident| {
+        let name = rs_name.unraw().to_string();
+        quote! {
+            #[serde(rename = #name)]
+            #rs_name
+        }
+    };
+    let column_variants = columns.iter().map(|(rs_name, _, _)| variant(rs_name));
+    let number_variants = columns
+        .iter()
+        .filter(|(_, _, is_number)| *is_number)
+        .map(|(rs_name, _, _)| variant(rs_name));
+    let is_number = columns.iter().any(|(_, _, is_number)| *is_number);
+    // only numbers can be summed and averaged
+    let number_token_stream = is_number.then(|| {
+        quote! {
+            #[derive(
+                ::core::clone::Clone,
+                ::core::marker::Copy,
+                ::core::cmp::PartialEq,
+                ::serde::Deserialize,
+            )]
+            #[allow(non_camel_case_types)]
+            pub enum #number_rs_name {
+                #( #number_variants, )*
+            }
+        }
+    });
+    let number_fields = is_number.then(|| {
+        quote! {
+            pub sum: ::core::option::Option<::laraxum::model::stats::Columns<#number_rs_name>>,
+            pub avg: ::core::option::Option<::laraxum::model::stats::Columns<#number_rs_name>>,
+        }
+    });
+    quote! {
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::cmp::PartialEq,
+            ::serde::Deserialize,
+        )]
+        #[allow(non_camel_case_types)]
+        pub enum #column_rs_name {
+            #( #column_variants, )*
+        }
+        #number_token_stream
+        #[derive(::serde::Deserialize)]
+        pub struct #request_rs_name {
+            pub group_by: ::core::option::Option<::laraxum::model::stats::Columns<#column_rs_name>>,
+            pub min: ::core::option::Option<::laraxum::model::stats::Columns<#column_rs_name>>,
+            pub max: ::core::option::Option<::laraxum::model::stats::Columns<#column_rs_name>>,
+            #number_fields
+        }
+    }
+}
+/// Statistics of the records of a table, like `?group_by=status&sum=total&avg=total`.
+///
+/// The columns are chosen at runtime, so the SQL is built at runtime, like `filter_aggregate`.
+fn stats(table: &stage3::Table, stats_rs_name: &Ident) -> proc_macro2::TokenStream {
+    let table_rs_name = table.rs_name;
+    let column_rs_name = quote::format_ident!("{}Column", stats_rs_name);
+    let number_rs_name = quote::format_ident!("{}Number", stats_rs_name);
+    let request_rs_name = quote::format_ident!("{}Request", stats_rs_name);
+
+    let columns = stats_columns(table);
+    let request = stats_request(
+        &columns,
+        [&request_rs_name, &column_rs_name, &number_rs_name],
+    );
+    let stats: Vec<Stat> = columns
+        .iter()
+        .flat_map(|&column| Stat::of_column(column, &column_rs_name, &number_rs_name))
+        .collect();
+    let stats_fields = stats.iter().map(Stat::field);
+    let is_requested = stats.iter().map(Stat::is_requested);
+    let stats_sql = stats.iter().map(Stat::sql);
+    let stats_values = stats.iter().map(Stat::value);
+
+    let select_count = fmt2::fmt! { { str } => "COUNT(*) AS " {STATS_COUNT} };
+    let from_sql = fmt2::fmt! { { str } =>
+        " FROM " {table.name_intern} " AS " {table.name_extern}
+    };
+    let response_run = run_query(
+        &quote! { ::sqlx::query(&sql).fetch_all(<Self::Db>::read_pool(db)) },
+        quote! { &*sql },
+        &table.name_intern,
+    );
+
+    quote! {
+        #request
+        #[derive(::serde::Serialize)]
+        pub struct #stats_rs_name {
+            pub count: u64,
+            #( #stats_fields )*
+        }
+
+        impl ::laraxum::Stats for #table_rs_name {
+            type StatsRequest = #request_rs_name;
+            type StatsResponse = #stats_rs_name;
+            async fn stats(
+                db: &Self::Db,
+                request: Self::StatsRequest,
+            ) -> ::core::result::Result<::std::vec::Vec<Self::StatsResponse>, ::laraxum::Error> {
+                #( #is_requested )*
+                let mut select: ::std::vec::Vec<&str> = ::std::vec![#select_count];
+                let mut group_by: ::std::vec::Vec<&str> = ::std::vec::Vec::new();
+                #( #stats_sql )*
+                let mut sql = ::std::string::String::from("SELECT ");
+                sql.push_str(&select.join(","));
+                sql.push_str(#from_sql);
+                if !group_by.is_empty() {
+                    sql.push_str(" GROUP BY ");
+                    sql.push_str(&group_by.join(","));
+                }
+                let response = #response_run?;
+                let mut stats = ::std::vec::Vec::with_capacity(response.len());
+                for response in response {
+                    let count = ::sqlx::Row::try_get::<i64, _>(&response, #STATS_COUNT)?;
+                    ::std::vec::Vec::push(&mut stats, #stats_rs_name {
+                        count: ::core::result::Result::unwrap_or_default(u64::try_from(count)),
+                        #( #stats_values )*
+                    });
+                }
+                ::core::result::Result::Ok(stats)
+            }
+        }
+    }
+}
+
+fn transform_response_one(
+    response: &proc_macro2::TokenStream,
+    (sql, table_name): (impl quote::ToTokens, &str),
+    response_getter: &proc_macro2::TokenStream,
+    batch: Option<&proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let batch = batch.map(|batch| {
+        quote! {
+            let mut response = response;
+            {
+                let response = ::core::slice::from_mut(&mut response);
+                #batch
+            }
+        }
+    });
+    let response_run = run_query(
+        &quote! { ::futures::TryStreamExt::try_next(&mut response) },
+        sql,
+        table_name,
+    );
+    quote! {{
+        let response = #response;
+        let response = response.fetch(<Self::Db>::read_pool(db));
+        let mut response = ::futures::StreamExt::then(response, #response_getter);
+        let mut response = ::core::pin::pin!(response);
+        let response: ::core::option::Option<_> = #response_run?;
+        let response = ::core::option::Option::ok_or(response, ::laraxum::Error::NotFound)?;
+        #batch
+        ::core::result::Result::Ok(response)
+    }}
+}
+fn transform_response_many(
+    response: &proc_macro2::TokenStream,
+    (sql, table_name): (impl quote::ToTokens, &str),
+    response_getter: &proc_macro2::TokenStream,
+    batch: Option<&proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let batch = batch.map(|batch| {
+        quote! {
+            let mut response = response;
+            #batch
+        }
+    });
+    let response_run = run_query(
+        &quote! { ::futures::TryStreamExt::try_collect(response) },
+        sql,
+        table_name,
+    );
+    quote! {{
+        let response = #response;
+        let response = response.fetch(<Self::Db>::read_pool(db));
+        let response = ::futures::StreamExt::then(response, #response_getter);
+        let response: ::std::vec::Vec<_> = #response_run?;
+        #batch
+        ::core::result::Result::Ok(response)
+    }}
+}
+/// Stream the records as they are read, see `::laraxum::Collection::stream_all`.
+///
+/// The relations are loaded for each chunk of records, instead of a query for each record.
+fn transform_response_stream(
     response: &proc_macro2::TokenStream,
     response_getter: &proc_macro2::TokenStream,
+    batch: Option<&proc_macro2::TokenStream>,
 ) -> proc_macro2::TokenStream {
+    /// How many records are in a chunk.
+    const CHUNK: usize = 256;
+
+    let batch = batch.map(|batch| {
+        quote! {
+            let response = ::futures::TryStreamExt::try_chunks(response, #CHUNK);
+            let response = ::futures::StreamExt::then(response, move |response| async move {
+                let mut response = response.map_err(|err| err.1)?;
+                #batch
+                let response = ::core::iter::Iterator::map(
+                    ::core::iter::IntoIterator::into_iter(response),
+                    ::core::result::Result::Ok::<_, ::laraxum::Error>,
+                );
+                ::core::result::Result::Ok::<_, ::laraxum::Error>(::futures::stream::iter(response))
+            });
+            let response = ::futures::TryStreamExt::try_flatten(response);
+        }
+    });
     quote! {{
         let response = #response;
-        let response = response.fetch(&db.pool);
-        let mut response = ::futures::StreamExt::then(response, #response_getter);
-        let mut response = ::core::pin::pin!(response);
-        let response: ::core::option::Option<_> =
-            ::futures::TryStreamExt::try_next(&mut response).await?;
-        ::core::option::Option::ok_or(response, ::laraxum::Error::NotFound)
+        let response = response.fetch(<Self::Db>::read_pool(db));
+        let response = ::futures::StreamExt::then(response, #response_getter);
+        let response = ::futures::TryStreamExt::map_err(response, ::laraxum::Error::from);
+        #batch
+        response
     }}
 }
-fn transform_response_many(
-    response: &proc_macro2::TokenStream,
+/// Get the records of a table for multiple ones, see `transform_response_batch`.
+fn table_batch(table: &stage3::Table, one_name_intern: &str) -> String {
+    let (response_getter_elements, response_getter_compounds) = flatten(
+        table.columns.iter().map(stage3::ColumnRef::response_getter),
+        false,
+    );
+    get_many_batch(
+        &table.name_intern,
+        &table.name_extern,
+        (&response_getter_elements, &response_getter_compounds),
+        one_name_intern,
+    )
+}
+/// Get the `(one, record)` rows of `table_batch`.
+///
+/// The ones are bound at runtime, so the rows are always decoded at runtime.
+fn table_batch_response_getter(
+    table: &stage3::Table,
+    one_rs_ty: &syn::Type,
+) -> proc_macro2::TokenStream {
+    let one = response_field_access(BATCH_ONE_NAME, one_rs_ty, false, false);
+    let response = response_getter_compound(
+        table.rs_name,
+        table.columns.iter().map(stage3::ColumnRef::response_getter),
+        false,
+        false,
+        true,
+        None,
+    );
+    response_getter_fn(&quote! { (#one, #response) })
+}
+/// Bind the ones, then group the `(one, many)` rows by one, in the same order as the ones.
+fn transform_response_batch(
+    ones: &proc_macro2::TokenStream,
+    (sql, table_name): (&str, &str),
     response_getter: &proc_macro2::TokenStream,
+    batch: Option<&proc_macro2::TokenStream>,
 ) -> proc_macro2::TokenStream {
+    let batch = batch.map(|batch| {
+        quote! {
+            let (ones_response, response): (::std::vec::Vec<_>, ::std::vec::Vec<_>) =
+                ::core::iter::Iterator::unzip(::core::iter::IntoIterator::into_iter(response));
+            let mut response = response;
+            #batch
+            let response = ::core::iter::Iterator::zip(
+                ::core::iter::IntoIterator::into_iter(ones_response),
+                response,
+            );
+        }
+    });
+    let response_run = run_query(
+        &quote! { ::futures::TryStreamExt::try_collect(chunk) },
+        sql,
+        table_name,
+    );
+    // the IDs are bound in chunks, so a query never has too many parameters
     quote! {{
-        let response = #response;
-        let response = response.fetch(&db.pool);
-        let response = ::futures::StreamExt::then(response, #response_getter);
-        let response: ::std::vec::Vec<_> =
-            ::futures::TryStreamExt::try_collect(response).await?;
-        ::core::result::Result::Ok(response)
+        /// How many IDs are bound in one query.
+        const BATCH: usize = 500;
+
+        let mut response = ::std::vec::Vec::new();
+        for ones in #ones.chunks(BATCH) {
+            let mut chunk = ::sqlx::QueryBuilder::new(#sql);
+            let mut separated = chunk.separated(",");
+            for one in ones {
+                separated.push_bind(::laraxum::model::types::Encode::encode(*one));
+            }
+            separated.push_unseparated(")");
+            let chunk = chunk.build();
+            let chunk = chunk.fetch(<Self::Db>::read_pool(db));
+            let chunk = ::futures::StreamExt::then(chunk, #response_getter);
+            let chunk: ::std::vec::Vec<_> = #response_run?;
+            response.extend(chunk);
+        }
+        #batch
+        let mut many = ::std::collections::HashMap::<_, ::std::vec::Vec<_>>::new();
+        for (one, response) in response {
+            many.entry(one).or_default().push(response);
+        }
+        let many = #ones.iter().map(|one| {
+            let one = ::laraxum::model::types::Encode::encode(*one);
+            many.remove(&one).unwrap_or_default()
+        });
+        ::core::result::Result::<_, ::laraxum::Error>::Ok(::core::iter::Iterator::collect(many))
     }}
 }
 fn transform_response(
     response: &proc_macro2::TokenStream,
+    sql_table_name: (impl quote::ToTokens, &str),
     response_getter: &proc_macro2::TokenStream,
+    batch: Option<&proc_macro2::TokenStream>,
     is_one: bool,
 ) -> proc_macro2::TokenStream {
     if is_one {
-        transform_response_one(response, response_getter)
+        transform_response_one(response, sql_table_name, response_getter, batch)
     } else {
-        transform_response_many(response, response_getter)
+        transform_response_many(response, sql_table_name, response_getter, batch)
     }
 }
 
@@ -780,14 +3454,21 @@ fn impl_deserialize_for_untagged_enum<'a, 'b>(
     enum_variants: impl Iterator<
         Item = (
             &'a Ident,
-            impl Iterator<Item = (&'a Ident, &'a Type)> + Clone,
+            impl Iterator<Item = (&'a Ident, &'a Type, bool)> + Clone,
         ),
     > + Clone,
     enum_default_variant: Option<&'b Ident>,
 ) -> proc_macro2::TokenStream {
-    fn field_matcher(ident: &Ident) -> proc_macro2::TokenStream {
-        quote! {
-            #ident: ::core::option::Option::Some(#ident)
+    fn field_matcher((ident, _, is_optional): (&Ident, &Type, bool)) -> proc_macro2::TokenStream {
+        // an optional field matches whether it's there or not
+        if is_optional {
+            quote! {
+                #ident
+            }
+        } else {
+            quote! {
+                #ident: ::core::option::Option::Some(#ident)
+            }
         }
     }
     fn forbidden_field_matcher(ident: &Ident) -> proc_macro2::TokenStream {
@@ -797,7 +3478,7 @@ fn impl_deserialize_for_untagged_enum<'a, 'b>(
     }
     let struct_fields_iter = enum_variants.clone().flat_map(|(_, fields)| fields);
     let mut struct_fields: Vec<(&Ident, &Type)> = vec![];
-    for (ident, ty) in struct_fields_iter {
+    for (ident, ty, _) in struct_fields_iter {
         let other_field = struct_fields
             .iter()
             .find(|&&(other_ident, _)| other_ident == ident);
@@ -818,7 +3499,7 @@ fn impl_deserialize_for_untagged_enum<'a, 'b>(
         }
     });
     let matchers = enum_variants.map(|(enum_variant_ident, enum_fields)| {
-        let enum_field_idents = enum_fields.clone().map(|(ident, _)| ident);
+        let enum_field_idents = enum_fields.clone().map(|(ident, _, _)| ident);
         let forbidden_field_matchers = struct_fields
             .iter()
             .filter(|&&(struct_field_ident, _)| {
@@ -827,7 +3508,7 @@ fn impl_deserialize_for_untagged_enum<'a, 'b>(
                     .all(|enum_field_ident| enum_field_ident != struct_field_ident)
             })
             .map(|&(field_ident, _)| forbidden_field_matcher(field_ident));
-        let field_matchers = enum_field_idents.clone().map(field_matcher);
+        let field_matchers = enum_fields.map(field_matcher);
         let field_setters = enum_field_idents.clone();
         quote! {
             __Struct {
@@ -876,6 +3557,24 @@ fn impl_deserialize_for_untagged_enum<'a, 'b>(
     }
 }
 
+fn response_field(field: &stage3::ResponseColumnField) -> proc_macro2::TokenStream {
+    let &stage3::ResponseColumnField {
+        rs_name,
+        rs_ty,
+        attr,
+        rs_attrs,
+        embed: _,
+    } = field;
+
+    let serde_skip = attr.skip.then(serde_skip_rs_attr);
+    let serde_name = attr.name.as_deref().map(serde_rename_rs_attr);
+
+    quote! {
+        #( #rs_attrs )* #serde_skip #serde_name
+        pub #rs_name: #rs_ty
+    }
+}
+
 struct Table {
     token_stream: proc_macro2::TokenStream,
     migration_up: String,
@@ -885,24 +3584,29 @@ impl From<stage3::Table<'_>> for Table {
     #[allow(clippy::too_many_lines)]
     fn from(table: stage3::Table) -> Self {
         let response_fields = table.columns.iter().map(|column| {
-            let &stage3::ResponseColumnField {
+            let field = column.response_field();
+            (field.embed, response_field(field))
+        });
+        let response_fields = embed_fields(response_fields, |embed| {
+            let stage3::ColumnEmbed {
                 rs_name,
-                rs_ty,
-                attr,
-                rs_attrs,
-            } = column.response_field();
-
-            let serde_skip = attr.skip.then(serde_skip_rs_attr);
-            let serde_name = attr.name.as_deref().map(serde_rename_rs_attr);
-
-            quote! {
-                #( #rs_attrs )* #serde_skip #serde_name
-                pub #rs_name: #rs_ty
-            }
+                rs_ty_name,
+                ..
+            } = embed;
+            quote! { pub #rs_name: #rs_ty_name }
         });
 
-        let create_table = create_table(&table.name_intern, table.columns.iter());
+        let create_table = create_table(&table);
+        // the triggers are dropped with the table, but not the FTS5 tables
+        #[cfg(feature = "sqlite")]
+        let delete_search_tables = fmt2::fmt! { { str } =>
+            @..(searches(&table) => |search_columns|
+                {delete_table(&search_name(&table.name_intern, &search_columns))}
+            )
+        };
         let delete_table = delete_table(&table.name_intern);
+        #[cfg(feature = "sqlite")]
+        let delete_table = delete_search_tables + &delete_table;
 
         let table_rs_name = table.rs_name;
         let create_request_rs_name = &*table.create_request_rs_name;
@@ -947,62 +3651,281 @@ impl From<stage3::Table<'_>> for Table {
 
         // molecule vs collection
         let collection_model_token_stream = table.columns.is_collection().then(|| {
-            let response_getters = table.columns.iter().map(|column| column.response_getter());
-            let response_getter =
-                &response_getter_compound(table.rs_name, response_getters.clone(), false);
+            let response_getters = table.columns.iter().map(stage3::ColumnRef::response_getter);
+            let response_getter = &response_getter_compound(
+                table.rs_name,
+                response_getters.clone(),
+                false,
+                table.is_checked,
+                true,
+                None,
+            );
             let response_getter = response_getter_fn(response_getter);
             let response_getter = &response_getter;
+            let table_id_rs_name = table
+                .columns
+                .model()
+                .map(|table_id| table_id.response.field.rs_name);
+            let batch = batch_many(
+                table_id_rs_name,
+                table.columns.iter().map(stage3::ColumnRef::response_getter),
+                None,
+            );
+            let batch = batch.as_ref();
+            // the children in the same table aren't loaded for children, see `Tree::get_subtree`
+            let children_batch = {
+                let columns = table
+                    .columns
+                    .iter()
+                    .map(stage3::ColumnRef::response_getter)
+                    .filter(|column| {
+                        !matches!(
+                            *column,
+                            stage3::ResponseColumnGetterRef::Collection(
+                                &stage3::ResponseColumnGetterCollection {
+                                    is_children: true,
+                                    many_foreign_table_rs_name,
+                                    ..
+                                },
+                            ) if many_foreign_table_rs_name == table.rs_name
+                        )
+                    });
+                batch_many(table_id_rs_name, columns, None)
+            };
+            let children_batch = children_batch.as_ref();
 
-            let (response_getter_elements, response_getter_compounds) = flatten(response_getters);
+            let (response_getter_elements, response_getter_compounds) =
+                flatten(response_getters, table.is_checked);
             let response_getters = (&*response_getter_elements, &*response_getter_compounds);
+            // the queries with an `in` filter are built at runtime, so they are never checked,
+            // see `InFilter`
+            let unchecked_response_getter = response_getter_compound(
+                table.rs_name,
+                table.columns.iter().map(stage3::ColumnRef::response_getter),
+                false,
+                false,
+                true,
+                None,
+            );
+            let unchecked_response_getter = &response_getter_fn(&unchecked_response_getter);
+            let (unchecked_response_getter_elements, unchecked_response_getter_compounds) = flatten(
+                table.columns.iter().map(stage3::ColumnRef::response_getter),
+                false,
+            );
+            let unchecked_response_getters = (
+                &*unchecked_response_getter_elements,
+                &*unchecked_response_getter_compounds,
+            );
+
+            // the ID is always selected, because the relations are loaded by it
+            let table_id_name_intern = table
+                .columns
+                .model()
+                .map(|table_id| table_id.response.getter.name_intern());
+            let select_fields = SelectFields::new(&table, table_id_name_intern.as_slice());
+            let select_fields = &select_fields;
 
             let get_all = get_all(&table.name_intern, &table.name_extern, response_getters);
+            let get_all_fields = select_fields.transform_response(
+                &get_all,
+                core::iter::empty::<Ident>(),
+                false,
+                None,
+            );
+            let stream_all = transform_response_stream(
+                &query(&get_all, core::iter::empty::<Ident>(), table.is_checked),
+                response_getter,
+                batch,
+            );
             let get_all = transform_response_many(
-                &quote! {
-                    ::sqlx::query!(#get_all)
-                },
+                &query(&get_all, core::iter::empty::<Ident>(), table.is_checked),
+                (&get_all, &table.name_intern),
                 response_getter,
+                batch,
             );
 
             let create_columns = table.columns.iter();
 
-            let create_request_fields = create_columns
-                .clone()
-                .filter_map(|column| column.request_field())
-                .map(|field| {
-                    request_field(
+            let create_request_fields = create_columns.clone().filter_map(|column| {
+                let field = column.request_field()?;
+                let nested = column
+                    .request_setter_molecule()
+                    .and_then(|setter| Some((setter.nested?, setter.is_optional)));
+                let embed = field.embed;
+                let field = match nested {
+                    Some((foreign_table_rs_name, is_optional)) => {
+                        let rs_ty = quote! {
+                            ::laraxum::model::Nested<
+                                <#foreign_table_rs_name as ::laraxum::Model>::Id,
+                                <#foreign_table_rs_name as ::laraxum::Collection>::CreateRequest,
+                            >
+                        };
+                        let rs_ty = if is_optional {
+                            quote! { ::core::option::Option<#rs_ty> }
+                        } else {
+                            rs_ty
+                        };
+                        request_field(
+                            field.rs_name,
+                            &rs_ty,
+                            field.attr.name.as_deref(),
+                            field.rs_attrs,
+                        )
+                    }
+                    None => request_field(
                         field.rs_name,
                         &*field.rs_ty,
                         field.attr.name.as_deref(),
                         field.rs_attrs,
-                    )
-                });
+                    ),
+                };
+                Some((embed, field))
+            });
+            let create_request_fields = embed_fields(create_request_fields, embed_request_field);
 
             let request_setters = create_columns
                 .clone()
-                .filter_map(|column| column.request_setter_molecule());
+                .filter_map(stage3::ColumnRef::request_setter_molecule);
 
             let create_request_setters = create_columns
                 .clone()
-                .filter_map(|column| column.request_setter_molecule())
+                .filter_map(stage3::ColumnRef::request_setter_molecule)
                 .map(|setter| {
                     let rs_name = setter.rs_name;
-                    request_setter(&quote! { request.#rs_name }, setter.is_optional)
+                    // the nested foreign record has already been created
+                    if setter.nested.is_some() {
+                        request_setter(&quote! { #rs_name }, setter.is_optional)
+                    } else {
+                        request_setter(
+                            &request_value(&quote! { request }, setter),
+                            setter.is_optional,
+                        )
+                    }
+                });
+
+            // create the nested foreign records first, then link them
+            let create_request_nested = create_columns
+                .clone()
+                .filter_map(stage3::ColumnRef::request_setter_molecule)
+                .filter_map(|setter| {
+                    let rs_name = setter.rs_name;
+                    let foreign_table_rs_name = setter.nested?;
+                    // the errors of the nested request are returned in its column
+                    let create = |nested| {
+                        quote! {
+                            match ::laraxum::model::Nested::create::<#foreign_table_rs_name>(
+                                #nested,
+                                db,
+                                &mut *transaction,
+                            ).await {
+                                ::core::result::Result::Ok(id) => id,
+                                ::core::result::Result::Err(
+                                    ::laraxum::ModelError::UnprocessableEntity(error),
+                                ) => {
+                                    return ::core::result::Result::Err(
+                                        ::laraxum::ModelError::UnprocessableEntity(
+                                            #request_error_rs_name {
+                                                #rs_name: ::core::option::Option::Some(
+                                                    ::std::boxed::Box::new(error),
+                                                ),
+                                                ..::core::default::Default::default()
+                                            },
+                                        ),
+                                    );
+                                }
+                                ::core::result::Result::Err(
+                                    ::laraxum::ModelError::Other(error),
+                                ) => return ::core::result::Result::Err(
+                                    ::laraxum::ModelError::Other(error),
+                                ),
+                            }
+                        }
+                    };
+                    let nested = if setter.is_optional {
+                        let create = create(quote! { nested });
+                        quote! {
+                            match request.#rs_name {
+                                ::core::option::Option::Some(nested) => {
+                                    ::core::option::Option::Some(#create)
+                                }
+                                ::core::option::Option::None => ::core::option::Option::None,
+                            }
+                        }
+                    } else {
+                        create(quote! { request.#rs_name })
+                    };
+                    Some(quote! { let #rs_name = #nested; })
                 });
+            let create_request_nested = quote! { #( #create_request_nested )* };
 
             let create_request_columns = create_columns
                 .clone()
-                .filter_map(|column| column.request_molecule());
+                .filter_map(stage3::ColumnRef::request_molecule);
+
+            // the rows are pushed at runtime, so they are always bound at runtime
+            let import_token_stream = is_import(&table).then(|| {
+                let create_many = create_many(&table.name_intern, create_request_columns.clone());
+                let pushes = create_request_columns.clone().map(|column| match column {
+                    stage3::RequestColumnMolecule::Mutable(mutable) => {
+                        let setter = request_setter(
+                            &request_value(&quote! { request }, &mutable.setter),
+                            mutable.setter.is_optional,
+                        );
+                        quote! { row.push_bind(#setter); }
+                    }
+                    stage3::RequestColumnMolecule::OnUpdate(on_update) => {
+                        let current_time_func = on_update.time_ty.current_time_func();
+                        quote! { row.push(#current_time_func); }
+                    }
+                });
+                let response = run_query(
+                    &quote! { response.build().execute(&mut *transaction) },
+                    &create_many,
+                    &table.name_intern,
+                );
+                quote! {
+                    impl ::laraxum::Import for #table_rs_name {
+                        async fn create_batch(
+                            db: &Self::Db,
+                            requests: ::std::vec::Vec<Self::CreateRequest>,
+                        ) -> ::core::result::Result<(), ::laraxum::Error> {
+                            /// How many rows are created with one query.
+                            const BATCH: usize = 500;
+
+                            let mut transaction = db.pool.begin().await?;
+                            let mut requests = ::core::iter::IntoIterator::into_iter(requests);
+                            loop {
+                                let batch: ::std::vec::Vec<_> = ::core::iter::Iterator::collect(
+                                    ::core::iter::Iterator::take(&mut requests, BATCH),
+                                );
+                                if batch.is_empty() {
+                                    break;
+                                }
+                                let mut response = ::sqlx::QueryBuilder::new(#create_many);
+                                response.push_values(batch, |mut row, request| {
+                                    #( #pushes )*
+                                });
+                                #response?;
+                            }
+                            transaction.commit().await?;
+                            ::core::result::Result::Ok(())
+                        }
+                    }
+                }
+            });
 
             let create_one = create_one(&table.name_intern, create_request_columns);
-            let create_one = quote! {
-                ::sqlx::query!(#create_one, #(#create_request_setters,)*)
-            };
+            let create_one_query = query(&create_one, create_request_setters, table.is_checked);
+            let create_one = run_query(
+                &quote! { #create_one_query.execute(&mut **transaction) },
+                &create_one,
+                &table.name_intern,
+            );
 
             let request_setter_collections = table
                 .columns
                 .iter()
-                .filter_map(|column| column.request_setter_collection());
+                .filter_map(stage3::ColumnRef::request_setter_collection);
 
             let create_request_setter_collections =
                 request_setter_collections.clone().map(|column| {
@@ -1014,8 +3937,9 @@ impl From<stage3::Table<'_>> for Table {
                     quote! {{
                         <#many_foreign_table_rs_name as ::laraxum::ManyModel::<
                             #aggregate_rs_name,
-                        >>::create_many(
+                        >>::create_many_in(
                             db,
+                            &mut *transaction,
                             id,
                             &request.#rs_name,
                         ).await?;
@@ -1024,6 +3948,191 @@ impl From<stage3::Table<'_>> for Table {
             let create_request_setter_collections =
                 quote! { #( #create_request_setter_collections )*};
 
+            // create the record in the transaction `transaction`, and return its ID
+            let create_in = quote! {{
+                #create_request_nested
+                let response = #create_one?;
+                let id = response.last_insert_id();
+                #create_request_setter_collections
+                id
+            }};
+
+            let expand = expand(table.columns.iter());
+            let fields = fields(table.columns.iter());
+
+            let children_token_streams = table.children.iter().map(|children| {
+                let stage3::TableChildren {
+                    name_intern,
+                    aggregate_rs_name,
+                    foreign_table_id_rs_ty,
+                } = children;
+
+                let get_children = get_many(
+                    &table.name_intern,
+                    &table.name_extern,
+                    response_getters,
+                    (stage3::ColumnAttrAggregateFilter::Eq, name_intern),
+                );
+                let get_children = transform_response_many(
+                    &query(&get_children, [quote! { parent }], table.is_checked),
+                    (&get_children, &table.name_intern),
+                    response_getter,
+                    children_batch,
+                );
+
+                let get_children_batch = table_batch(&table, name_intern);
+                let get_children_batch = transform_response_batch(
+                    &quote! { parents },
+                    (&get_children_batch, &table.name_intern),
+                    &table_batch_response_getter(&table, &syn::parse_quote!(Self::ParentId)),
+                    children_batch,
+                );
+
+                quote! {
+                    impl ::laraxum::Children<#aggregate_rs_name> for #table_rs_name {
+                        type ParentId = #foreign_table_id_rs_ty;
+
+                        async fn get_children(
+                            db: &Self::Db,
+                            parent: Self::ParentId,
+                        )
+                            -> ::core::result::Result<
+                                ::std::vec::Vec<Self::Response>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            #get_children
+                        }
+                        async fn get_children_batch(
+                            db: &Self::Db,
+                            parents: &[Self::ParentId],
+                        )
+                            -> ::core::result::Result<
+                                ::std::vec::Vec<::std::vec::Vec<Self::Response>>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            #get_children_batch
+                        }
+                    }
+                }
+            });
+
+            let poly_token_streams = table.polys.iter().map(|poly| {
+                let stage3::TablePoly {
+                    rs_ty_name,
+                    id_rs_ty,
+                    foreign_tables,
+                    table_name_intern,
+                    id_name_intern,
+                } = poly;
+
+                let variants = foreign_tables.iter().map(|(foreign_table_rs_name, name)| {
+                    quote! {
+                        #[serde(rename = #name)]
+                        #foreign_table_rs_name(#id_rs_ty)
+                    }
+                });
+                let table_arms = foreign_tables.iter().map(|(foreign_table_rs_name, name)| {
+                    quote! { Self::#foreign_table_rs_name(_) => #name }
+                });
+                let id_arms = foreign_tables.iter().map(|(foreign_table_rs_name, _)| {
+                    quote! { Self::#foreign_table_rs_name(id) => *id }
+                });
+                // the placeholder of an unselected field, see `::laraxum::model::fields`
+                let unselected = foreign_tables.first().map(|(foreign_table_rs_name, _)| {
+                    quote! {
+                        impl ::laraxum::model::types::Unselected for #rs_ty_name {
+                            fn unselected() -> Self {
+                                Self::#foreign_table_rs_name(
+                                    ::laraxum::model::types::Unselected::unselected(),
+                                )
+                            }
+                        }
+                    }
+                });
+                let from_parts_arms = foreign_tables.iter().map(|(foreign_table_rs_name, name)| {
+                    quote! {
+                        #name => ::core::option::Option::Some(Self::#foreign_table_rs_name(id))
+                    }
+                });
+
+                let get_poly = get_many_poly(
+                    &table.name_intern,
+                    &table.name_extern,
+                    response_getters,
+                    (table_name_intern, id_name_intern),
+                );
+                let get_poly = transform_response_many(
+                    &query(
+                        &get_poly,
+                        [
+                            request_setter(
+                                &quote! {
+                                    ::std::string::String::from(
+                                        ::laraxum::model::Poly::table(&one),
+                                    )
+                                },
+                                false,
+                            ),
+                            request_setter(&quote! { ::laraxum::model::Poly::id(&one) }, false),
+                        ],
+                        table.is_checked,
+                    ),
+                    (&get_poly, &table.name_intern),
+                    response_getter,
+                    children_batch,
+                );
+
+                quote! {
+                    #[derive(::core::clone::Clone, ::serde::Serialize, ::serde::Deserialize)]
+                    #[serde(tag = "type", content = "id")]
+                    pub enum #rs_ty_name {
+                        #( #variants, )*
+                    }
+                    impl ::laraxum::model::Poly for #rs_ty_name {
+                        type Id = #id_rs_ty;
+
+                        fn table(&self) -> &'static str {
+                            match self {
+                                #( #table_arms, )*
+                            }
+                        }
+                        fn id(&self) -> Self::Id {
+                            match self {
+                                #( #id_arms, )*
+                            }
+                        }
+                        fn from_parts(
+                            table: &str,
+                            id: Self::Id,
+                        ) -> ::core::option::Option<Self> {
+                            match table {
+                                #( #from_parts_arms, )*
+                                _ => ::core::option::Option::None,
+                            }
+                        }
+                    }
+                    #unselected
+
+                    impl ::laraxum::AggregateMany<#rs_ty_name> for #table_rs_name {
+                        type OneRequest<'b> = #rs_ty_name;
+                        type ManyResponse = Self;
+                        async fn aggregate_many<'a>(
+                            db: &Self::Db,
+                            one: Self::OneRequest<'a>,
+                        )
+                            -> ::core::result::Result<
+                                ::std::vec::Vec<Self::ManyResponse>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            #get_poly
+                        }
+                    }
+                }
+            });
+
             let collection_token_stream = quote! {
                 #[derive(::serde::Deserialize)]
                 pub struct #create_request_rs_name {
@@ -1042,6 +4151,21 @@ impl From<stage3::Table<'_>> for Table {
                     {
                         #get_all
                     }
+                    async fn get_all_fields(db: &Self::Db, fields: &[&str])
+                        -> ::core::result::Result<
+                            ::std::vec::Vec<Self::Response>,
+                            ::laraxum::Error,
+                        >
+                    {
+                        #get_all_fields
+                    }
+                    fn stream_all(db: &Self::Db)
+                        -> impl ::futures::Stream<
+                            Item = ::core::result::Result<Self::Response, ::laraxum::Error>,
+                        > + ::core::marker::Send
+                    {
+                        #stream_all
+                    }
                     async fn create_one(
                         db: &Self::Db,
                         request: Self::CreateRequest,
@@ -1055,15 +4179,21 @@ impl From<stage3::Table<'_>> for Table {
                                 ::laraxum::model::request::method::Create
                             >
                         >::validate(&request)?;
-                        let transaction = db.pool.begin().await?;
-                        let response = #create_one;
-                        let response = response.execute(&db.pool).await?;
-                        let id = response.last_insert_id();
-                        #create_request_setter_collections
+                        let mut transaction = db.pool.begin().await?;
+                        {
+                            let transaction = &mut transaction;
+                            #create_in;
+                        }
                         transaction.commit().await?;
                         ::core::result::Result::Ok(())
                     }
+                    #expand
+                    #fields
                 }
+                #import_token_stream
+
+                #( #children_token_streams )*
+                #( #poly_token_streams )*
             };
 
             let validates = request_setters
@@ -1132,8 +4262,9 @@ impl From<stage3::Table<'_>> for Table {
                     let validates = validates.into_iter().flatten();
                     let not_empty = validates.clone().next().is_some();
                     not_empty.then(|| {
+                        let value = request_value(&quote! { self }, column);
                         let value = quote! {
-                            &self.#rs_name
+                            &#value
                         };
                         let validates = validates.map(|validate| {
                             quote! {
@@ -1161,6 +4292,21 @@ impl From<stage3::Table<'_>> for Table {
                     pub #rs_name: ::std::vec::Vec::<&'static str>,
                 }
             });
+            // the errors of the request to create a nested foreign record
+            let request_error_nested_fields = table
+                .columns
+                .iter()
+                .filter_map(stage3::ColumnRef::request_setter_molecule)
+                .filter_map(|setter| {
+                    let rs_name = setter.rs_name;
+                    let foreign_table_rs_name = setter.nested?;
+                    Some(quote! {
+                        #[serde(skip_serializing_if = "::core::option::Option::is_none")]
+                        pub #rs_name: ::core::option::Option<::std::boxed::Box<
+                            <#foreign_table_rs_name as ::laraxum::Collection>::CreateRequestError,
+                        >>,
+                    })
+                });
 
             let create_request_validates = validates.iter();
 
@@ -1187,6 +4333,7 @@ impl From<stage3::Table<'_>> for Table {
                 #[derive(Default, ::serde::Serialize)]
                 pub struct #request_error_rs_name {
                     #( #request_error_fields )*
+                    #( #request_error_nested_fields )*
                 }
                 impl ::core::convert::From<#request_error_rs_name>
                     for ::laraxum::ModelError<#request_error_rs_name>
@@ -1208,6 +4355,8 @@ impl From<stage3::Table<'_>> for Table {
                 }
             };
 
+            let table_ref = &table;
+            let table_id = table.columns.model();
             let aggregates = table
                 .columns
                 .iter()
@@ -1216,19 +4365,14 @@ impl From<stage3::Table<'_>> for Table {
                     stage3::ColumnRef::Collection(_) => None,
                 })
                 .flat_map(|column| {
-                    let filter_rs_ty_owned = column.response.field.rs_ty;
-                    let is_borrowed = column.borrow.is_some();
-                    let lifetime = is_borrowed.then(|| quote! { 'b });
-
-                    let auto_lifetime = is_borrowed.then(|| quote! { '_ });
-                    let filter_rs_ty = if let Some(borrow) = column.borrow {
-                        let borrow = borrow.unwrap_or(filter_rs_ty_owned);
+                    let column_rs_name = &column.response.field.rs_name;
+                    let column_rs_ty = column.response.field.rs_ty;
+                    let column_rs_ty_borrowed: Option<Type> = column.borrow.map(|borrow| {
+                        let borrow = borrow.unwrap_or(column_rs_ty);
                         syn::parse_quote! {
                             &'b #borrow
                         }
-                    } else {
-                        filter_rs_ty_owned.clone()
-                    };
+                    });
                     let name_intern = column.name_intern();
                     let is_unique = column.create.ty.is_unique();
 
@@ -1238,6 +4382,25 @@ impl From<stage3::Table<'_>> for Table {
                     column.aggregates.iter().map(move |aggregate| {
                         let is_one = is_unique && aggregate.filter.is_eq();
                         let aggregate_rs_name = &aggregate.rs_name;
+                        let filter_name_intern = filter_name_intern(table_ref, column, aggregate);
+                        // the cursor is the column and the ID of the last record
+                        let cursor = table_id.filter(|_| aggregate.limit.is_cursor() && !is_one);
+
+                        // only filters that compare the column to a value can borrow it
+                        let filter_rs_ty_borrowed = column_rs_ty_borrowed
+                            .as_ref()
+                            .filter(|_| aggregate.filter.is_value());
+                        let is_borrowed = filter_rs_ty_borrowed.is_some();
+                        let lifetime = is_borrowed.then(|| quote! { 'b });
+                        let auto_lifetime = is_borrowed.then(|| quote! { '_ });
+                        let filter_rs_ty_owned =
+                            &aggregate_filter_rs_ty(aggregate.filter, column_rs_ty);
+                        let filter_rs_ty = filter_rs_ty_borrowed.unwrap_or(filter_rs_ty_owned);
+                        let (response_getters, response_getter) = if aggregate.filter.is_in() {
+                            (unchecked_response_getters, unchecked_response_getter)
+                        } else {
+                            (response_getters, response_getter)
+                        };
 
                         let filter = aggregate.filter.parameter().map(|parameter_name| {
                             (
@@ -1247,7 +4410,7 @@ impl From<stage3::Table<'_>> for Table {
                                     column_response_name,
                                     parameter_name
                                 ),
-                                &filter_rs_ty,
+                                filter_rs_ty,
                                 filter_rs_ty_owned,
                             )
                         });
@@ -1264,6 +4427,32 @@ impl From<stage3::Table<'_>> for Table {
                                         },
                                     )
                                 });
+                        // the page of page based pagination, with how many records per page
+                        let page = limit.as_ref().and(match aggregate.limit {
+                            stage3::ColumnAttrAggregateLimit::Page {
+                                per_page,
+                                max_per_page,
+                                is_envelope,
+                            } => Some((per_page, max_per_page, is_envelope)),
+                            _ => None,
+                        });
+                        let per_page = page.map(|(per_page, max_per_page, _)| {
+                            if let Some(max_per_page) = max_per_page {
+                                quote! {
+                                    ::core::cmp::Ord::min(
+                                        ::core::option::Option::unwrap_or(
+                                            request.per_page,
+                                            #per_page,
+                                        ),
+                                        #max_per_page,
+                                    )
+                                }
+                            } else {
+                                quote! { #per_page }
+                            }
+                        });
+                        let is_per_page =
+                            page.is_some_and(|(_, max_per_page, _)| max_per_page.is_some());
                         let sort = aggregate.is_sort.then(|| -> (Ident, Ident, Type) {
                             (
                                 quote::format_ident!("sort"),
@@ -1294,70 +4483,225 @@ impl From<stage3::Table<'_>> for Table {
                             }
                         });
 
+                        let cursor_field = cursor.map(|_| {
+                            quote! {
+                                pub cursor: ::core::option::Option<::laraxum::model::Cursor>,
+                            }
+                        });
+
+                        let per_page_field = is_per_page.then(|| {
+                            quote! {
+                                pub per_page: ::core::option::Option<u64>,
+                            }
+                        });
+                        // the request is cloned to count the records
+                        let derive_clone = page.map(|_| quote! { ::core::clone::Clone, });
+
                         let aggregate_struct_token_stream = quote! {
-                            #[derive(::serde::Deserialize)]
+                            #[derive(#derive_clone ::serde::Deserialize)]
                             pub struct #aggregate_rs_name<#lifetime> {
                                 #filter_field
                                 #limit_field
+                                #per_page_field
                                 #sort_field
+                                #cursor_field
+                            }
+                        };
+                        let filter_parameters = filter.as_ref().map(|(short_name, _, _, _)| {
+                            filter_args(aggregate.filter, &quote! { request.#short_name })
+                        });
+                        let limit_parameter = match (&limit, page) {
+                            (Some((name, _)), Some((_, Some(_), _))) => {
+                                vec![quote! { per_page }, quote! { request.#name * per_page }]
+                            }
+                            (Some((name, _)), Some((per_page, None, _))) => {
+                                vec![quote! { request.#name * #per_page }]
                             }
+                            (Some((name, _)), None) => vec![quote! { request.#name }],
+                            (None, _) => vec![],
+                        };
+                        let parameters = filter_parameters.iter().flatten().chain(&limit_parameter);
+                        let in_filter = filter_parameters
+                            .as_ref()
+                            .and_then(|parameters| parameters.first())
+                            .filter(|_| aggregate.filter.is_in())
+                            .map(|value| InFilter {
+                                column: &filter_name_intern,
+                                value,
+                            });
+
+                        // the next cursor is the column and the ID of the last record,
+                        // so they are always selected
+                        let cursor_select_fields = cursor.map(|table_id| {
+                            SelectFields::new(table_ref, &[table_id.name_intern(), name_intern])
+                        });
+                        let select_fields = cursor_select_fields.as_ref().unwrap_or(select_fields);
+                        // the query of all fields and the query of the selected fields
+                        let transform = |sql: &str, parameters: &[&proc_macro2::TokenStream]| {
+                            let Some(in_filter) = in_filter else {
+                                let response = query(sql, parameters, table.is_checked);
+                                let response = transform_response(
+                                    &response,
+                                    (sql, table_name_intern),
+                                    response_getter,
+                                    batch,
+                                    is_one,
+                                );
+                                let response_fields =
+                                    select_fields.transform_response(sql, parameters, is_one, None);
+                                return [response, response_fields];
+                            };
+                            // the values of the filter are bound instead of its parameter
+                            let parameters = parameters.get(1..).unwrap_or_default();
+                            let values = in_filter.values();
+                            let in_sql = in_filter.sql(sql);
+                            let response =
+                                InFilter::query(&quote! { ::sqlx::query(&sql) }, parameters);
+                            let response = transform_response(
+                                &response,
+                                (quote! { &*sql }, table_name_intern),
+                                response_getter,
+                                batch,
+                                is_one,
+                            );
+                            let response = quote! {{
+                                #values
+                                let sql = #in_sql;
+                                #response
+                            }};
+                            let response_fields = select_fields.transform_response(
+                                sql,
+                                parameters,
+                                is_one,
+                                Some(in_filter),
+                            );
+                            [response, response_fields]
                         };
-                        let filter_parameter = filter.as_ref().map(|(short_name, _, _, _)| {
-                            quote! { request.#short_name, }
-                        });
-                        let limit_parameter =
-                            limit.as_ref().map(|(name, _)| match aggregate.limit {
-                                stage3::ColumnAttrAggregateLimit::Page { per_page } => {
-                                    quote! { request.#name * #per_page, }
-                                }
-                                _ => {
-                                    quote! { request.#name, }
+                        let sort_match = |[asc, desc]: [proc_macro2::TokenStream; 2]| {
+                            quote! {
+                                match request.sort {
+                                    ::laraxum::model::Sort::Ascending => #asc,
+                                    ::laraxum::model::Sort::Descending => #desc,
                                 }
-                            });
+                            }
+                        };
 
-                        let response = if aggregate.is_sort {
+                        let responses = if let Some(table_id) = cursor {
+                            let id_name_intern = table_id.name_intern();
+                            let id_rs_ty = table_id.response.field.rs_ty;
+                            let cursor_parameters =
+                                [quote! { cursor_column }, quote! { cursor_id }];
+                            let parameters_after = filter_parameters
+                                .iter()
+                                .flatten()
+                                .chain(&cursor_parameters)
+                                .chain(&limit_parameter);
+                            let response_cursor = |aggregate_sort: Sort| {
+                                let responses = [false, true].map(|is_after| {
+                                    let get_cursor = get_cursor(
+                                        table_name_intern,
+                                        table_name_extern,
+                                        response_getters,
+                                        (aggregate.filter, &*filter_name_intern),
+                                        (aggregate_sort, name_intern),
+                                        id_name_intern,
+                                        is_after,
+                                    );
+                                    let parameters: Vec<_> = if is_after {
+                                        parameters_after.clone().collect()
+                                    } else {
+                                        parameters.clone().collect()
+                                    };
+                                    transform(&get_cursor, &parameters)
+                                });
+                                let [[first, first_fields], [after, after_fields]] = responses;
+                                [[first, after], [first_fields, after_fields]].map(
+                                    |[response_first, response_after]| {
+                                        quote! {
+                                            match cursor {
+                                                ::core::option::Option::None => #response_first,
+                                                ::core::option::Option::Some(
+                                                    (cursor_column, cursor_id)
+                                                ) => #response_after,
+                                            }
+                                        }
+                                    },
+                                )
+                            };
+                            let responses = if aggregate.is_sort {
+                                let [asc, asc_fields] = response_cursor(Sort::Ascending);
+                                let [desc, desc_fields] = response_cursor(Sort::Descending);
+                                [[asc, desc], [asc_fields, desc_fields]].map(sort_match)
+                            } else {
+                                response_cursor(Sort::Ascending)
+                            };
+                            responses.map(|response| {
+                                quote! {
+                                    let cursor = match request.cursor {
+                                        ::core::option::Option::Some(ref cursor) => {
+                                            ::core::option::Option::Some(
+                                                cursor.decode::<(#column_rs_ty, #id_rs_ty)>()?
+                                            )
+                                        }
+                                        ::core::option::Option::None => {
+                                            ::core::option::Option::None
+                                        }
+                                    };
+                                    #response
+                                }
+                            })
+                        } else if aggregate.is_sort {
                             let (get_sort_asc, get_sort_desc) = get_sort_asc_desc(
                                 table_name_intern,
                                 table_name_extern,
                                 response_getters,
-                                Some((aggregate.filter, name_intern)),
+                                Some((aggregate.filter, &*filter_name_intern)),
                                 name_intern,
                                 Some(aggregate.limit),
                                 is_one,
                             );
-
-                            let response_sort_asc = quote! {
-                                ::sqlx::query!(#get_sort_asc, #filter_parameter #limit_parameter)
-                            };
-                            let response_sort_asc =
-                                transform_response(&response_sort_asc, response_getter, is_one);
-
-                            let response_sort_desc = quote! {
-                                ::sqlx::query!(#get_sort_desc, #filter_parameter #limit_parameter)
-                            };
-                            let response_sort_desc =
-                                transform_response(&response_sort_desc, response_getter, is_one);
-                            quote! {
-                                match request.sort {
-                                    ::laraxum::model::Sort::Ascending => #response_sort_asc,
-                                    ::laraxum::model::Sort::Descending => #response_sort_desc,
-                                }
-                            }
+                            let parameters: Vec<_> = parameters.collect();
+                            let [asc, asc_fields] = transform(&get_sort_asc, &parameters);
+                            let [desc, desc_fields] = transform(&get_sort_desc, &parameters);
+                            [[asc, desc], [asc_fields, desc_fields]].map(sort_match)
                         } else {
                             let get = get(
                                 table_name_intern,
                                 table_name_extern,
                                 response_getters,
-                                Some((aggregate.filter, name_intern)),
+                                Some((aggregate.filter, &*filter_name_intern)),
                                 None,
                                 Some(aggregate.limit),
                                 is_one,
                             );
-                            let response = quote! {
-                                ::sqlx::query!(#get, #filter_parameter #limit_parameter)
-                            };
-                            transform_response(&response, response_getter, is_one)
+                            // a search is ordered by relevance, which binds the value again,
+                            // so it's cloned for the filter
+                            let relevance_parameters: Vec<proc_macro2::TokenStream> =
+                                filter_parameters
+                                    .iter()
+                                    .flatten()
+                                    .filter(|_| aggregate.filter.is_search())
+                                    .map(|parameter| {
+                                        quote! { ::core::clone::Clone::clone(&#parameter) }
+                                    })
+                                    .collect();
+                            let parameters: Vec<_> = relevance_parameters
+                                .iter()
+                                .chain(filter_parameters.iter().flatten())
+                                .chain(&limit_parameter)
+                                .collect();
+                            transform(&get, &parameters)
                         };
+                        let [response, response_fields] = responses.map(|response| match per_page
+                            .as_ref()
+                            .filter(|_| is_per_page)
+                        {
+                            Some(per_page) => quote! {
+                                let per_page: u64 = #per_page;
+                                #response
+                            },
+                            None => response,
+                        });
 
                         let aggregate_impl_token_stream = if is_one {
                             quote! {
@@ -1381,6 +4725,156 @@ impl From<stage3::Table<'_>> for Table {
                                 }
                             }
                         } else {
+                            // the next cursor is only needed if the page is full
+                            // the page of all fields, and the page of the selected fields
+                            let aggregate_page_fns = |is_fields: bool| {
+                                if is_fields {
+                                    (
+                                        quote! { aggregate_page_fields },
+                                        quote! { fields: &[&str], },
+                                        quote! { aggregate_many_fields(db, request, fields) },
+                                    )
+                                } else {
+                                    (
+                                        quote! { aggregate_page },
+                                        quote! {},
+                                        quote! { aggregate_many(db, request) },
+                                    )
+                                }
+                            };
+                            let aggregate_page = cursor.map(|table_id| {
+                                let id_rs_name = &table_id.response.field.rs_name;
+                                let aggregate_page = |is_fields| {
+                                let (aggregate_page, fields, aggregate_many) =
+                                    aggregate_page_fns(is_fields);
+                                quote! {
+                                    async fn #aggregate_page<'a>(
+                                        db: &Self::Db,
+                                        request: Self::OneRequest<'a>,
+                                        #fields
+                                    )
+                                        -> ::core::result::Result<
+                                            ::laraxum::model::Page<Self::ManyResponse>,
+                                            ::laraxum::Error,
+                                        >
+                                    {
+                                        let limit = request.limit;
+                                        let data = <Self as ::laraxum::AggregateMany<
+                                            #aggregate_rs_name<#auto_lifetime>
+                                        >>::#aggregate_many.await?;
+                                        let is_full = <u64 as ::core::convert::TryFrom<usize>>
+                                            ::try_from(data.len())
+                                            .is_ok_and(|len| len == limit);
+                                        let next_cursor = match data.last() {
+                                            ::core::option::Option::Some(last) if is_full => {
+                                                ::core::option::Option::Some(
+                                                    ::laraxum::model::Cursor::encode(
+                                                        &(&last.#column_rs_name, &last.#id_rs_name)
+                                                    )?
+                                                )
+                                            }
+                                            _ => ::core::option::Option::None,
+                                        };
+                                        ::core::result::Result::Ok(::laraxum::model::Page {
+                                            data,
+                                            next_cursor,
+                                            pagination: ::core::option::Option::None,
+                                        })
+                                    }
+                                }
+                                };
+                                [aggregate_page(false), aggregate_page(true)]
+                            });
+                            // the total is counted with the same filter
+                            let aggregate_page = aggregate_page.or_else(|| {
+                                let (page_rs_name, _) = limit.as_ref()?;
+                                let (_, _, is_envelope) = page?;
+                                let count = count(
+                                    table_name_intern,
+                                    table_name_extern,
+                                    (aggregate.filter, &*filter_name_intern),
+                                );
+                                let count_parameters = filter_parameters.iter().flatten();
+                                let count_request = count_parameters.clone().next().map(|_| {
+                                    quote! {
+                                        let request = ::core::clone::Clone::clone(&request);
+                                    }
+                                });
+                                let (count_in, count_query, count_sql) = if let Some(in_filter) =
+                                    in_filter
+                                {
+                                    let values = in_filter.values();
+                                    let in_sql = in_filter.sql(&count);
+                                    (
+                                        Some(quote! {
+                                            #values
+                                            let sql = #in_sql;
+                                        }),
+                                        InFilter::query(
+                                            &quote! { ::sqlx::query_scalar::<_, i64>(&sql) },
+                                            count_parameters.skip(1),
+                                        ),
+                                        quote! { &*sql },
+                                    )
+                                } else {
+                                    let count_query = query_scalar(
+                                        &count,
+                                        count_parameters,
+                                        &syn::parse_quote!(i64),
+                                        table.is_checked,
+                                    );
+                                    (None, count_query, quote! { #count })
+                                };
+                                let count_run = run_query(
+                                    &quote! { #count_query.fetch_one(<Self::Db>::read_pool(db)) },
+                                    count_sql,
+                                    table_name_intern,
+                                );
+                                let aggregate_page = |is_fields| {
+                                let (aggregate_page, fields, aggregate_many) =
+                                    aggregate_page_fns(is_fields);
+                                quote! {
+                                    async fn #aggregate_page<'a>(
+                                        db: &Self::Db,
+                                        request: Self::OneRequest<'a>,
+                                        #fields
+                                    )
+                                        -> ::core::result::Result<
+                                            ::laraxum::model::Page<Self::ManyResponse>,
+                                            ::laraxum::Error,
+                                        >
+                                    {
+                                        let page = request.#page_rs_name;
+                                        let per_page: u64 = #per_page;
+                                        let total = {
+                                            #count_request
+                                            #count_in
+                                            let total: i64 = #count_run?;
+                                            <u64 as ::core::convert::TryFrom<i64>>::try_from(total)
+                                                .unwrap_or_default()
+                                        };
+                                        let data = <Self as ::laraxum::AggregateMany<
+                                            #aggregate_rs_name<#auto_lifetime>
+                                        >>::#aggregate_many.await?;
+                                        let pagination = ::laraxum::model::page::Pagination {
+                                            page,
+                                            per_page,
+                                            total,
+                                            is_envelope: #is_envelope,
+                                            query: ::core::option::Option::None,
+                                        };
+                                        ::core::result::Result::Ok(::laraxum::model::Page {
+                                            data,
+                                            next_cursor: ::core::option::Option::None,
+                                            pagination: ::core::option::Option::Some(pagination),
+                                        })
+                                    }
+                                }
+                                };
+                                Some([aggregate_page(false), aggregate_page(true)])
+                            });
+                            let [aggregate_page, aggregate_page_fields] =
+                                aggregate_page.map_or([None, None], |page| page.map(Some));
                             quote! {
                                 impl
                                     ::laraxum::AggregateMany<#aggregate_rs_name<#auto_lifetime>>
@@ -1399,6 +4893,20 @@ impl From<stage3::Table<'_>> for Table {
                                     {
                                         #response
                                     }
+                                    async fn aggregate_many_fields(
+                                        db: &Self::Db,
+                                        request: Self::OneRequest<'_>,
+                                        fields: &[&str],
+                                    )
+                                        -> ::core::result::Result<
+                                            ::std::vec::Vec<Self::ManyResponse>,
+                                            ::laraxum::Error,
+                                        >
+                                    {
+                                        #response_fields
+                                    }
+                                    #aggregate_page
+                                    #aggregate_page_fields
                                 }
                             }
                         };
@@ -1428,11 +4936,24 @@ impl From<stage3::Table<'_>> for Table {
                                 }
                             });
 
+                            let per_page_field = is_per_page.then(|| {
+                                quote! {
+                                    per_page: ::core::option::Option<u64>,
+                                }
+                            });
+                            let cursor_field = cursor.map(|_| {
+                                quote! {
+                                    cursor: ::core::option::Option<::laraxum::model::Cursor>,
+                                }
+                            });
+
                             let aggregate_variant_def_token_stream = quote! {
                                 #aggregate_rs_name {
                                     #filter_field
                                     #limit_field
+                                    #per_page_field
                                     #sort_field
+                                    #cursor_field
                                 }
                             };
 
@@ -1473,52 +4994,107 @@ impl From<stage3::Table<'_>> for Table {
                                     #short_name,
                                 }
                             });
+                            let per_page_get_set = is_per_page.then(|| {
+                                quote! {
+                                    per_page,
+                                }
+                            });
+                            let cursor_get_set = cursor.map(|_| {
+                                quote! {
+                                    cursor,
+                                }
+                            });
 
                             let aggregate_get = quote! {
                                 #table_aggregate_rs_name::#aggregate_rs_name {
                                     #filter_get
                                     #limit_get
+                                    #per_page_get_set
                                     #sort_get
+                                    #cursor_get_set
                                 }
                             };
                             let aggregate_set = quote! {
                                 #aggregate_rs_name {
                                     #filter_set
                                     #limit_set
+                                    #per_page_get_set
                                     #sort_set
+                                    #cursor_get_set
                                 }
                             };
-                            let aggregate_variant_match_token_stream = if is_one {
-                                quote! {
+                            let aggregate_variant_match_token_streams = if is_one {
+                                let aggregate_one_vec = quote! {
+                                    <#table_rs_name as
+                                        ::laraxum::AggregateOne<
+                                            #aggregate_rs_name<#auto_lifetime>
+                                        >
+                                    >::aggregate_one_vec(db, #aggregate_set).await
+                                };
+                                // a single record is always selected with all of its fields
+                                let aggregate_variant_match_token_stream = quote! {
                                     #aggregate_get => {
-                                        <#table_rs_name as
-                                            ::laraxum::AggregateOne<
-                                                #aggregate_rs_name<#auto_lifetime>
-                                            >
-                                        >::aggregate_one_vec(db, #aggregate_set).await
+                                        #aggregate_one_vec
                                     }
-                                }
-                            } else {
-                                quote! {
+                                };
+                                let aggregate_variant_page_match_token_stream = quote! {
                                     #aggregate_get => {
-                                        <#table_rs_name as
-                                            ::laraxum::AggregateMany<
-                                                #aggregate_rs_name<#auto_lifetime>
-                                            >
-                                        >::aggregate_many(db, #aggregate_set).await
+                                        #aggregate_one_vec.map(::laraxum::model::Page::from)
                                     }
-                                }
+                                };
+                                [
+                                    aggregate_variant_match_token_stream.clone(),
+                                    aggregate_variant_page_match_token_stream.clone(),
+                                    aggregate_variant_match_token_stream,
+                                    aggregate_variant_page_match_token_stream,
+                                ]
+                            } else {
+                                let aggregate_many = quote! {
+                                    <#table_rs_name as
+                                        ::laraxum::AggregateMany<
+                                            #aggregate_rs_name<#auto_lifetime>
+                                        >
+                                    >
+                                };
+                                [
+                                    quote! { aggregate_many(db, #aggregate_set) },
+                                    quote! { aggregate_page(db, #aggregate_set) },
+                                    quote! { aggregate_many_fields(db, #aggregate_set, fields) },
+                                    quote! { aggregate_page_fields(db, #aggregate_set, fields) },
+                                ]
+                                .map(|aggregate| {
+                                    quote! {
+                                        #aggregate_get => #aggregate_many::#aggregate.await
+                                    }
+                                })
                             };
+                            // how many per page and the cursor are optional
                             let aggregate_variants = [
-                                filter.map(|(_, name, _, rs_ty_owned)| (name, rs_ty_owned.clone())),
-                                limit.map(|(name, rs_ty)| (name, rs_ty)),
-                                sort.map(|(_, name, rs_ty)| (name, rs_ty)),
+                                filter.map(|(_, name, _, rs_ty_owned)| {
+                                    (name, rs_ty_owned.clone(), false)
+                                }),
+                                limit.map(|(name, rs_ty)| (name, rs_ty, false)),
+                                is_per_page.then(|| {
+                                    (
+                                        quote::format_ident!("per_page"),
+                                        syn::parse_quote! { u64 },
+                                        true,
+                                    )
+                                }),
+                                sort.map(|(_, name, rs_ty)| (name, rs_ty, false)),
+                                cursor.map(|_| {
+                                    (
+                                        quote::format_ident!("cursor"),
+                                        syn::parse_quote! { ::laraxum::model::Cursor },
+                                        true,
+                                    )
+                                }),
                             ];
                             let aggregate_variant_type_signature =
                                 (aggregate_rs_name, aggregate_variants);
                             (
                                 aggregate_variant_def_token_stream,
-                                aggregate_variant_match_token_stream,
+                                aggregate_variant_match_token_streams,
                                 aggregate_variant_type_signature,
                             )
                         });
@@ -1537,13 +5113,21 @@ impl From<stage3::Table<'_>> for Table {
                     let aggregate_variant_def_token_streams =
                         aggregate_variants.clone().map(|(i, _, _)| i);
                     let aggregate_variant_match_token_streams =
-                        aggregate_variants.clone().map(|(_, i, _)| i);
+                        aggregate_variants.clone().map(|(_, [i, _, _, _], _)| i);
+                    let aggregate_variant_page_match_token_streams =
+                        aggregate_variants.clone().map(|(_, [_, i, _, _], _)| i);
+                    let aggregate_variant_match_fields_token_streams =
+                        aggregate_variants.clone().map(|(_, [_, _, i, _], _)| i);
+                    let aggregate_variant_page_match_fields_token_streams =
+                        aggregate_variants.clone().map(|(_, [_, _, _, i], _)| i);
                     let aggregate_variant_type_signatures =
                         aggregate_variants.map(|(_, _, (aggregate_rs_name, fields))| {
                             (
                                 *aggregate_rs_name,
-                                fields.iter().flat_map(|field| {
-                                    field.as_ref().map(|(rs_name, rs_ty)| (rs_name, rs_ty))
+                                fields.iter().filter_map(|field| {
+                                    field.as_ref().map(|(rs_name, rs_ty, is_optional)| {
+                                        (rs_name, rs_ty, *is_optional)
+                                    })
                                 }),
                             )
                         });
@@ -1582,6 +5166,67 @@ impl From<stage3::Table<'_>> for Table {
                                     }
                                 }
                             }
+                            async fn aggregate_page<'a>(
+                                db: &Self::Db,
+                                request: Self::OneRequest<'a>,
+                            )
+                                -> ::core::result::Result<
+                                    ::laraxum::model::Page<Self::ManyResponse>,
+                                    ::laraxum::Error,
+                                >
+                            {
+                                match request {
+                                    #( #aggregate_variant_page_match_token_streams, )*
+                                    #table_aggregate_rs_name::#table_aggregate_rs_name => {
+                                        <#table_rs_name as ::laraxum::Collection>::get_all(db)
+                                            .await
+                                            .map(::laraxum::model::Page::from)
+                                    }
+                                }
+                            }
+                            async fn aggregate_many_fields(
+                                db: &Self::Db,
+                                request: Self::OneRequest<'_>,
+                                fields: &[&str],
+                            )
+                                -> ::core::result::Result<
+                                    ::std::vec::Vec<Self::ManyResponse>,
+                                    ::laraxum::Error,
+                                >
+                            {
+                                match request {
+                                    #( #aggregate_variant_match_fields_token_streams, )*
+                                    #table_aggregate_rs_name::#table_aggregate_rs_name => {
+                                        <#table_rs_name as ::laraxum::Collection>::get_all_fields(
+                                            db,
+                                            fields,
+                                        )
+                                        .await
+                                    }
+                                }
+                            }
+                            async fn aggregate_page_fields(
+                                db: &Self::Db,
+                                request: Self::OneRequest<'_>,
+                                fields: &[&str],
+                            )
+                                -> ::core::result::Result<
+                                    ::laraxum::model::Page<Self::ManyResponse>,
+                                    ::laraxum::Error,
+                                >
+                            {
+                                match request {
+                                    #( #aggregate_variant_page_match_fields_token_streams, )*
+                                    #table_aggregate_rs_name::#table_aggregate_rs_name => {
+                                        <#table_rs_name as ::laraxum::Collection>::get_all_fields(
+                                            db,
+                                            fields,
+                                        )
+                                        .await
+                                        .map(::laraxum::model::Page::from)
+                                    }
+                                }
+                            }
                         }
                     }
                 } else {
@@ -1593,6 +5238,17 @@ impl From<stage3::Table<'_>> for Table {
                     }
                 };
 
+            let filter_token_stream = table.filter_rs_name.map(|filter_rs_name| {
+                filter_aggregate(&table, filter_rs_name, batch, select_fields)
+            });
+            let stats_token_stream = is_stats(&table)
+                .then(|| stats(&table, &quote::format_ident!("{}Stats", table_rs_name)));
+            let collection_token_stream = quote! {
+                #collection_token_stream
+                #filter_token_stream
+                #stats_token_stream
+            };
+
             let Some(table_id) = table.columns.model() else {
                 return collection_token_stream;
             };
@@ -1607,80 +5263,291 @@ impl From<stage3::Table<'_>> for Table {
                 response_getters,
                 (stage3::ColumnAttrAggregateFilter::Eq, table_id_name_intern),
             );
+            let get_one_fields =
+                select_fields.transform_response(&get_one, [quote! { id }], true, None);
             let get_one = transform_response_one(
-                &quote! {
-                    ::sqlx::query!(#get_one, id)
-                },
+                &query(&get_one, [quote! { id }], table.is_checked),
+                (&get_one, &table.name_intern),
                 response_getter,
+                batch,
             );
 
+            let get_one_batch = table_batch(&table, table_id_name_intern);
+            let get_one_batch = transform_response_batch(
+                &quote! { ids },
+                (&get_one_batch, &table.name_intern),
+                &table_batch_response_getter(&table, &syn::parse_quote!(Self::Id)),
+                batch,
+            );
+
+            let tree_token_streams = table.trees.iter().map(|tree| {
+                let &stage3::TableTree {
+                    name,
+                    ref name_intern,
+                    aggregate_rs_name,
+                } = tree;
+
+                let (response_getter_elements, response_getter_compounds) = flatten(
+                    table.columns.iter().map(stage3::ColumnRef::response_getter),
+                    false,
+                );
+                let tree_query = |start_next_match| {
+                    let get_tree = get_tree(
+                        &table.name_intern,
+                        &table.name_extern,
+                        (&response_getter_elements, &response_getter_compounds),
+                        (table_id_name, table_id_name_intern),
+                        name_intern,
+                        start_next_match,
+                    );
+                    let parent = response_field_access(
+                        BATCH_ONE_NAME,
+                        &syn::parse_quote!(Self::Id),
+                        true,
+                        false,
+                    );
+                    let parent = response_getter_column(&parent, true, false);
+                    let response = response_getter_compound(
+                        table.rs_name,
+                        table.columns.iter().map(stage3::ColumnRef::response_getter),
+                        false,
+                        false,
+                        true,
+                        None,
+                    );
+                    let response_getter = response_getter_fn(&quote! { (#parent, #response) });
+                    let batch = batch.map(|batch| {
+                        quote! {
+                            let (parents, response): (::std::vec::Vec<_>, ::std::vec::Vec<_>) =
+                                ::core::iter::Iterator::unzip(
+                                    ::core::iter::IntoIterator::into_iter(response),
+                                );
+                            let mut response = response;
+                            #batch
+                            let response = ::core::iter::Iterator::collect(
+                                ::core::iter::Iterator::zip(
+                                    ::core::iter::IntoIterator::into_iter(parents),
+                                    response,
+                                ),
+                            );
+                        }
+                    });
+                    let response_run = run_query(
+                        &quote! { ::futures::TryStreamExt::try_collect(response) },
+                        &get_tree,
+                        &table.name_intern,
+                    );
+                    let response = query(&get_tree, [quote! { id }], false);
+                    quote! {{
+                        let response = #response;
+                        let response = response.fetch(<Self::Db>::read_pool(db));
+                        let response = ::futures::StreamExt::then(response, #response_getter);
+                        let response: ::std::vec::Vec<_> = #response_run?;
+                        #batch
+                        response
+                    }}
+                };
+                let get_ancestors = tree_query((table_id_name, name, table_id_name));
+                let get_descendants = tree_query((name, table_id_name, name));
+                let get_subtree = tree_query((table_id_name, table_id_name, name));
+                let table_id_rs_name = table_id.response.field.rs_name;
+                let record_id = quote! {
+                    |response: &Self::Response| response.#table_id_rs_name
+                };
+
+                quote! {
+                    impl ::laraxum::Tree<#aggregate_rs_name> for #table_rs_name {
+                        async fn get_ancestors(
+                            db: &Self::Db,
+                            id: Self::Id,
+                        )
+                            -> ::core::result::Result<
+                                ::std::vec::Vec<Self::Response>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            let response = #get_ancestors;
+                            ::laraxum::model::tree::ancestors(&id, response, #record_id)
+                        }
+                        async fn get_descendants(
+                            db: &Self::Db,
+                            id: Self::Id,
+                        )
+                            -> ::core::result::Result<
+                                ::std::vec::Vec<Self::Response>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            let response = #get_descendants;
+                            ::core::result::Result::Ok(
+                                ::laraxum::model::tree::descendants(&id, response, #record_id),
+                            )
+                        }
+                        async fn get_subtree(
+                            db: &Self::Db,
+                            id: Self::Id,
+                        )
+                            -> ::core::result::Result<
+                                ::laraxum::model::tree::Node<Self::Response>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            let response = #get_subtree;
+                            ::laraxum::model::tree::subtree(&id, response, #record_id)
+                        }
+                    }
+                }
+            });
+
             let update_patch_columns = table.columns.iter().filter(|column| column.is_mut());
 
             let update_patch_request_fields = update_patch_columns
                 .clone()
-                .filter_map(|column| column.request_field());
+                .filter_map(stage3::ColumnRef::request_field);
             let update_request_fields = update_patch_request_fields.clone().map(|field| {
-                request_field(
+                let request_field = request_field(
                     field.rs_name,
                     &*field.rs_ty,
                     field.attr.name.as_deref(),
                     field.rs_attrs,
-                )
+                );
+                (field.embed, request_field)
             });
+            let update_request_fields = embed_fields(update_request_fields, embed_request_field);
             let patch_request_fields = update_patch_request_fields.clone().map(|field| {
                 let rs_ty = &*field.rs_ty;
                 let rs_ty = quote! {
                     ::core::option::Option<#rs_ty>
                 };
-                request_field(
+                let request_field = request_field(
                     field.rs_name,
                     &rs_ty,
                     field.attr.name.as_deref(),
                     field.rs_attrs,
-                )
+                );
+                (field.embed, request_field)
+            });
+            // the embedded struct is patched as a whole
+            let patch_request_fields = embed_fields(patch_request_fields, |embed| {
+                let stage3::ColumnEmbed {
+                    rs_name,
+                    rs_ty_name,
+                    ..
+                } = embed;
+                quote! { pub #rs_name: ::core::option::Option<#rs_ty_name>, }
             });
 
             let update_request_setters = update_patch_columns
                 .clone()
-                .filter_map(|column| column.request_setter_molecule())
+                .filter_map(stage3::ColumnRef::request_setter_molecule)
                 .map(|setter| {
-                    let rs_name = setter.rs_name;
-                    request_setter(&quote! { request.#rs_name }, setter.is_optional)
+                    request_setter(
+                        &request_value(&quote! { request }, setter),
+                        setter.is_optional,
+                    )
                 });
 
             let update_patch_request_columns = update_patch_columns
                 .clone()
-                .filter_map(|column| column.request_molecule());
+                .filter_map(stage3::ColumnRef::request_molecule);
 
             let update_one = update_one(
                 &table.name_intern,
                 table_id_name,
                 update_patch_request_columns.clone(),
             );
-            let update_one = quote! {
-                ::sqlx::query!(#update_one, #( #update_request_setters, )* id)
-            };
+            let update_one = execute_query(
+                &query(
+                    &update_one,
+                    update_request_setters.chain([quote! { id }]),
+                    table.is_checked,
+                ),
+                &update_one,
+                &table.name_intern,
+            );
 
             let patch_one = update_patch_request_columns.clone().map(|request| {
                 let patch_one = patch_one(&table.name_intern, table_id_name, request);
                 if let Some(setter) = request.setter() {
                     let rs_name = setter.rs_name;
-                    let setter = request_setter(&rs_name.to_token_stream(), setter.is_optional);
+                    let setter_value =
+                        request_setter(&rs_name.to_token_stream(), setter.is_optional);
+                    let patch_one = execute_query(
+                        &query(&patch_one, [setter_value, quote! { id }], table.is_checked),
+                        &patch_one,
+                        &table.name_intern,
+                    );
+                    let value = if let Some(embed) = setter.embed {
+                        let embed_rs_name = &embed.rs_name;
+                        let value = embed_value(embed, &quote! { (*embed) });
+                        let value = if embed.poly.is_some() {
+                            value
+                        } else {
+                            quote! { ::core::clone::Clone::clone(&#value) }
+                        };
+                        quote! {
+                            ::core::option::Option::map(
+                                ::core::option::Option::as_ref(&request.#embed_rs_name),
+                                |embed| #value,
+                            )
+                        }
+                    } else {
+                        quote! { request.#rs_name }
+                    };
                     quote! {
-                        if let ::core::option::Option::Some(#rs_name) = request.#rs_name {
-                            let response = ::sqlx::query!(#patch_one, #setter, id);
-                            response.execute(&db.pool).await?;
+                        if let ::core::option::Option::Some(#rs_name) = #value {
+                            #patch_one?;
                         }
                     }
                 } else {
+                    let patch_one = execute_query(
+                        &query(&patch_one, [quote! { id }], table.is_checked),
+                        &patch_one,
+                        &table.name_intern,
+                    );
                     quote! {
-                        let response = ::sqlx::query!(#patch_one, id);
-                        response.execute(&db.pool).await?;
+                        #patch_one?;
                     }
                 }
             });
 
             let delete_one = delete_one(&table.name_intern, table_id_name);
+            let delete_one = execute_query(
+                &query(&delete_one, [quote! { id }], table.is_checked),
+                &delete_one,
+                &table.name_intern,
+            );
+
+            let table_name_intern = &*table.name_intern;
+            let get_seeded = get_seeded(&table.seed_table_name_intern);
+            let get_seeded_query = query(
+                &get_seeded,
+                [quote! { #table_name_intern }, quote! { name }],
+                table.is_checked,
+            );
+            let get_seeded = run_query(
+                &quote! { #get_seeded_query.fetch_optional(&db.pool) },
+                &get_seeded,
+                &table.seed_table_name_intern,
+            );
+            let seeded_id =
+                response_field_access("id", &syn::parse_quote!(u64), false, table.is_checked);
+            let create_seeded = create_seeded(&table.seed_table_name_intern);
+            let create_seeded_query = query(
+                &create_seeded,
+                [
+                    quote! { #table_name_intern },
+                    quote! { name },
+                    quote! { id },
+                ],
+                table.is_checked,
+            );
+            // the record and its name are created in the same transaction
+            let create_seeded = run_query(
+                &quote! { #create_seeded_query.execute(&mut *transaction) },
+                &create_seeded,
+                &table.seed_table_name_intern,
+            );
 
             let update_request_setter_collections =
                 request_setter_collections.clone().map(|column| {
@@ -1762,6 +5629,20 @@ impl From<stage3::Table<'_>> for Table {
             let patch_request_validates =
                 update_patch_request_validates.map(|(column, value, validate)| {
                     let rs_name = column.rs_name;
+                    // the embedded struct is patched as a whole
+                    let value = match column.embed {
+                        Some(embed) => {
+                            let embed_rs_name = &embed.rs_name;
+                            let column_rs_name = &embed.column_rs_name;
+                            quote! {
+                                ::core::option::Option::map(
+                                    ::core::option::Option::as_ref(&self.#embed_rs_name),
+                                    |embed| &embed.#column_rs_name,
+                                )
+                            }
+                        }
+                        None => value.clone(),
+                    };
                     if column.is_optional {
                         quote! {
                             if let ::core::option::Option::Some(
@@ -1812,6 +5693,8 @@ impl From<stage3::Table<'_>> for Table {
                     }
                 }
 
+                #( #tree_token_streams )*
+
                 impl ::laraxum::Model for #table_rs_name {
                     type Id = #table_id_rs_ty;
                     type UpdateRequest = #update_request_rs_name;
@@ -1830,6 +5713,70 @@ impl From<stage3::Table<'_>> for Table {
                     {
                         #get_one
                     }
+                    async fn get_one_fields(
+                        db: &Self::Db,
+                        id: Self::Id,
+                        fields: &[&str],
+                    )
+                        -> ::core::result::Result<
+                            Self::Response,
+                            ::laraxum::Error,
+                        >
+                    {
+                        #get_one_fields
+                    }
+                    async fn get_one_batch(
+                        db: &Self::Db,
+                        ids: &[Self::Id],
+                    )
+                        -> ::core::result::Result<
+                            ::std::vec::Vec<::core::option::Option<Self::Response>>,
+                            ::laraxum::Error,
+                        >
+                    {
+                        let response: ::std::vec::Vec<::std::vec::Vec<Self::Response>> =
+                            #get_one_batch?;
+                        let response = ::core::iter::Iterator::map(
+                            ::core::iter::IntoIterator::into_iter(response),
+                            |mut response| response.pop(),
+                        );
+                        ::core::result::Result::Ok(::core::iter::Iterator::collect(response))
+                    }
+                    async fn create_get_id(
+                        db: &Self::Db,
+                        request: Self::CreateRequest,
+                    )
+                        -> ::core::result::Result<
+                            Self::Id,
+                            ::laraxum::ModelError<Self::CreateRequestError>
+                        >
+                    {
+                        let mut transaction = db.pool.begin().await?;
+                        let id = Self::create_get_id_in(db, &mut transaction, request).await?;
+                        transaction.commit().await?;
+                        ::core::result::Result::Ok(id)
+                    }
+                    async fn create_get_id_in(
+                        db: &Self::Db,
+                        transaction: &mut ::laraxum::model::Transaction<Self>,
+                        request: Self::CreateRequest,
+                    )
+                        -> ::core::result::Result<
+                            Self::Id,
+                            ::laraxum::ModelError<Self::CreateRequestError>
+                        >
+                    {
+                        <Self::CreateRequest as ::laraxum::model::request::Request::<
+                                ::laraxum::model::request::method::Create
+                            >
+                        >::validate(&request)?;
+                        let id = #create_in;
+                        let id: Self::Id = ::core::result::Result::map_err(
+                            ::core::convert::TryFrom::try_from(id),
+                            |_| ::laraxum::Error::Internal,
+                        )?;
+                        ::core::result::Result::Ok(id)
+                    }
                     async fn create_get_one(
                         db: &Self::Db,
                         request: Self::CreateRequest,
@@ -1843,13 +5790,16 @@ impl From<stage3::Table<'_>> for Table {
                                 ::laraxum::model::request::method::Create
                             >
                         >::validate(&request)?;
-                        let transaction = db.pool.begin().await?;
-                        let response = #create_one;
-                        let response = response.execute(&db.pool).await?;
-                        let id = response.last_insert_id();
-                        #create_request_setter_collections
+                        let mut transaction = db.pool.begin().await?;
+                        let id = {
+                            let transaction = &mut transaction;
+                            #create_in
+                        };
                         transaction.commit().await?;
-                        let response = Self::get_one(db, id).await?;
+                        let response = Self::get_one(
+                            &::laraxum::model::ReadReplica::primary(db),
+                            id,
+                        ).await?;
                         ::core::result::Result::Ok(response)
                     }
                     async fn update_one(
@@ -1868,8 +5818,7 @@ impl From<stage3::Table<'_>> for Table {
                             >
                         >::validate(&request)?;
                         let transaction = db.pool.begin().await?;
-                        let response = #update_one;
-                        response.execute(&db.pool).await?;
+                        #update_one?;
                         #update_request_setter_collections
                         transaction.commit().await?;
                         ::core::result::Result::Ok(())
@@ -1904,14 +5853,65 @@ impl From<stage3::Table<'_>> for Table {
                             ::laraxum::Error,
                         >
                     {
-                        let response = ::sqlx::query!(#delete_one, id);
                         let transaction = db.pool.begin().await?;
-                        response.execute(&db.pool).await?;
+                        #delete_one?;
                         #delete_request_setter_collections
                         transaction.commit().await?;
                         ::core::result::Result::Ok(())
                     }
                 }
+
+                impl ::laraxum::Seed for #table_rs_name {
+                    async fn get_seeded(
+                        db: &Self::Db,
+                        name: &str,
+                    )
+                        -> ::core::result::Result<
+                            ::core::option::Option<Self::Id>,
+                            ::laraxum::Error,
+                        >
+                    {
+                        let response = #get_seeded?;
+                        let ::core::option::Option::Some(response) = response else {
+                            return ::core::result::Result::Ok(::core::option::Option::None);
+                        };
+                        let id: u64 = #seeded_id;
+                        ::core::result::Result::map_err(
+                            ::core::result::Result::map(
+                                ::core::convert::TryFrom::try_from(id),
+                                ::core::option::Option::Some,
+                            ),
+                            |_| ::laraxum::Error::Internal,
+                        )
+                    }
+                    async fn create_seeded(
+                        db: &Self::Db,
+                        name: &str,
+                        request: Self::CreateRequest,
+                    )
+                        -> ::core::result::Result<
+                            Self::Id,
+                            ::laraxum::ModelError<Self::CreateRequestError>
+                        >
+                    {
+                        <Self::CreateRequest as ::laraxum::model::request::Request::<
+                                ::laraxum::model::request::method::Create
+                            >
+                        >::validate(&request)?;
+                        let mut transaction = db.pool.begin().await?;
+                        let id = {
+                            let transaction = &mut transaction;
+                            #create_in
+                        };
+                        #create_seeded?;
+                        transaction.commit().await?;
+                        let id: Self::Id = ::core::result::Result::map_err(
+                            ::core::convert::TryFrom::try_from(id),
+                            |_| ::laraxum::Error::Internal,
+                        )?;
+                        ::core::result::Result::Ok(id)
+                    }
+                }
             }
         });
 
@@ -1921,11 +5921,15 @@ impl From<stage3::Table<'_>> for Table {
                 .as_deref()
                 .map_or_else(|| quote! { () }, quote::ToTokens::to_token_stream);
 
-            let get_many_request_query = table
-                .aggregate_rs_name
+            // the filter combines the public aggregators, so it is used instead of the aggregator
+            let get_many_aggregate_rs_name = table.filter_rs_name.or(table.aggregate_rs_name);
+            let get_many_request_query = get_many_aggregate_rs_name
                 .map_or_else(|| quote! { () }, quote::ToTokens::to_token_stream);
 
-            let get_many = table.aggregate_rs_name.map(|aggregate_rs_name_rs_name| {
+            let get_many = get_many_aggregate_rs_name.map(|aggregate_rs_name_rs_name| {
+                let aggregate_many = quote! {
+                    <#table_rs_name as ::laraxum::AggregateMany<#aggregate_rs_name_rs_name>>
+                };
                 quote! {
                     async fn get_many(
                         ::axum::extract::State(state):
@@ -1933,16 +5937,112 @@ impl From<stage3::Table<'_>> for Table {
                         ::laraxum::AuthToken(_): ::laraxum::AuthToken<Self::Auth>,
                         ::axum::extract::Query(query):
                             ::axum::extract::Query<Self::GetManyRequestQuery>,
+                        ::axum::extract::Query(mut expand):
+                            ::axum::extract::Query<::laraxum::model::ExpandQuery>,
+                        ::axum::extract::Query(fields):
+                            ::axum::extract::Query<::laraxum::model::FieldsQuery>,
+                        ::axum::extract::RawQuery(raw_query): ::axum::extract::RawQuery,
+                        read_primary: ::laraxum::ReadPrimary,
                     ) -> ::core::result::Result<
-                            ::laraxum::Json<::std::vec::Vec<Self::Response>>,
+                            ::laraxum::model::Page<::laraxum::model::Fields<Self::Response>>,
                             ::laraxum::Error,
                         >
                     {
-                        let records = <
-                            #table_rs_name as ::laraxum::AggregateMany<#aggregate_rs_name_rs_name>
-                        >::aggregate_many(&*state, query).await?;
-                        ::core::result::Result::Ok(::laraxum::Json(records))
+                        fields.validate::<Self>()?;
+                        fields.restrict(&mut expand);
+                        let db: &Self::Db = &state;
+                        let primary = read_primary.primary(db);
+                        let db = ::core::option::Option::unwrap_or(primary.as_ref(), db);
+                        let mut records = match fields.paths() {
+                            ::core::option::Option::Some(paths) => {
+                                #aggregate_many::aggregate_page_fields(db, query, &paths).await?
+                            }
+                            ::core::option::Option::None => {
+                                #aggregate_many::aggregate_page(db, query).await?
+                            }
+                        };
+                        expand.expand::<Self>(db, &mut records.data).await?;
+                        let records = records.with_query(raw_query);
+                        ::core::result::Result::Ok(fields.select_page(records))
+                    }
+                }
+            });
+
+            let relation_routes = table
+                .columns
+                .iter()
+                .filter_map(|column| match column {
+                    stage3::ColumnRef::Collection(collection) => Some(&collection.response),
+                    stage3::ColumnRef::Molecule(_) => None,
+                })
+                .filter(|response| !response.getter.is_children)
+                .map(|response| {
+                    let &stage3::ResponseColumnGetterCollection {
+                        aggregate_rs_name,
+                        many_foreign_table_rs_name,
+                        ..
+                    } = &response.getter;
+                    let name = response
+                        .field
+                        .attr
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| response.field.rs_name.to_string());
+                    let path_many = fmt2::fmt! { { str } => "/" {name} };
+                    let path_many_id = fmt2::fmt! { { str } => "/" {name} "/{many_id}" };
+                    let controller = quote! { <Self as ::laraxum::Controller> };
+                    let generics = quote! { #many_foreign_table_rs_name, #aggregate_rs_name };
+                    quote! {
+                        .route(
+                            &[path, #path_many].concat(),
+                            ::axum::routing::MethodRouter::new()
+                                .get(#controller::get_relation::<#generics>),
+                        )
+                        .route(
+                            &[path, #path_many_id].concat(),
+                            ::axum::routing::MethodRouter::new()
+                                .post(#controller::add_relation::<#generics>)
+                                .delete(#controller::remove_relation::<#generics>),
+                        )
+                    }
+                })
+                .collect::<Vec<_>>();
+            let relation_routes = (!relation_routes.is_empty()).then(|| {
+                quote! {
+                    fn relation_routes(
+                        router: ::axum::routing::Router<::std::sync::Arc<Self::State>>,
+                        path: &str,
+                    ) -> ::axum::routing::Router<::std::sync::Arc<Self::State>> {
+                        router #( #relation_routes )*
+                    }
+                }
+            });
 
+            let stats_route = is_stats(&table).then(|| {
+                quote! {
+                    .route(
+                        &[path, "/stats"].concat(),
+                        ::axum::routing::MethodRouter::new()
+                            .get(<Self as ::laraxum::Controller>::get_stats),
+                    )
+                }
+            });
+            let import_route = is_import(&table).then(|| {
+                quote! {
+                    .route(
+                        &[path, "/import"].concat(),
+                        ::axum::routing::MethodRouter::new()
+                            .post(<Self as ::laraxum::Controller>::import),
+                    )
+                }
+            });
+            let collection_routes = (stats_route.is_some() || import_route.is_some()).then(|| {
+                quote! {
+                    fn collection_routes(
+                        router: ::axum::routing::Router<::std::sync::Arc<Self::State>>,
+                        path: &str,
+                    ) -> ::axum::routing::Router<::std::sync::Arc<Self::State>> {
+                        router #stats_route #import_route
                     }
                 }
             });
@@ -1953,40 +6053,115 @@ impl From<stage3::Table<'_>> for Table {
                     type Auth = #auth;
                     type GetManyRequestQuery = #get_many_request_query;
                     #get_many
+                    #relation_routes
+                    #collection_routes
                 }
             }
         });
 
-        let many_model_token_stream = table.columns.many_model().map(|(a, b)| {
+        let many_model_token_stream = table.columns.many_model().map(|(a, b, pivots)| {
             fn many_model(
                 table: &stage3::Table,
                 one: &stage3::ColumnMolecule,
                 many: &stage3::ColumnMolecule,
+                pivots: &[stage3::Column],
             ) -> proc_macro2::TokenStream {
                 let aggregate_rs_ty = many.struct_name.map_or_else(
                     || one.response.field.rs_ty.to_token_stream(),
-                    |struct_name| struct_name.to_token_stream(),
+                    quote::ToTokens::to_token_stream,
                 );
                 let one_request_rs_ty = one
                     .request
                     .as_ref()
                     .and_then(|request| request.field())
                     .map(|field| &*field.rs_ty);
-                let many_request_rs_ty = many
+                let many_id_rs_ty = many
                     .request
                     .as_ref()
                     .and_then(|request| request.field())
                     .map(|field| &*field.rs_ty);
                 let many_response_rs_ty = many.response.field.rs_ty;
 
+                let pivot_columns = pivots.iter().map(stage3::ColumnRef::from);
+                let is_pivot = !pivots.is_empty();
+                let is_pivot_request = pivot_columns
+                    .clone()
+                    .any(|column| column.request_setter_molecule().is_some());
+                let pivot_rs_name = &*table.pivot_rs_name;
+                let pivot_request_rs_name = &*table.pivot_request_rs_name;
+                let many_response_rs_ty = if is_pivot {
+                    quote! { ::laraxum::model::Pivot<#many_response_rs_ty, #pivot_rs_name> }
+                } else {
+                    many_response_rs_ty.to_token_stream()
+                };
+                let many_request_rs_ty = if is_pivot_request {
+                    quote! {
+                        ::laraxum::model::PivotRequest<#many_id_rs_ty, #pivot_request_rs_name>
+                    }
+                } else {
+                    many_id_rs_ty.to_token_stream()
+                };
+                let (pivot_request_rs_ty, many_request) = if is_pivot_request {
+                    (
+                        pivot_request_rs_name.to_token_stream(),
+                        quote! {
+                            fn many_request(
+                                many: Self::ManyId,
+                                pivot: Self::PivotRequest,
+                            ) -> Self::ManyRequest {
+                                ::laraxum::model::PivotRequest { id: many, pivot }
+                            }
+                        },
+                    )
+                } else {
+                    (
+                        quote! { () },
+                        quote! {
+                            fn many_request(
+                                many: Self::ManyId,
+                                _: Self::PivotRequest,
+                            ) -> Self::ManyRequest {
+                                many
+                            }
+                        },
+                    )
+                };
+
                 let many_response_getter =
                     stage3::ResponseColumnGetterRef::Molecule(&many.response.getter);
+                let many_response_getters = core::iter::once(many_response_getter)
+                    .chain(pivot_columns.clone().map(stage3::ColumnRef::response_getter));
+                let many_getter = |is_checked| {
+                    let many =
+                        response_getter(many_response_getter, false, is_checked, false, None);
+                    if is_pivot {
+                        let pivots = pivot_columns.clone().map(|column| {
+                            let rs_name = column.response_field().rs_name;
+                            let getter = response_getter(
+                                column.response_getter(),
+                                false,
+                                is_checked,
+                                false,
+                                None,
+                            );
+                            quote! { #rs_name: #getter }
+                        });
+                        quote! {
+                            ::laraxum::model::Pivot {
+                                record: #many,
+                                pivot: #pivot_rs_name { #( #pivots ),* },
+                            }
+                        }
+                    } else {
+                        many
+                    }
+                };
 
-                let response_getter = response_getter(many_response_getter, false);
+                let response_getter = many_getter(table.is_checked);
                 let response_getter = response_getter_fn(&response_getter);
 
                 let (response_getter_column_elements, response_getter_column_compounds) =
-                    flatten(core::iter::once(many_response_getter));
+                    flatten(many_response_getters.clone(), table.is_checked);
 
                 let get_many = get_many(
                     &table.name_intern,
@@ -1998,24 +6173,252 @@ impl From<stage3::Table<'_>> for Table {
                     (stage3::ColumnAttrAggregateFilter::Eq, one.name_intern()),
                 );
                 let get_many_response = transform_response_many(
+                    &query(&get_many, [quote! { one }], table.is_checked),
+                    (&get_many, &table.name_intern),
+                    &response_getter,
+                    None,
+                );
+
+                // the ones are bound at runtime, so the rows are always decoded at runtime
+                let batch_response_getter = {
+                    let one = response_field_access(
+                        BATCH_ONE_NAME,
+                        &syn::parse_quote!(Self::OneRequest),
+                        false,
+                        false,
+                    );
+                    let many = many_getter(false);
+                    response_getter_fn(&quote! { (#one, #many) })
+                };
+                let (batch_response_getter_elements, batch_response_getter_compounds) =
+                    flatten(many_response_getters, false);
+                let get_many_batch = get_many_batch(
+                    &table.name_intern,
+                    &table.name_extern,
+                    (
+                        &batch_response_getter_elements,
+                        &batch_response_getter_compounds,
+                    ),
+                    one.name_intern(),
+                );
+                let get_many_batch = transform_response_batch(
+                    &quote! { ones },
+                    (&get_many_batch, &table.name_intern),
+                    &batch_response_getter,
+                    None,
+                );
+
+                let one_name = one.name();
+                let many_name = many.name();
+                let many_id = if is_pivot_request {
+                    quote! { many.id }
+                } else {
+                    quote! { *many }
+                };
+                let pivot_request_columns = pivot_columns
+                    .clone()
+                    .filter_map(stage3::ColumnRef::request_molecule);
+                let pivot_setter = |setter: &stage3::RequestColumnSetterMolecule| {
+                    let rs_name = setter.rs_name;
+                    request_setter(
+                        &quote! { ::core::clone::Clone::clone(&many.pivot.#rs_name) },
+                        setter.is_optional,
+                    )
+                };
+
+                // the rows are pushed at runtime, so they are always bound at runtime
+                let request_columns = [&one.request, &many.request]
+                    .into_iter()
+                    .flatten()
+                    .chain(pivot_request_columns.clone());
+                let create_many = create_many(&table.name_intern, request_columns);
+                let pivot_pushes = pivot_request_columns.clone().map(|column| match column {
+                    stage3::RequestColumnMolecule::Mutable(mutable) => {
+                        let setter = pivot_setter(&mutable.setter);
+                        quote! { row.push_bind(#setter); }
+                    }
+                    stage3::RequestColumnMolecule::OnUpdate(on_update) => {
+                        let current_time_func = on_update.time_ty.current_time_func();
+                        quote! { row.push(#current_time_func); }
+                    }
+                });
+                let pivot_pushes = quote! { #( #pivot_pushes )* };
+                let create_many = |many: &proc_macro2::TokenStream,
+                                   executor: &proc_macro2::TokenStream| {
+                    let response = run_query(
+                        &quote! { response.build().execute(#executor) },
+                        &create_many,
+                        &table.name_intern,
+                    );
+                    // a value is only linked once and the rows are pushed in batches,
+                    // so a query never has too many parameters
+                    quote! {
+                        if !#many.is_empty() {
+                            /// How many rows are created with one query.
+                            const BATCH: usize = 500;
+
+                            let mut unique = ::std::collections::HashSet::<Self::ManyId>::new();
+                            let many: ::std::vec::Vec<&Self::ManyRequest> =
+                                ::core::iter::Iterator::collect(::core::iter::Iterator::filter(
+                                    ::core::iter::IntoIterator::into_iter(#many),
+                                    |&many| unique.insert(#many_id),
+                                ));
+                            for many in many.chunks(BATCH) {
+                                let mut response = ::sqlx::QueryBuilder::new(#create_many);
+                                response.push_values(many.iter().copied(), |mut row, many| {
+                                    row.push_bind(::laraxum::model::types::Encode::encode(one));
+                                    row.push_bind(
+                                        ::laraxum::model::types::Encode::encode(#many_id),
+                                    );
+                                    #pivot_pushes
+                                });
+                                #response?;
+                            }
+                        }
+                    }
+                };
+
+                let delete_many_values =
+                    delete_many_values(&table.name_intern, one_name, many_name);
+                let delete_many_values = |many: &proc_macro2::TokenStream,
+                                          executor: &proc_macro2::TokenStream| {
+                    let response = run_query(
+                        &quote! { response.build().execute(#executor) },
+                        &delete_many_values,
+                        &table.name_intern,
+                    );
+                    quote! {
+                        if !#many.is_empty() {
+                            /// How many rows are deleted with one query.
+                            const BATCH: usize = 500;
+
+                            for many in #many.chunks(BATCH) {
+                                let mut response =
+                                    ::sqlx::QueryBuilder::new(#delete_many_values);
+                                response.push_tuples(many.iter(), |mut row, many| {
+                                    row.push_bind(::laraxum::model::types::Encode::encode(one));
+                                    row.push_bind(::laraxum::model::types::Encode::encode(*many));
+                                });
+                                #response?;
+                            }
+                        }
+                    }
+                };
+
+                let get_many_values = get_many_values(&table.name_intern, one_name, many_name);
+                let get_many_values = run_query(
                     &quote! {
-                        ::sqlx::query!(#get_many, one)
+                        ::sqlx::query(#get_many_values)
+                            .bind(::laraxum::model::types::Encode::encode(one))
+                            .fetch_all(&mut *transaction)
                     },
-                    &response_getter,
+                    &get_many_values,
+                    &table.name_intern,
                 );
 
-                let request_columns = [&one.request, &many.request].into_iter().flatten();
-                let create_one = create_one(&table.name_intern, request_columns);
+                let update_pivots = if is_pivot_request {
+                    let update_pivot = update_pivot(
+                        &table.name_intern,
+                        one_name,
+                        many_name,
+                        pivot_request_columns.clone(),
+                    );
+                    let update_pivot_setters = pivot_request_columns
+                        .clone()
+                        .filter_map(|column| column.setter())
+                        .map(pivot_setter)
+                        .chain([quote! { one }, many_id.clone()]);
+                    let update_pivot_query =
+                        query(&update_pivot, update_pivot_setters, table.is_checked);
+                    let update_pivot = run_query(
+                        &quote! { #update_pivot_query.execute(&mut *transaction) },
+                        &update_pivot,
+                        &table.name_intern,
+                    );
+                    quote! {
+                        for many in kept {
+                            #update_pivot?;
+                        }
+                    }
+                } else {
+                    quote! { let _ = kept; }
+                };
+
+                // create the new values and update the pivot columns of the existing values
+                let add_many = |remove: Option<proc_macro2::TokenStream>| {
+                    let create_many =
+                        create_many(&quote! { added }, &quote! { &mut *transaction });
+                    quote! {
+                        let mut transaction = db.pool.begin().await?;
+                        let existing = #get_many_values?;
+                        let existing = ::core::iter::Iterator::map(existing.iter(), |row| {
+                            ::core::result::Result::map(
+                                ::sqlx::Row::try_get::<
+                                    <Self::ManyId as ::laraxum::model::types::Decode>::Decode,
+                                    _,
+                                >(row, 0),
+                                ::laraxum::model::types::Decode::decode,
+                            )
+                        });
+                        let existing: ::std::vec::Vec<Self::ManyId> =
+                            ::core::iter::Iterator::collect::<::core::result::Result<_, _>>(
+                                existing,
+                            )?;
+                        #remove
+                        let existing: ::std::collections::HashSet<Self::ManyId> =
+                            ::core::iter::Iterator::collect(
+                                ::core::iter::IntoIterator::into_iter(existing),
+                            );
+                        let (kept, added): (
+                            ::std::vec::Vec<&Self::ManyRequest>,
+                            ::std::vec::Vec<&Self::ManyRequest>,
+                        ) = ::core::iter::Iterator::partition(many.iter(), |&many| {
+                            existing.contains(&#many_id)
+                        });
+                        #update_pivots
+                        #create_many
+                        transaction.commit().await?;
+                        ::core::result::Result::Ok(())
+                    }
+                };
+                let remove =
+                    delete_many_values(&quote! { removed }, &quote! { &mut *transaction });
+                let update_many = add_many(Some(quote! {
+                    let requested: ::std::collections::HashSet<Self::ManyId> =
+                        ::core::iter::Iterator::collect(
+                            ::core::iter::Iterator::map(many.iter(), |many| #many_id),
+                        );
+                    let removed: ::std::vec::Vec<Self::ManyId> = ::core::iter::Iterator::collect(
+                        ::core::iter::Iterator::filter(existing.iter().copied(), |many| {
+                            !requested.contains(many)
+                        }),
+                    );
+                    #remove
+                }));
+                let add_many = add_many(None);
+                let create_many_in = create_many(&quote! { many }, &quote! { &mut **transaction });
+                let create_many = create_many(&quote! { many }, &quote! { &db.pool });
+                let remove_many = delete_many_values(&quote! { many }, &quote! { &db.pool });
+
                 let delete_many = delete_one(&table.name_intern, one.name());
+                let delete_many = execute_query(
+                    &query(&delete_many, [quote! { one }], table.is_checked),
+                    &delete_many,
+                    &table.name_intern,
+                );
 
                 let table_rs_name = table.rs_name;
 
                 quote! {
                     impl ::laraxum::ManyModel<#aggregate_rs_ty> for #table_rs_name {
                         type OneRequest = #one_request_rs_ty;
+                        type ManyId = #many_id_rs_ty;
                         type ManyRequest = #many_request_rs_ty;
+                        type PivotRequest = #pivot_request_rs_ty;
                         type ManyResponse = #many_response_rs_ty;
 
+                        #many_request
+
                         async fn get_many(
                             db: &Self::Db,
                             one: Self::OneRequest,
@@ -2027,6 +6430,17 @@ impl From<stage3::Table<'_>> for Table {
                         {
                             #get_many_response
                         }
+                        async fn get_many_batch(
+                            db: &Self::Db,
+                            ones: &[Self::OneRequest],
+                        )
+                            -> ::core::result::Result<
+                                ::std::vec::Vec<::std::vec::Vec<Self::ManyResponse>>,
+                                ::laraxum::Error,
+                            >
+                        {
+                            #get_many_batch
+                        }
                         async fn create_many(
                             db: &Self::Db,
                             one: Self::OneRequest,
@@ -2037,10 +6451,21 @@ impl From<stage3::Table<'_>> for Table {
                                 ::laraxum::Error,
                             >
                         {
-                            for many in many {
-                                let response = ::sqlx::query!(#create_one, one, many);
-                                response.execute(&db.pool).await?;
-                            }
+                            #create_many
+                            ::core::result::Result::Ok(())
+                        }
+                        async fn create_many_in(
+                            db: &Self::Db,
+                            transaction: &mut ::laraxum::model::Transaction<Self>,
+                            one: Self::OneRequest,
+                            many: &[Self::ManyRequest],
+                        )
+                            -> ::core::result::Result<
+                                (),
+                                ::laraxum::Error,
+                            >
+                        {
+                            #create_many_in
                             ::core::result::Result::Ok(())
                         }
                         async fn update_many(
@@ -2053,12 +6478,31 @@ impl From<stage3::Table<'_>> for Table {
                                 ::laraxum::Error,
                             >
                         {
-                            <
-                                Self as ::laraxum::ManyModel<#aggregate_rs_ty>
-                            >::delete_many(db, one).await?;
-                            <
-                                Self as ::laraxum::ManyModel<#aggregate_rs_ty>
-                            >::create_many(db, one, many).await?;
+                            #update_many
+                        }
+                        async fn add_many(
+                            db: &Self::Db,
+                            one: Self::OneRequest,
+                            many: &[Self::ManyRequest],
+                        )
+                            -> ::core::result::Result<
+                                (),
+                                ::laraxum::Error,
+                            >
+                        {
+                            #add_many
+                        }
+                        async fn remove_many(
+                            db: &Self::Db,
+                            one: Self::OneRequest,
+                            many: &[Self::ManyId],
+                        )
+                            -> ::core::result::Result<
+                                (),
+                                ::laraxum::Error,
+                            >
+                        {
+                            #remove_many
                             ::core::result::Result::Ok(())
                         }
                         async fn delete_many(
@@ -2070,17 +6514,70 @@ impl From<stage3::Table<'_>> for Table {
                                 ::laraxum::Error,
                             >
                         {
-                            let response = ::sqlx::query!(#delete_many, one);
-                            response.execute(&db.pool).await?;
+                            #delete_many?;
                             ::core::result::Result::Ok(())
                         }
                     }
                 }
             }
 
-            let a_token_stream = many_model(&table, a, b);
-            let b_token_stream = many_model(&table, b, a);
+            let a_token_stream = many_model(&table, a, b, pivots);
+            let b_token_stream = many_model(&table, b, a, pivots);
+
+            let pivot_token_stream = (!pivots.is_empty()).then(|| {
+                let pivot_rs_name = &*table.pivot_rs_name;
+                let pivot_request_rs_name = &*table.pivot_request_rs_name;
+                let pivot_columns = pivots.iter().map(stage3::ColumnRef::from);
+                let pivot_response_fields = pivot_columns
+                    .clone()
+                    .map(|column| response_field(column.response_field()));
+                let pivot_request_fields = pivot_columns
+                    .filter_map(stage3::ColumnRef::request_field)
+                    .map(|field| {
+                        request_field(
+                            field.rs_name,
+                            &*field.rs_ty,
+                            field.attr.name.as_deref(),
+                            field.rs_attrs,
+                        )
+                    });
+
+                // the foreign records can be converted into the records without the pivot columns
+                let a_rs_ty = a.response.field.rs_ty;
+                let b_rs_ty = b.response.field.rs_ty;
+                let mut record_rs_tys = vec![a_rs_ty];
+                if a_rs_ty != b_rs_ty {
+                    record_rs_tys.push(b_rs_ty);
+                }
+                let from_pivots = record_rs_tys.into_iter().map(|record_rs_ty| {
+                    quote! {
+                        impl ::core::convert::From<
+                            ::laraxum::model::Pivot<#record_rs_ty, #pivot_rs_name>
+                        > for #record_rs_ty {
+                            fn from(
+                                pivot: ::laraxum::model::Pivot<#record_rs_ty, #pivot_rs_name>,
+                            ) -> Self {
+                                pivot.record
+                            }
+                        }
+                    }
+                });
+
+                quote! {
+                    #[derive(::serde::Serialize)]
+                    pub struct #pivot_rs_name {
+                        #( #pivot_response_fields ),*
+                    }
+                    #[derive(::serde::Deserialize)]
+                    pub struct #pivot_request_rs_name {
+                        #( #pivot_request_fields )*
+                    }
+                    #( #from_pivots )*
+                }
+            });
+
             quote! {
+                #pivot_token_stream
                 #a_token_stream
                 #b_token_stream
             }
@@ -2089,7 +6586,7 @@ impl From<stage3::Table<'_>> for Table {
         let structs = table
             .columns
             .iter()
-            .filter_map(|column| column.struct_name())
+            .filter_map(stage3::ColumnRef::struct_name)
             .map(|struct_name| {
                 quote! {
                     pub struct #struct_name;
@@ -2120,13 +6617,20 @@ impl From<stage3::Db<'_>> for Db {
 
         let tables_token_stream = tables.iter().map(|table| &table.token_stream);
 
+        let embeds_token_stream = db.embeds.iter().map(embed_struct);
+
+        let create_seed_table = create_seed_table(&db.seed_table_name_intern);
+        let delete_seed_table = delete_table(&db.seed_table_name_intern);
+
         let migration_up = fmt2::fmt! { { str } =>
             "BEGIN TRANSACTION;"
             @..(tables.iter() => |table| {table.migration_up})
+            {create_seed_table}
             "COMMIT;"
         };
         let migration_down = fmt2::fmt! { { str } =>
             "BEGIN TRANSACTION;"
+            {delete_seed_table}
             @..(tables.iter().rev() => |table| {table.migration_down})
             "COMMIT;"
         };
@@ -2162,18 +6666,90 @@ impl From<stage3::Db<'_>> for Db {
             }
         };
 
+        let db_token_stream = db_struct(db_ident, &db_pool_type, &migration_up_full);
+
+        quote! {
+            #db_token_stream
+
+            #(#embeds_token_stream)*
+
+            #(#tables_token_stream)*
+        }
+    }
+}
+
+/// An embedded struct, whose columns are flattened into the tables with a field of it.
+fn embed_struct(embed: &stage3::Embed) -> proc_macro2::TokenStream {
+    let stage3::Embed {
+        rs_name,
+        columns,
+        rs_attrs,
+    } = embed;
+    let fields = columns.iter().map(|column| {
+        let rs_name = &column.rs_name;
+        let rs_ty = &column.rs_ty;
+        let rs_attrs = &column.attr.attrs;
         quote! {
-            /// ```sql
-            #[doc = #migration_up_full]
-            /// ```
-            pub struct #db_ident {
-                pub pool: ::sqlx::Pool<#db_pool_type>,
+            #( #rs_attrs )*
+            pub #rs_name: #rs_ty
+        }
+    });
+    quote! {
+        #[derive(::serde::Serialize, ::serde::Deserialize)]
+        #( #rs_attrs )*
+        pub struct #rs_name {
+            #( #fields ),*
+        }
+    }
+}
+
+/// The database struct with its pools, and how to connect to it.
+fn db_struct(
+    db_ident: &Ident,
+    db_pool_type: &proc_macro2::TokenStream,
+    migration_up_full: &str,
+) -> proc_macro2::TokenStream {
+    quote! {
+        /// ```sql
+        #[doc = #migration_up_full]
+        /// ```
+        pub struct #db_ident {
+            /// The primary database, used for writing.
+            pub pool: ::sqlx::Pool<#db_pool_type>,
+            /// The read replica, used for reading if there is one.
+            pub replica: ::core::option::Option<::sqlx::Pool<#db_pool_type>>,
+            /// The timeout and logging of queries.
+            pub query: ::laraxum::model::QueryOptions,
+        }
+
+        impl #db_ident {
+            /// The pool used for reading, the read replica if there is one.
+            pub fn read_pool(&self) -> &::sqlx::Pool<#db_pool_type> {
+                ::core::option::Option::unwrap_or(self.replica.as_ref(), &self.pool)
+            }
+        }
+
+        impl ::laraxum::model::ReadReplica for #db_ident {
+            fn primary(&self) -> Self {
+                Self {
+                    pool: ::core::clone::Clone::clone(&self.pool),
+                    replica: ::core::option::Option::None,
+                    query: ::core::clone::Clone::clone(&self.query),
+                }
             }
+        }
+
+        impl ::laraxum::model::Transact for #db_ident {
+            type Database = #db_pool_type;
+        }
 
-            impl ::laraxum::Connect for #db_ident {
-                type Error = ::sqlx::Error;
-                async fn connect() -> ::core::result::Result<Self, Self::Error> {
-                    let connect_options = ::laraxum::model::database_url()
+        impl ::laraxum::Connect for #db_ident {
+            type Error = ::sqlx::Error;
+            async fn connect_with(
+                options: ::laraxum::model::ConnectOptions,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                let connect = async |url: ::core::option::Option<&str>| {
+                    let mut connect_options = url
                         .map(|url| {
                             <
                                 <
@@ -2181,24 +6757,44 @@ impl From<stage3::Db<'_>> for Db {
                                         #db_pool_type as ::sqlx::Database
                                     >::Connection as ::sqlx::Connection
                                 >::Options as ::core::str::FromStr
-                            >::from_str(&url)
+                            >::from_str(url)
                         })
                         .transpose()?
                         .unwrap_or_default();
-                    let pool_options = ::sqlx::pool::PoolOptions::<#db_pool_type>::new();
-                    let pool = pool_options.connect_with(connect_options).await?;
-                    ::core::result::Result::Ok(Self { pool })
-                }
+                    if let ::core::option::Option::Some(capacity) =
+                        options.statement_cache_capacity
+                    {
+                        connect_options = connect_options.statement_cache_capacity(capacity);
+                    }
+                    let pool_options = options.pool_options::<#db_pool_type>();
+                    options
+                        .retry
+                        .run(|| {
+                            ::core::clone::Clone::clone(&pool_options)
+                                .connect_with(::core::clone::Clone::clone(&connect_options))
+                        })
+                        .await
+                };
+                let pool = connect(options.url.as_deref()).await?;
+                let replica = match options.read_url.as_deref() {
+                    ::core::option::Option::Some(read_url) => {
+                        ::core::option::Option::Some(connect(::core::option::Option::Some(read_url)).await?)
+                    }
+                    ::core::option::Option::None => ::core::option::Option::None,
+                };
+                ::core::result::Result::Ok(Self {
+                    pool,
+                    replica,
+                    query: options.query,
+                })
             }
+        }
 
-            impl ::core::ops::Deref for #db_ident {
-                type Target = Self;
-                fn deref(&self) -> &Self::Target {
-                    self
-                }
+        impl ::core::ops::Deref for #db_ident {
+            type Target = Self;
+            fn deref(&self) -> &Self::Target {
+                self
             }
-
-            #(#tables_token_stream)*
         }
     }
 }
@@ -19,15 +19,78 @@ impl Parse for MethodRoute {
     }
 }
 
+pub struct ChildrenRoute {
+    pub path: Ident,
+    pub ty: TypePath,
+    pub aggregate_ty: Option<TypePath>,
+}
+impl Parse for ChildrenRoute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse::<Ident>()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse::<TypePath>()?;
+        let aggregate_ty = if input.peek(Token![as]) {
+            input.parse::<Token![as]>()?;
+            Some(input.parse::<TypePath>()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            path,
+            ty,
+            aggregate_ty,
+        })
+    }
+}
+
+/// The tree routes of a table with a foreign key column to the same table.
+pub struct TreeRoute {
+    pub aggregate_ty: Option<TypePath>,
+}
+
+pub enum ControllerRoute {
+    Children(ChildrenRoute),
+    Tree(TreeRoute),
+}
+impl Parse for ControllerRoute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let is_tree = input
+            .fork()
+            .parse::<Ident>()
+            .is_ok_and(|ident| ident == "tree")
+            && !input.peek2(Token![:]);
+        if is_tree {
+            input.parse::<Ident>()?;
+            let aggregate_ty = if input.peek(Token![as]) {
+                input.parse::<Token![as]>()?;
+                Some(input.parse::<TypePath>()?)
+            } else {
+                None
+            };
+            Ok(Self::Tree(TreeRoute { aggregate_ty }))
+        } else {
+            input.parse::<ChildrenRoute>().map(Self::Children)
+        }
+    }
+}
+
 pub enum MethodRouter {
     MethodRoutes(Vec<MethodRoute>),
-    Controller(TypePath),
+    Controller(TypePath, Vec<ControllerRoute>),
 }
 impl Parse for MethodRouter {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         input.parse::<Token![use]>()?;
         let method_router = if let Ok(ty) = input.parse::<TypePath>() {
-            Self::Controller(ty)
+            let controller_routes = if input.peek(syn::token::Brace) {
+                let content = parse_curly_brackets(input)?;
+                let controller_routes =
+                    Punctuated::<ControllerRoute, Token![,]>::parse_terminated(&content)?;
+                controller_routes.into_iter().collect()
+            } else {
+                vec![]
+            };
+            Self::Controller(ty, controller_routes)
         } else {
             let content = parse_curly_brackets(input)?;
             let method_routes = Punctuated::<MethodRoute, Token![,]>::parse_terminated(&content)?;
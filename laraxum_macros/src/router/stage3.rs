@@ -1,7 +1,8 @@
-use super::stage2;
+use super::{stage1, stage2};
 
 pub use proc_macro2::TokenStream as Router;
 use quote::quote;
+use syn::TypePath;
 impl From<&stage2::Router<'_>> for Router {
     fn from(router: &stage2::Router) -> Self {
         // routes are added in reverse because of ownership rules so we reverse it to get the original order again
@@ -27,29 +28,102 @@ impl From<&stage2::Router<'_>> for Router {
                                 )
                         }
                     }
-                    stage2::MethodRouter::Controller(ty) => {
-                        let path_id = fmt2::fmt! { { str } => {path} "/{id}" };
-                        quote! {
-                            #token_stream
-                                .route(
-                                    #path,
-                                    ::axum::routing::MethodRouter::new()
-                                        .get(<#ty as ::laraxum::Controller>::get_many)
-                                        .post(<#ty as ::laraxum::Controller>::create),
-                                )
-                                .route(
-                                    #path_id,
-                                    ::axum::routing::MethodRouter::new()
-                                        .get(<#ty as ::laraxum::Controller>::get)
-                                        .put(<#ty as ::laraxum::Controller>::update)
-                                        .patch(<#ty as ::laraxum::Controller>::patch)
-                                        .delete(<#ty as ::laraxum::Controller>::delete),
-                                )
-
-                        }
+                    stage2::MethodRouter::Controller(ty, controller_routes) => {
+                        controller(&token_stream, path, ty, controller_routes)
                     }
                 }
             },
         )
     }
 }
+
+/// The routes of a controller, and the routes of its relations.
+fn controller(
+    token_stream: &Router,
+    path: &str,
+    ty: &TypePath,
+    controller_routes: &[stage1::ControllerRoute],
+) -> Router {
+    let path_id = fmt2::fmt! { { str } => {path} "/{id}" };
+    let path_stream = fmt2::fmt! { { str } => {path} "/stream" };
+    let controller_routes = controller_routes
+        .iter()
+        .map(|controller_route| self::controller_route(ty, &path_id, controller_route));
+    let token_stream = quote! {
+        <#ty as ::laraxum::Controller>::relation_routes(
+            #token_stream
+            .route(
+                #path,
+                ::axum::routing::MethodRouter::new()
+                    .get(<#ty as ::laraxum::Controller>::get_many_or_export)
+                    .post(<#ty as ::laraxum::Controller>::create),
+            )
+            .route(
+                #path_stream,
+                ::axum::routing::MethodRouter::new()
+                    .get(<#ty as ::laraxum::Controller>::stream_many),
+            )
+            .route(
+                #path_id,
+                ::axum::routing::MethodRouter::new()
+                    .get(<#ty as ::laraxum::Controller>::get)
+                    .put(<#ty as ::laraxum::Controller>::update)
+                    .patch(<#ty as ::laraxum::Controller>::patch)
+                    .delete(<#ty as ::laraxum::Controller>::delete),
+            )
+            #( #controller_routes )*,
+            #path_id,
+        )
+    };
+    quote! {
+        <#ty as ::laraxum::Controller>::collection_routes(
+            #token_stream,
+            #path,
+        )
+    }
+}
+
+/// The routes of the children or the tree of a record, after the path with its ID.
+fn controller_route(
+    ty: &TypePath,
+    path_id: &str,
+    controller_route: &stage1::ControllerRoute,
+) -> Router {
+    match controller_route {
+        stage1::ControllerRoute::Children(stage1::ChildrenRoute {
+            path: children_path,
+            ty: children_ty,
+            aggregate_ty,
+        }) => {
+            let path_children = fmt2::fmt! { { str } => {path_id} "/" {children_path.to_string()} };
+            let aggregate_ty = aggregate_ty.as_ref().unwrap_or(ty);
+            quote! {
+                .route(
+                    #path_children,
+                    ::axum::routing::MethodRouter::new()
+                        .get(<#ty as ::laraxum::Controller>::get_children::<
+                            #children_ty,
+                            #aggregate_ty,
+                        >),
+                )
+            }
+        }
+        stage1::ControllerRoute::Tree(stage1::TreeRoute { aggregate_ty }) => {
+            let aggregate_ty = aggregate_ty.as_ref().unwrap_or(ty);
+            let routes = ["ancestors", "descendants", "subtree"].map(|name| {
+                let path_tree = fmt2::fmt! { { str } => {path_id} "/" {name} };
+                let f = quote::format_ident!("get_{}", name);
+                quote! {
+                    .route(
+                        #path_tree,
+                        ::axum::routing::MethodRouter::new()
+                            .get(<#ty as ::laraxum::Controller>::#f::<
+                                #aggregate_ty,
+                            >),
+                    )
+                }
+            });
+            quote! { #( #routes )* }
+        }
+    }
+}